@@ -0,0 +1,347 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::collections::HashMap;
+use std::ffi::{
+  CStr,
+  CString,
+};
+use std::ptr::null_mut;
+
+use libc::{
+  c_char,
+  c_int,
+  c_void,
+};
+
+use ::error::ErrorTrap;
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+// Memoizes atoms in both directions for one display connection. The
+// predefined XA_* atoms are known up front and never cost a round trip.
+pub struct AtomCache {
+  display: *mut Display,
+  by_name: HashMap<String, Atom>,
+  by_atom: HashMap<Atom, String>,
+}
+
+impl AtomCache {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the cache. A null
+  /// display only works for as long as nothing but the predefined atoms is
+  /// looked up.
+  pub unsafe fn new (display: *mut Display) -> AtomCache {
+    let mut cache = AtomCache {
+      display,
+      by_name: HashMap::new(),
+      by_atom: HashMap::new(),
+    };
+    for &(name, atom) in PREDEFINED_ATOMS.iter() {
+      cache.insert(name.to_string(), atom);
+    }
+    cache
+  }
+
+  /// Creates a cache and interns `names` with a single round trip.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the cache.
+  pub unsafe fn with_names (display: *mut Display, names: &[&str]) -> AtomCache {
+    let mut cache = AtomCache::new(display);
+    cache.intern_all(names);
+    cache
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  /// Interns every name not already cached with one XInternAtoms request.
+  /// Returns false if the request failed or a name contains a NUL byte.
+  ///
+  /// # Safety
+  ///
+  /// The cache's display must still be open.
+  pub unsafe fn intern_all (&mut self, names: &[&str]) -> bool {
+    let mut missing: Vec<&str> = names.iter().cloned().filter(|name| !self.by_name.contains_key(*name)).collect();
+    missing.sort();
+    missing.dedup();
+    if missing.is_empty() {
+      return true;
+    }
+
+    let cnames: Vec<CString> = match missing.iter().map(|&name| CString::new(name)).collect() {
+      Ok(cnames) => cnames,
+      Err(_) => { return false; },
+    };
+    let mut pointers: Vec<*mut c_char> = cnames.iter().map(|name| name.as_ptr() as *mut c_char).collect();
+    let mut atoms: Vec<Atom> = vec![0; missing.len()];
+    if XInternAtoms(self.display, pointers.as_mut_ptr(), pointers.len() as c_int, False, atoms.as_mut_ptr()) == 0 {
+      return false;
+    }
+
+    for (&name, &atom) in missing.iter().zip(atoms.iter()) {
+      if atom != 0 {
+        self.insert(name.to_string(), atom);
+      }
+    }
+    true
+  }
+
+  /// Returns the atom for `name`, interning it if it isn't cached yet.
+  /// Returns 0 (None) if the name contains a NUL byte.
+  ///
+  /// # Safety
+  ///
+  /// The cache's display must still be open.
+  pub unsafe fn atom (&mut self, name: &str) -> Atom {
+    if let Some(&atom) = self.by_name.get(name) {
+      return atom;
+    }
+
+    let cname = match CString::new(name) {
+      Ok(cname) => cname,
+      Err(_) => { return 0; },
+    };
+    let atom = XInternAtom(self.display, cname.as_ptr(), False);
+    if atom != 0 {
+      self.insert(name.to_string(), atom);
+    }
+    atom
+  }
+
+  // The cached atom for `name`, without querying the server.
+  pub fn get (&self, name: &str) -> Option<Atom> {
+    self.by_name.get(name).cloned()
+  }
+
+  // The cached name of `atom`, without querying the server.
+  pub fn get_name (&self, atom: Atom) -> Option<&str> {
+    self.by_atom.get(&atom).map(|name| name.as_str())
+  }
+
+  /// Returns the name of `atom`, asking the server if it isn't cached. An
+  /// unknown atom yields None; its BadAtom error is trapped.
+  ///
+  /// # Safety
+  ///
+  /// The cache's display must still be open.
+  pub unsafe fn name (&mut self, atom: Atom) -> Option<String> {
+    if let Some(name) = self.by_atom.get(&atom) {
+      return Some(name.clone());
+    }
+
+    let trap = ErrorTrap::new(self.display);
+    let name = XGetAtomName(self.display, atom);
+    drop(trap);
+    if name.is_null() {
+      return None;
+    }
+    let owned = take_string(name);
+    self.insert(owned.clone(), atom);
+    Some(owned)
+  }
+
+  /// Looks up the names of `atoms`, fetching all uncached ones with a single
+  /// XGetAtomNames request. Unknown atoms yield None; their BadAtom errors
+  /// are trapped.
+  ///
+  /// # Safety
+  ///
+  /// The cache's display must still be open.
+  pub unsafe fn names (&mut self, atoms: &[Atom]) -> Vec<Option<String>> {
+    let mut missing: Vec<Atom> = atoms.iter().cloned().filter(|atom| !self.by_atom.contains_key(atom)).collect();
+    missing.sort();
+    missing.dedup();
+
+    if !missing.is_empty() {
+      let mut names: Vec<*mut c_char> = vec![null_mut(); missing.len()];
+      let trap = ErrorTrap::new(self.display);
+      let status = XGetAtomNames(self.display, missing.as_mut_ptr(), missing.len() as c_int, names.as_mut_ptr());
+      drop(trap);
+
+      // A failed request still fills in the names it did get; the unknown
+      // atoms are left null and stay uncached.
+      for (&atom, &name) in missing.iter().zip(names.iter()) {
+        if !name.is_null() {
+          self.insert(take_string(name), atom);
+        } else {
+          debug_assert!(status == 0, "XGetAtomNames succeeded without naming atom {}", atom);
+        }
+      }
+    }
+
+    atoms.iter().map(|atom| self.by_atom.get(atom).cloned()).collect()
+  }
+
+  fn insert (&mut self, name: String, atom: Atom) {
+    self.by_atom.insert(atom, name.clone());
+    self.by_name.insert(name, atom);
+  }
+}
+
+
+//
+// functions
+//
+
+
+pub fn predefined_atom (name: &str) -> Option<Atom> {
+  PREDEFINED_ATOMS.iter().find(|&&(predefined, _)| predefined == name).map(|&(_, atom)| atom)
+}
+
+pub fn predefined_name (atom: Atom) -> Option<&'static str> {
+  PREDEFINED_ATOMS.iter().find(|&&(_, predefined)| predefined == atom).map(|&(name, _)| name)
+}
+
+
+//
+// constants
+//
+
+
+// The atoms every server defines, from Xatom.h.
+pub const PREDEFINED_ATOMS: [(&str, Atom); 68] = [
+  ("PRIMARY", XA_PRIMARY),
+  ("SECONDARY", XA_SECONDARY),
+  ("ARC", XA_ARC),
+  ("ATOM", XA_ATOM),
+  ("BITMAP", XA_BITMAP),
+  ("CARDINAL", XA_CARDINAL),
+  ("COLORMAP", XA_COLORMAP),
+  ("CURSOR", XA_CURSOR),
+  ("CUT_BUFFER0", XA_CUT_BUFFER0),
+  ("CUT_BUFFER1", XA_CUT_BUFFER1),
+  ("CUT_BUFFER2", XA_CUT_BUFFER2),
+  ("CUT_BUFFER3", XA_CUT_BUFFER3),
+  ("CUT_BUFFER4", XA_CUT_BUFFER4),
+  ("CUT_BUFFER5", XA_CUT_BUFFER5),
+  ("CUT_BUFFER6", XA_CUT_BUFFER6),
+  ("CUT_BUFFER7", XA_CUT_BUFFER7),
+  ("DRAWABLE", XA_DRAWABLE),
+  ("FONT", XA_FONT),
+  ("INTEGER", XA_INTEGER),
+  ("PIXMAP", XA_PIXMAP),
+  ("POINT", XA_POINT),
+  ("RECTANGLE", XA_RECTANGLE),
+  ("RESOURCE_MANAGER", XA_RESOURCE_MANAGER),
+  ("RGB_COLOR_MAP", XA_RGB_COLOR_MAP),
+  ("RGB_BEST_MAP", XA_RGB_BEST_MAP),
+  ("RGB_BLUE_MAP", XA_RGB_BLUE_MAP),
+  ("RGB_DEFAULT_MAP", XA_RGB_DEFAULT_MAP),
+  ("RGB_GRAY_MAP", XA_RGB_GRAY_MAP),
+  ("RGB_GREEN_MAP", XA_RGB_GREEN_MAP),
+  ("RGB_RED_MAP", XA_RGB_RED_MAP),
+  ("STRING", XA_STRING),
+  ("VISUALID", XA_VISUALID),
+  ("WINDOW", XA_WINDOW),
+  ("WM_COMMAND", XA_WM_COMMAND),
+  ("WM_HINTS", XA_WM_HINTS),
+  ("WM_CLIENT_MACHINE", XA_WM_CLIENT_MACHINE),
+  ("WM_ICON_NAME", XA_WM_ICON_NAME),
+  ("WM_ICON_SIZE", XA_WM_ICON_SIZE),
+  ("WM_NAME", XA_WM_NAME),
+  ("WM_NORMAL_HINTS", XA_WM_NORMAL_HINTS),
+  ("WM_SIZE_HINTS", XA_WM_SIZE_HINTS),
+  ("WM_ZOOM_HINTS", XA_WM_ZOOM_HINTS),
+  ("MIN_SPACE", XA_MIN_SPACE),
+  ("NORM_SPACE", XA_NORM_SPACE),
+  ("MAX_SPACE", XA_MAX_SPACE),
+  ("END_SPACE", XA_END_SPACE),
+  ("SUPERSCRIPT_X", XA_SUPERSCRIPT_X),
+  ("SUPERSCRIPT_Y", XA_SUPERSCRIPT_Y),
+  ("SUBSCRIPT_X", XA_SUBSCRIPT_X),
+  ("SUBSCRIPT_Y", XA_SUBSCRIPT_Y),
+  ("UNDERLINE_POSITION", XA_UNDERLINE_POSITION),
+  ("UNDERLINE_THICKNESS", XA_UNDERLINE_THICKNESS),
+  ("STRIKEOUT_ASCENT", XA_STRIKEOUT_ASCENT),
+  ("STRIKEOUT_DESCENT", XA_STRIKEOUT_DESCENT),
+  ("ITALIC_ANGLE", XA_ITALIC_ANGLE),
+  ("X_HEIGHT", XA_X_HEIGHT),
+  ("QUAD_WIDTH", XA_QUAD_WIDTH),
+  ("WEIGHT", XA_WEIGHT),
+  ("POINT_SIZE", XA_POINT_SIZE),
+  ("RESOLUTION", XA_RESOLUTION),
+  ("COPYRIGHT", XA_COPYRIGHT),
+  ("NOTICE", XA_NOTICE),
+  ("FONT_NAME", XA_FONT_NAME),
+  ("FAMILY_NAME", XA_FAMILY_NAME),
+  ("FULL_NAME", XA_FULL_NAME),
+  ("CAP_HEIGHT", XA_CAP_HEIGHT),
+  ("WM_CLASS", XA_WM_CLASS),
+  ("WM_TRANSIENT_FOR", XA_WM_TRANSIENT_FOR),
+];
+
+
+//
+// private functions
+//
+
+
+// Copies and frees a string returned by XGetAtomName(s).
+unsafe fn take_string (name: *mut c_char) -> String {
+  let owned = CStr::from_ptr(name).to_string_lossy().into_owned();
+  XFree(name as *mut c_void);
+  owned
+}
+
+#[test]
+fn atom_predefined_test () {
+  for (i, &(name, atom)) in PREDEFINED_ATOMS.iter().enumerate() {
+    assert_eq!(atom, i as Atom + 1);
+    assert_eq!(predefined_atom(name), Some(atom));
+    assert_eq!(predefined_name(atom), Some(name));
+  }
+  assert_eq!(predefined_atom("UTF8_STRING"), None);
+
+  // Predefined atoms never reach the (here null) display.
+  unsafe {
+    let mut cache = AtomCache::new(null_mut());
+    assert_eq!(cache.atom("WM_NAME"), XA_WM_NAME);
+    assert_eq!(cache.name(XA_CARDINAL), Some("CARDINAL".to_string()));
+    assert_eq!(cache.names(&[XA_ATOM, XA_WINDOW]), vec![Some("ATOM".to_string()), Some("WINDOW".to_string())]);
+    assert!(cache.intern_all(&["STRING", "WM_CLASS"]));
+    assert_eq!(cache.get("UTF8_STRING"), None);
+  }
+}
+
+#[test]
+#[ignore]
+fn atom_cache_test () {
+  use std::ptr::null;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let names = ["UTF8_STRING", "_NET_WM_NAME", "WM_PROTOCOLS", "UTF8_STRING", "WM_NAME"];
+    let mut cache = AtomCache::with_names(display, &names);
+    let utf8_string = cache.get("UTF8_STRING").unwrap();
+    assert_eq!(utf8_string, XInternAtom(display, b"UTF8_STRING\0".as_ptr() as *const c_char, False));
+    assert_eq!(cache.atom("UTF8_STRING"), utf8_string);
+    assert_eq!(cache.get_name(utf8_string), Some("UTF8_STRING"));
+
+    let mut fresh = AtomCache::new(display);
+    let net_wm_name = cache.get("_NET_WM_NAME").unwrap();
+    assert_eq!(fresh.names(&[net_wm_name, XA_WM_NAME]), vec![Some("_NET_WM_NAME".to_string()), Some("WM_NAME".to_string())]);
+    assert_eq!(fresh.get("_NET_WM_NAME"), Some(net_wm_name));
+
+    // Atoms the server never handed out come back as None instead of
+    // killing the client with BadAtom.
+    let unknown: Atom = 0x1fff_ffff;
+    assert_eq!(fresh.name(unknown), None);
+    assert_eq!(fresh.names(&[XA_WM_NAME, unknown]), vec![Some("WM_NAME".to_string()), None]);
+    assert_eq!(fresh.get_name(unknown), None);
+
+    XCloseDisplay(display);
+  }
+}
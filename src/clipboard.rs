@@ -0,0 +1,577 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::cmp::min;
+use std::mem::zeroed;
+use std::rc::Rc;
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use libc::{
+  c_int,
+  poll,
+  pollfd,
+  POLLIN,
+};
+
+use ::atom::AtomCache;
+use ::property::{
+  Property,
+  PropertyData,
+  PropertyError,
+  get_property,
+  set_atoms,
+  set_property,
+  take_property,
+};
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipboardError {
+  NoOwner,
+  // XSetSelectionOwner didn't take effect, usually because `time` was older
+  // than the current owner's timestamp.
+  NotOwner,
+  // The owner can't convert the selection to the requested target.
+  Refused,
+  Timeout,
+  Property(PropertyError),
+}
+
+impl From<PropertyError> for ClipboardError {
+  fn from (error: PropertyError) -> ClipboardError {
+    ClipboardError::Property(error)
+  }
+}
+
+// Owns and requests selections through a private window, following the
+// ICCCM transfer rules including INCR for payloads larger than a request.
+//
+// Events for the clipboard's window (and PropertyNotify/DestroyNotify for
+// windows receiving an INCR transfer) must be passed to handle_event by the
+// application's event loop. While get is waiting it handles them itself.
+pub struct Clipboard {
+  display: *mut Display,
+  window: Window,
+  atoms: AtomCache,
+  chunk_size: usize,
+  owned: Vec<Owned>,
+  transfers: Vec<Transfer>,
+}
+
+impl Clipboard {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the clipboard.
+  pub unsafe fn new (display: *mut Display) -> Clipboard {
+    let window = XCreateSimpleWindow(display, XDefaultRootWindow(display), -10, -10, 1, 1, 0, 0, 0);
+    XSelectInput(display, window, PropertyChangeMask);
+
+    // The maximum request length is in 4-byte units, so this is a quarter
+    // of it in bytes.
+    let max_request = match XExtendedMaxRequestSize(display) {
+      0 => XMaxRequestSize(display),
+      size => size,
+    };
+    let chunk_size = min(max_request as usize, MAX_CHUNK_SIZE);
+
+    Clipboard {
+      display,
+      window,
+      atoms: AtomCache::with_names(display, &ATOMS),
+      chunk_size,
+      owned: Vec::new(),
+      transfers: Vec::new(),
+    }
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  pub fn window (&self) -> Window {
+    self.window
+  }
+
+  // The CLIPBOARD selection. PRIMARY is XA_PRIMARY.
+  pub fn clipboard_atom (&self) -> Atom {
+    self.known_atom("CLIPBOARD")
+  }
+
+  /// Interns a target name such as "image/png".
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn atom (&mut self, name: &str) -> Atom {
+    self.atoms.atom(name)
+  }
+
+  pub fn owns (&self, selection: Atom) -> bool {
+    self.owned.iter().any(|owned| owned.selection == selection)
+  }
+
+  /// A server timestamp, suitable for XSetSelectionOwner, obtained from a
+  /// zero-length property change on the clipboard's window.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn server_time (&mut self, timeout: Duration) -> Result<Time, ClipboardError> {
+    let property = self.known_atom("_X11_RS_TIMESTAMP");
+    XChangeProperty(self.display, self.window, property, XA_INTEGER, 8, PropModeAppend, [].as_ptr(), 0);
+    let event = self.wait_for(PropertyNotify, timeout, |event| XPropertyEvent::from(*event).atom == property)?;
+    Ok(XPropertyEvent::from(event).time)
+  }
+
+  /// Takes ownership of `selection` and offers `data` under each target
+  /// name, which may be an atom name or a MIME type. `time` should be the
+  /// timestamp of the event that caused the copy, not CurrentTime.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn set (&mut self, selection: Atom, data: &[(&str, &[u8])], time: Time) -> Result<(), ClipboardError> {
+    let names: Vec<&str> = data.iter().map(|&(name, _)| name).collect();
+    self.atoms.intern_all(&names);
+
+    let mut offers = Vec::with_capacity(data.len());
+    for &(name, bytes) in data {
+      let target = self.atoms.atom(name);
+      offers.push(Offer { target, kind: target, data: Rc::new(bytes.to_vec()) });
+    }
+    self.take_ownership(selection, offers, time)
+  }
+
+  /// Offers `text` as UTF8_STRING and text/plain;charset=utf-8, and also as
+  /// STRING and TEXT when it is plain ASCII.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn set_text (&mut self, selection: Atom, text: &str, time: Time) -> Result<(), ClipboardError> {
+    let data = Rc::new(text.as_bytes().to_vec());
+    let utf8_string = self.known_atom("UTF8_STRING");
+    let mime = self.known_atom("text/plain;charset=utf-8");
+
+    let mut offers = vec![
+      Offer { target: utf8_string, kind: utf8_string, data: data.clone() },
+      Offer { target: mime, kind: mime, data: data.clone() },
+    ];
+    if text.is_ascii() {
+      offers.push(Offer { target: XA_STRING, kind: XA_STRING, data: data.clone() });
+      offers.push(Offer { target: self.known_atom("TEXT"), kind: XA_STRING, data });
+    }
+    self.take_ownership(selection, offers, time)
+  }
+
+  /// Gives up `selection` if the clipboard owns it.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn release (&mut self, selection: Atom) {
+    if let Some(index) = self.owned.iter().position(|owned| owned.selection == selection) {
+      let owned = self.owned.remove(index);
+      if XGetSelectionOwner(self.display, selection) == self.window {
+        XSetSelectionOwner(self.display, selection, 0, owned.time);
+      }
+    }
+  }
+
+  /// Converts `selection` to `target` and waits up to `timeout` for the
+  /// owner's reply, or for each chunk of an INCR transfer.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn get (&mut self, selection: Atom, target: Atom, timeout: Duration) -> Result<Property, ClipboardError> {
+    self.get_at(selection, target, CurrentTime, timeout)
+  }
+
+  /// Like get, but with the timestamp of the event that triggered the
+  /// request, as protocols such as XDND require.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn get_at (&mut self, selection: Atom, target: Atom, time: Time, timeout: Duration) -> Result<Property, ClipboardError> {
+    if XGetSelectionOwner(self.display, selection) == 0 {
+      return Err(ClipboardError::NoOwner);
+    }
+
+    let property = self.known_atom("_X11_RS_SELECTION");
+    XDeleteProperty(self.display, self.window, property);
+    XConvertSelection(self.display, selection, target, property, self.window, time);
+
+    let event = self.wait_for(SelectionNotify, timeout, |event| {
+      let event = XSelectionEvent::from(*event);
+      event.selection == selection && event.target == target
+    })?;
+    if XSelectionEvent::from(event).property == 0 {
+      return Err(ClipboardError::Refused);
+    }
+
+    let reply = take_property(self.display, self.window, property, AnyPropertyType)?.ok_or(ClipboardError::Refused)?;
+    if reply.kind != self.known_atom("INCR") {
+      return Ok(reply);
+    }
+
+    // Deleting the INCR property above told the owner to start sending.
+    let mut result: Option<Property> = None;
+    loop {
+      self.wait_for(PropertyNotify, timeout, |event| {
+        let event = XPropertyEvent::from(*event);
+        event.atom == property && event.state == PropertyNewValue
+      })?;
+
+      let chunk = match take_property(self.display, self.window, property, AnyPropertyType)? {
+        Some(chunk) => chunk,
+        None => { continue; },
+      };
+      if chunk.data.is_empty() {
+        return Ok(result.unwrap_or(Property { kind: chunk.kind, data: PropertyData::Format8(Vec::new()) }));
+      }
+
+      match result {
+        Some(ref mut result) => {
+          if !result.data.append(chunk.data) {
+            return Err(ClipboardError::Property(PropertyError::Changed));
+          }
+        },
+        None => { result = Some(chunk); },
+      }
+    }
+  }
+
+  /// Reads `selection` as text, preferring UTF8_STRING and falling back to
+  /// Latin-1 STRING.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn get_text (&mut self, selection: Atom, timeout: Duration) -> Result<String, ClipboardError> {
+    let utf8_string = self.known_atom("UTF8_STRING");
+    match self.get(selection, utf8_string, timeout) {
+      Ok(reply) => Ok(String::from_utf8_lossy(reply.bytes().unwrap_or(&[])).into_owned()),
+      Err(ClipboardError::Refused) => {
+        let reply = self.get(selection, XA_STRING, timeout)?;
+        Ok(reply.bytes().unwrap_or(&[]).iter().map(|&byte| byte as char).collect())
+      },
+      Err(error) => Err(error),
+    }
+  }
+
+  /// The targets the owner of `selection` can convert to.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn targets (&mut self, selection: Atom, timeout: Duration) -> Result<Vec<Atom>, ClipboardError> {
+    let targets = self.known_atom("TARGETS");
+    Ok(self.get(selection, targets, timeout)?.atoms().unwrap_or_default())
+  }
+
+  /// Serves selection requests, tracks lost ownership and advances INCR
+  /// transfers. Returns false for events that aren't the clipboard's.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open, and `event` must have been
+  /// read from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> bool {
+    match event.kind() {
+      SelectionRequest => {
+        let request = XSelectionRequestEvent::from(*event);
+        if request.owner != self.window {
+          return false;
+        }
+        self.serve(&request);
+        true
+      },
+      SelectionClear => {
+        let clear = XSelectionClearEvent::from(*event);
+        if clear.window != self.window {
+          return false;
+        }
+        self.owned.retain(|owned| owned.selection != clear.selection);
+        true
+      },
+      PropertyNotify => {
+        let notify = XPropertyEvent::from(*event);
+        if notify.state != PropertyDelete {
+          return false;
+        }
+        match self.transfers.iter().position(|transfer| transfer.requestor == notify.window && transfer.property == notify.atom) {
+          Some(index) => { self.continue_transfer(index); true },
+          None => false,
+        }
+      },
+      DestroyNotify => {
+        let destroyed = XDestroyWindowEvent::from(*event).window;
+        let count = self.transfers.len();
+        self.transfers.retain(|transfer| transfer.requestor != destroyed);
+        self.transfers.len() != count
+      },
+      _ => false,
+    }
+  }
+
+  fn known_atom (&self, name: &str) -> Atom {
+    self.atoms.get(name).unwrap_or(0)
+  }
+
+  unsafe fn take_ownership (&mut self, selection: Atom, offers: Vec<Offer>, time: Time) -> Result<(), ClipboardError> {
+    XSetSelectionOwner(self.display, selection, self.window, time);
+    if XGetSelectionOwner(self.display, selection) != self.window {
+      return Err(ClipboardError::NotOwner);
+    }
+
+    self.owned.retain(|owned| owned.selection != selection);
+    self.owned.push(Owned { selection, time, offers });
+    Ok(())
+  }
+
+  // Pulls the clipboard window's events off the queue, handling them until
+  // one of type `kind` satisfies `matches` or the timeout expires.
+  unsafe fn wait_for<F> (&mut self, kind: c_int, timeout: Duration, mut matches: F) -> Result<XEvent, ClipboardError>
+    where F: FnMut(&XEvent) -> bool
+  {
+    let deadline = Instant::now() + timeout;
+    loop {
+      let mut event: XEvent = zeroed();
+      while XCheckIfEvent(self.display, &mut event, Some(is_for_window), self.window as XPointer) != 0 {
+        if event.kind() == kind && matches(&event) {
+          return Ok(event);
+        }
+        self.handle_event(&event);
+      }
+
+      let now = Instant::now();
+      if now >= deadline {
+        return Err(ClipboardError::Timeout);
+      }
+
+      XFlush(self.display);
+      let mut fd = pollfd { fd: XConnectionNumber(self.display), events: POLLIN, revents: 0 };
+      let millis = min((deadline - now).as_millis() + 1, c_int::MAX as u128);
+      poll(&mut fd, 1, millis as c_int);
+    }
+  }
+
+  unsafe fn serve (&mut self, request: &XSelectionRequestEvent) {
+    // Obsolete clients leave out the property and expect the target name.
+    let property = if request.property == 0 { request.target } else { request.property };
+
+    let owned = self.owned.iter().position(|owned| {
+      owned.selection == request.selection && (request.time == CurrentTime || request.time >= owned.time)
+    });
+    let accepted = match owned {
+      Some(index) if request.target == self.known_atom("MULTIPLE") => self.convert_multiple(index, request.requestor, property),
+      Some(index) => self.convert(index, request.requestor, request.target, property),
+      None => false,
+    };
+
+    let mut reply: XSelectionEvent = zeroed();
+    reply.kind = SelectionNotify;
+    reply.requestor = request.requestor;
+    reply.selection = request.selection;
+    reply.target = request.target;
+    reply.property = if accepted { property } else { 0 };
+    reply.time = request.time;
+    XSendEvent(self.display, request.requestor, False, 0, &XEvent::from(reply));
+  }
+
+  unsafe fn convert (&mut self, index: usize, requestor: Window, target: Atom, property: Atom) -> bool {
+    let owned = &self.owned[index];
+
+    if target == self.known_atom("TARGETS") {
+      let mut targets = vec![self.known_atom("TARGETS"), self.known_atom("TIMESTAMP"), self.known_atom("MULTIPLE")];
+      targets.extend(owned.offers.iter().map(|offer| offer.target));
+      set_atoms(self.display, requestor, property, &targets);
+      return true;
+    }
+    if target == self.known_atom("TIMESTAMP") {
+      let data = PropertyData::Format32(vec![owned.time as u32]);
+      set_property(self.display, requestor, property, XA_INTEGER, &data, PropModeReplace);
+      return true;
+    }
+
+    let (kind, data) = match owned.offers.iter().find(|offer| offer.target == target) {
+      Some(offer) => (offer.kind, offer.data.clone()),
+      None => { return false; },
+    };
+    self.send(requestor, property, kind, data);
+    true
+  }
+
+  // MULTIPLE names a property holding (target, property) pairs; refused
+  // conversions have their property replaced with None.
+  unsafe fn convert_multiple (&mut self, index: usize, requestor: Window, property: Atom) -> bool {
+    let pairs = match get_property(self.display, requestor, property, AnyPropertyType) {
+      Ok(Some(pairs)) => pairs.atoms().unwrap_or_default(),
+      _ => { return false; },
+    };
+
+    let mut results = pairs.clone();
+    for (i, pair) in pairs.chunks(2).enumerate() {
+      if pair.len() == 2 && !self.convert(index, requestor, pair[0], pair[1]) {
+        results[i * 2 + 1] = 0;
+      }
+    }
+
+    let data = PropertyData::Format32(results.iter().map(|&atom| atom as u32).collect());
+    set_property(self.display, requestor, property, self.known_atom("ATOM_PAIR"), &data, PropModeReplace);
+    true
+  }
+
+  unsafe fn send (&mut self, requestor: Window, property: Atom, kind: Atom, data: Rc<Vec<u8>>) {
+    if data.len() <= self.chunk_size {
+      set_property(self.display, requestor, property, kind, &PropertyData::Format8(data.to_vec()), PropModeReplace);
+      return;
+    }
+
+    // Each deletion of the property by the requestor asks for the next chunk.
+    if requestor != self.window {
+      XSelectInput(self.display, requestor, PropertyChangeMask | StructureNotifyMask);
+    }
+    let length = PropertyData::Format32(vec![data.len() as u32]);
+    set_property(self.display, requestor, property, self.known_atom("INCR"), &length, PropModeReplace);
+
+    self.transfers.retain(|transfer| transfer.requestor != requestor || transfer.property != property);
+    self.transfers.push(Transfer { requestor, property, kind, data, offset: 0 });
+  }
+
+  // Writes the next chunk, or the terminating zero-length chunk.
+  unsafe fn continue_transfer (&mut self, index: usize) {
+    let (requestor, property, kind, chunk, done) = {
+      let transfer = &mut self.transfers[index];
+      let end = min(transfer.offset + self.chunk_size, transfer.data.len());
+      let chunk = transfer.data[transfer.offset..end].to_vec();
+      let done = chunk.is_empty();
+      transfer.offset = end;
+      (transfer.requestor, transfer.property, transfer.kind, chunk, done)
+    };
+    set_property(self.display, requestor, property, kind, &PropertyData::Format8(chunk), PropModeReplace);
+
+    if done {
+      self.transfers.remove(index);
+      if requestor != self.window && !self.transfers.iter().any(|transfer| transfer.requestor == requestor) {
+        XSelectInput(self.display, requestor, NoEventMask);
+      }
+    }
+  }
+}
+
+impl Drop for Clipboard {
+  fn drop (&mut self) {
+    unsafe {
+      for transfer in self.transfers.iter().filter(|transfer| transfer.requestor != self.window) {
+        XSelectInput(self.display, transfer.requestor, NoEventMask);
+      }
+      XDestroyWindow(self.display, self.window);
+    }
+  }
+}
+
+struct Offer {
+  target: Atom,
+  kind: Atom,
+  data: Rc<Vec<u8>>,
+}
+
+struct Owned {
+  selection: Atom,
+  time: Time,
+  offers: Vec<Offer>,
+}
+
+// An outgoing INCR transfer.
+struct Transfer {
+  requestor: Window,
+  property: Atom,
+  kind: Atom,
+  data: Rc<Vec<u8>>,
+  offset: usize,
+}
+
+
+//
+// constants
+//
+
+
+const ATOMS: [&str; 11] = [
+  "ATOM_PAIR",
+  "CLIPBOARD",
+  "INCR",
+  "MULTIPLE",
+  "TARGETS",
+  "TEXT",
+  "TIMESTAMP",
+  "UTF8_STRING",
+  "_X11_RS_SELECTION",
+  "_X11_RS_TIMESTAMP",
+  "text/plain;charset=utf-8",
+];
+
+const MAX_CHUNK_SIZE: usize = 0x40000;
+
+
+//
+// private functions
+//
+
+
+unsafe extern "C" fn is_for_window (_display: *mut Display, event: *mut XEvent, window: XPointer) -> Bool {
+  (XAnyEvent::from(*event).window == window as Window) as Bool
+}
+
+#[test]
+#[ignore]
+fn clipboard_self_transfer_test () {
+  use std::ptr::null;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let timeout = Duration::from_secs(5);
+    let mut clipboard = Clipboard::new(display);
+    let selection = clipboard.clipboard_atom();
+    let time = clipboard.server_time(timeout).unwrap();
+
+    clipboard.set_text(selection, "Grüße", time).unwrap();
+    assert!(clipboard.owns(selection));
+    assert_eq!(clipboard.get_text(selection, timeout), Ok("Grüße".to_string()));
+
+    let targets = clipboard.targets(selection, timeout).unwrap();
+    assert!(targets.contains(&clipboard.atom("text/plain;charset=utf-8")));
+    assert!(!targets.contains(&XA_STRING));
+
+    // Larger than any chunk, so it goes through INCR.
+    let image: Vec<u8> = (0..3 * MAX_CHUNK_SIZE + 17).map(|i| i as u8).collect();
+    clipboard.set(selection, &[("image/png", &image)], time).unwrap();
+    let png = clipboard.atom("image/png");
+    let reply = clipboard.get(selection, png, timeout).unwrap();
+    assert_eq!(reply.kind, png);
+    assert_eq!(reply.bytes(), Some(&image[..]));
+    assert_eq!(clipboard.get(selection, XA_STRING, timeout), Err(ClipboardError::Refused));
+
+    clipboard.release(selection);
+    assert!(!clipboard.owns(selection));
+    assert_eq!(clipboard.get(selection, png, timeout), Err(ClipboardError::NoOwner));
+
+    drop(clipboard);
+    XCloseDisplay(display);
+  }
+}
@@ -0,0 +1,117 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::mem::zeroed;
+
+use libc::c_uint;
+
+use ::xlib::{
+  Cursor,
+  Display,
+  Window,
+  XColor,
+  XCreateBitmapFromData,
+  XCreatePixmapCursor,
+  XDefineCursor,
+  XFreePixmap,
+  XQueryBestCursor,
+};
+
+
+//
+// functions
+//
+
+
+/// Creates a fully transparent cursor from a blank 1x1 bitmap, for hiding
+/// the pointer over `window`. Free it with XFreeCursor.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn invisible_cursor (display: *mut Display, window: Window) -> Cursor {
+  let data = [0];
+  let bitmap = XCreateBitmapFromData(display, window, data.as_ptr(), 1, 1);
+  if bitmap == 0 {
+    return 0;
+  }
+
+  // Every pixel is masked out, so the colours never show; Xlib still wants
+  // two distinct ones to write the allocated values back into.
+  let mut foreground: XColor = zeroed();
+  let mut background: XColor = zeroed();
+  let cursor = XCreatePixmapCursor(display, bitmap, bitmap, &mut foreground, &mut background, 0, 0);
+  XFreePixmap(display, bitmap);
+  cursor
+}
+
+/// Hides the pointer while it is over `window` and returns the cursor used,
+/// which should be freed with XFreeCursor once the window no longer needs it.
+/// Restore the parent's cursor with XUndefineCursor.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn hide_cursor (display: *mut Display, window: Window) -> Cursor {
+  let cursor = invisible_cursor(display, window);
+  if cursor != 0 {
+    XDefineCursor(display, window, cursor);
+  }
+  cursor
+}
+
+/// The size closest to width x height that the server can display as a
+/// cursor on the screen of `window`.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn best_cursor_size (display: *mut Display, window: Window, width: c_uint, height: c_uint) -> Option<(c_uint, c_uint)> {
+  let (mut best_width, mut best_height) = (0, 0);
+  match XQueryBestCursor(display, window, width, height, &mut best_width, &mut best_height) {
+    0 => None,
+    _ => Some((best_width, best_height)),
+  }
+}
+
+// Needs an X server; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn cursor_test () {
+  use std::ptr::null;
+  use ::xlib::{
+    XCloseDisplay,
+    XCreateSimpleWindow,
+    XDefaultRootWindow,
+    XDestroyWindow,
+    XFreeCursor,
+    XOpenDisplay,
+    XSync,
+    XUndefineCursor,
+  };
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let root = XDefaultRootWindow(display);
+    let window = XCreateSimpleWindow(display, root, 0, 0, 16, 16, 0, 0, 0);
+
+    let cursor = invisible_cursor(display, window);
+    assert!(cursor != 0);
+    XFreeCursor(display, cursor);
+
+    let hidden = hide_cursor(display, window);
+    assert!(hidden != 0);
+    XUndefineCursor(display, window);
+    XFreeCursor(display, hidden);
+
+    let (width, height) = best_cursor_size(display, window, 32, 32).unwrap();
+    assert!(width > 0 && height > 0);
+
+    XDestroyWindow(display, window);
+    XSync(display, 0);
+    XCloseDisplay(display);
+  }
+}
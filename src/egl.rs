@@ -0,0 +1,331 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_char,
+  c_uint,
+  c_void,
+};
+
+use ::xlib::{
+  Display,
+  Pixmap,
+  Window,
+};
+
+pub mod context;
+
+
+//
+// functions
+//
+
+
+#[link(name="EGL")]
+extern "C" {
+  pub fn eglBindAPI (api: EGLenum) -> EGLBoolean;
+  pub fn eglBindTexImage (display: EGLDisplay, surface: EGLSurface, buffer: EGLint) -> EGLBoolean;
+  pub fn eglChooseConfig (display: EGLDisplay, attrib_list: *const EGLint, configs: *mut EGLConfig, config_size: EGLint, num_config: *mut EGLint) -> EGLBoolean;
+  pub fn eglCopyBuffers (display: EGLDisplay, surface: EGLSurface, target: EGLNativePixmapType) -> EGLBoolean;
+  pub fn eglCreateContext (display: EGLDisplay, config: EGLConfig, share_context: EGLContext, attrib_list: *const EGLint) -> EGLContext;
+  pub fn eglCreatePbufferFromClientBuffer (display: EGLDisplay, buftype: EGLenum, buffer: EGLClientBuffer, config: EGLConfig, attrib_list: *const EGLint) -> EGLSurface;
+  pub fn eglCreatePbufferSurface (display: EGLDisplay, config: EGLConfig, attrib_list: *const EGLint) -> EGLSurface;
+  pub fn eglCreatePixmapSurface (display: EGLDisplay, config: EGLConfig, pixmap: EGLNativePixmapType, attrib_list: *const EGLint) -> EGLSurface;
+  pub fn eglCreateWindowSurface (display: EGLDisplay, config: EGLConfig, window: EGLNativeWindowType, attrib_list: *const EGLint) -> EGLSurface;
+  pub fn eglDestroyContext (display: EGLDisplay, context: EGLContext) -> EGLBoolean;
+  pub fn eglDestroySurface (display: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+  pub fn eglGetConfigAttrib (display: EGLDisplay, config: EGLConfig, attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+  pub fn eglGetConfigs (display: EGLDisplay, configs: *mut EGLConfig, config_size: EGLint, num_config: *mut EGLint) -> EGLBoolean;
+  pub fn eglGetCurrentContext () -> EGLContext;
+  pub fn eglGetCurrentDisplay () -> EGLDisplay;
+  pub fn eglGetCurrentSurface (readdraw: EGLint) -> EGLSurface;
+  pub fn eglGetDisplay (display_id: EGLNativeDisplayType) -> EGLDisplay;
+  pub fn eglGetError () -> EGLint;
+  pub fn eglGetProcAddress (procname: *const c_char) -> Option<unsafe extern "C" fn ()>;
+  pub fn eglInitialize (display: EGLDisplay, major: *mut EGLint, minor: *mut EGLint) -> EGLBoolean;
+  pub fn eglMakeCurrent (display: EGLDisplay, draw: EGLSurface, read: EGLSurface, context: EGLContext) -> EGLBoolean;
+  pub fn eglQueryAPI () -> EGLenum;
+  pub fn eglQueryContext (display: EGLDisplay, context: EGLContext, attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+  pub fn eglQueryString (display: EGLDisplay, name: EGLint) -> *const c_char;
+  pub fn eglQuerySurface (display: EGLDisplay, surface: EGLSurface, attribute: EGLint, value: *mut EGLint) -> EGLBoolean;
+  pub fn eglReleaseTexImage (display: EGLDisplay, surface: EGLSurface, buffer: EGLint) -> EGLBoolean;
+  pub fn eglReleaseThread () -> EGLBoolean;
+  pub fn eglSurfaceAttrib (display: EGLDisplay, surface: EGLSurface, attribute: EGLint, value: EGLint) -> EGLBoolean;
+  pub fn eglSwapBuffers (display: EGLDisplay, surface: EGLSurface) -> EGLBoolean;
+  pub fn eglSwapInterval (display: EGLDisplay, interval: EGLint) -> EGLBoolean;
+  pub fn eglTerminate (display: EGLDisplay) -> EGLBoolean;
+  pub fn eglWaitClient () -> EGLBoolean;
+  pub fn eglWaitGL () -> EGLBoolean;
+  pub fn eglWaitNative (engine: EGLint) -> EGLBoolean;
+}
+
+
+//
+// types
+//
+
+
+// scalar types
+pub type EGLBoolean = c_uint;
+pub type EGLenum = c_uint;
+pub type EGLint = i32;
+
+// native types (X11 platform)
+pub type EGLNativeDisplayType = *mut Display;
+pub type EGLNativePixmapType = Pixmap;
+pub type EGLNativeWindowType = Window;
+
+// opaque pointers
+pub type EGLClientBuffer = *mut c_void;
+pub type EGLConfig = *mut c_void;
+pub type EGLContext = *mut c_void;
+pub type EGLDisplay = *mut c_void;
+pub type EGLSurface = *mut c_void;
+
+
+//
+// constants
+//
+
+
+// booleans
+pub const EGL_FALSE: EGLBoolean = 0;
+pub const EGL_TRUE: EGLBoolean = 1;
+
+// client APIs
+pub const EGL_OPENGL_ES_API: EGLenum = 0x30a0;
+pub const EGL_OPENVG_API: EGLenum = 0x30a1;
+pub const EGL_OPENGL_API: EGLenum = 0x30a2;
+
+// color buffer types
+pub const EGL_RGB_BUFFER: EGLint = 0x308e;
+pub const EGL_LUMINANCE_BUFFER: EGLint = 0x308f;
+
+// config attributes
+pub const EGL_BUFFER_SIZE: EGLint = 0x3020;
+pub const EGL_ALPHA_SIZE: EGLint = 0x3021;
+pub const EGL_BLUE_SIZE: EGLint = 0x3022;
+pub const EGL_GREEN_SIZE: EGLint = 0x3023;
+pub const EGL_RED_SIZE: EGLint = 0x3024;
+pub const EGL_DEPTH_SIZE: EGLint = 0x3025;
+pub const EGL_STENCIL_SIZE: EGLint = 0x3026;
+pub const EGL_CONFIG_CAVEAT: EGLint = 0x3027;
+pub const EGL_CONFIG_ID: EGLint = 0x3028;
+pub const EGL_LEVEL: EGLint = 0x3029;
+pub const EGL_MAX_PBUFFER_HEIGHT: EGLint = 0x302a;
+pub const EGL_MAX_PBUFFER_PIXELS: EGLint = 0x302b;
+pub const EGL_MAX_PBUFFER_WIDTH: EGLint = 0x302c;
+pub const EGL_NATIVE_RENDERABLE: EGLint = 0x302d;
+pub const EGL_NATIVE_VISUAL_ID: EGLint = 0x302e;
+pub const EGL_NATIVE_VISUAL_TYPE: EGLint = 0x302f;
+pub const EGL_SAMPLES: EGLint = 0x3031;
+pub const EGL_SAMPLE_BUFFERS: EGLint = 0x3032;
+pub const EGL_SURFACE_TYPE: EGLint = 0x3033;
+pub const EGL_TRANSPARENT_TYPE: EGLint = 0x3034;
+pub const EGL_TRANSPARENT_BLUE_VALUE: EGLint = 0x3035;
+pub const EGL_TRANSPARENT_GREEN_VALUE: EGLint = 0x3036;
+pub const EGL_TRANSPARENT_RED_VALUE: EGLint = 0x3037;
+pub const EGL_BIND_TO_TEXTURE_RGB: EGLint = 0x3039;
+pub const EGL_BIND_TO_TEXTURE_RGBA: EGLint = 0x303a;
+pub const EGL_MIN_SWAP_INTERVAL: EGLint = 0x303b;
+pub const EGL_MAX_SWAP_INTERVAL: EGLint = 0x303c;
+pub const EGL_LUMINANCE_SIZE: EGLint = 0x303d;
+pub const EGL_ALPHA_MASK_SIZE: EGLint = 0x303e;
+pub const EGL_COLOR_BUFFER_TYPE: EGLint = 0x303f;
+pub const EGL_RENDERABLE_TYPE: EGLint = 0x3040;
+pub const EGL_MATCH_NATIVE_PIXMAP: EGLint = 0x3041;
+pub const EGL_CONFORMANT: EGLint = 0x3042;
+
+// config caveats
+pub const EGL_SLOW_CONFIG: EGLint = 0x3050;
+pub const EGL_NON_CONFORMANT_CONFIG: EGLint = 0x3051;
+
+// context attributes
+pub const EGL_CONTEXT_CLIENT_TYPE: EGLint = 0x3097;
+pub const EGL_CONTEXT_CLIENT_VERSION: EGLint = 0x3098;
+pub const EGL_CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+pub const EGL_CONTEXT_MINOR_VERSION: EGLint = 0x30fb;
+pub const EGL_CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30fd;
+pub const EGL_CONTEXT_OPENGL_DEBUG: EGLint = 0x31b0;
+pub const EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE: EGLint = 0x31b1;
+pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS: EGLint = 0x31b2;
+pub const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY: EGLint = 0x31bd;
+
+// context profile mask
+pub const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0001;
+pub const EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT: EGLint = 0x0002;
+
+// current surfaces
+pub const EGL_DRAW: EGLint = 0x3059;
+pub const EGL_READ: EGLint = 0x305a;
+
+// error codes
+pub const EGL_SUCCESS: EGLint = 0x3000;
+pub const EGL_NOT_INITIALIZED: EGLint = 0x3001;
+pub const EGL_BAD_ACCESS: EGLint = 0x3002;
+pub const EGL_BAD_ALLOC: EGLint = 0x3003;
+pub const EGL_BAD_ATTRIBUTE: EGLint = 0x3004;
+pub const EGL_BAD_CONFIG: EGLint = 0x3005;
+pub const EGL_BAD_CONTEXT: EGLint = 0x3006;
+pub const EGL_BAD_CURRENT_SURFACE: EGLint = 0x3007;
+pub const EGL_BAD_DISPLAY: EGLint = 0x3008;
+pub const EGL_BAD_MATCH: EGLint = 0x3009;
+pub const EGL_BAD_NATIVE_PIXMAP: EGLint = 0x300a;
+pub const EGL_BAD_NATIVE_WINDOW: EGLint = 0x300b;
+pub const EGL_BAD_PARAMETER: EGLint = 0x300c;
+pub const EGL_BAD_SURFACE: EGLint = 0x300d;
+pub const EGL_CONTEXT_LOST: EGLint = 0x300e;
+
+// misc
+pub const EGL_DONT_CARE: EGLint = -1;
+pub const EGL_NONE: EGLint = 0x3038;
+pub const EGL_CORE_NATIVE_ENGINE: EGLint = 0x305b;
+
+// null handles
+pub const EGL_DEFAULT_DISPLAY: EGLNativeDisplayType = 0 as EGLNativeDisplayType;
+pub const EGL_NO_CONTEXT: EGLContext = 0 as EGLContext;
+pub const EGL_NO_DISPLAY: EGLDisplay = 0 as EGLDisplay;
+pub const EGL_NO_SURFACE: EGLSurface = 0 as EGLSurface;
+
+// query strings
+pub const EGL_VENDOR: EGLint = 0x3053;
+pub const EGL_VERSION: EGLint = 0x3054;
+pub const EGL_EXTENSIONS: EGLint = 0x3055;
+pub const EGL_CLIENT_APIS: EGLint = 0x308d;
+
+// renderable type mask
+pub const EGL_OPENGL_ES_BIT: EGLint = 0x0001;
+pub const EGL_OPENVG_BIT: EGLint = 0x0002;
+pub const EGL_OPENGL_ES2_BIT: EGLint = 0x0004;
+pub const EGL_OPENGL_BIT: EGLint = 0x0008;
+pub const EGL_OPENGL_ES3_BIT: EGLint = 0x0040;
+
+// surface attributes
+pub const EGL_HEIGHT: EGLint = 0x3056;
+pub const EGL_WIDTH: EGLint = 0x3057;
+pub const EGL_LARGEST_PBUFFER: EGLint = 0x3058;
+pub const EGL_TEXTURE_FORMAT: EGLint = 0x3080;
+pub const EGL_TEXTURE_TARGET: EGLint = 0x3081;
+pub const EGL_MIPMAP_TEXTURE: EGLint = 0x3082;
+pub const EGL_MIPMAP_LEVEL: EGLint = 0x3083;
+pub const EGL_RENDER_BUFFER: EGLint = 0x3086;
+pub const EGL_VG_COLORSPACE: EGLint = 0x3087;
+pub const EGL_VG_ALPHA_FORMAT: EGLint = 0x3088;
+pub const EGL_HORIZONTAL_RESOLUTION: EGLint = 0x3090;
+pub const EGL_VERTICAL_RESOLUTION: EGLint = 0x3091;
+pub const EGL_PIXEL_ASPECT_RATIO: EGLint = 0x3092;
+pub const EGL_SWAP_BEHAVIOR: EGLint = 0x3093;
+pub const EGL_MULTISAMPLE_RESOLVE: EGLint = 0x3099;
+pub const EGL_GL_COLORSPACE: EGLint = 0x309d;
+
+// surface attribute values
+pub const EGL_BACK_BUFFER: EGLint = 0x3084;
+pub const EGL_SINGLE_BUFFER: EGLint = 0x3085;
+pub const EGL_GL_COLORSPACE_SRGB: EGLint = 0x3089;
+pub const EGL_GL_COLORSPACE_LINEAR: EGLint = 0x308a;
+pub const EGL_BUFFER_PRESERVED: EGLint = 0x3094;
+pub const EGL_BUFFER_DESTROYED: EGLint = 0x3095;
+pub const EGL_NO_TEXTURE: EGLint = 0x305c;
+pub const EGL_TEXTURE_RGB: EGLint = 0x305d;
+pub const EGL_TEXTURE_RGBA: EGLint = 0x305e;
+pub const EGL_TEXTURE_2D: EGLint = 0x305f;
+
+// surface type mask
+pub const EGL_PBUFFER_BIT: EGLint = 0x0001;
+pub const EGL_PIXMAP_BIT: EGLint = 0x0002;
+pub const EGL_WINDOW_BIT: EGLint = 0x0004;
+pub const EGL_MULTISAMPLE_RESOLVE_BOX_BIT: EGLint = 0x0200;
+pub const EGL_SWAP_BEHAVIOR_PRESERVED_BIT: EGLint = 0x0400;
+
+// transparent types
+pub const EGL_TRANSPARENT_RGB: EGLint = 0x3052;
+
+
+//
+// EXT extensions
+//
+
+
+#[allow(clippy::missing_safety_doc)]
+pub mod ext {
+  use std::mem::transmute;
+
+  use libc::c_void;
+
+  use super::{
+    eglGetProcAddress,
+    EGLConfig,
+    EGLDisplay,
+    EGLenum,
+    EGLint,
+    EGLSurface,
+  };
+
+  // function pointer types
+  pub type CreatePlatformWindowSurfaceEXTFn = Option<unsafe extern "C" fn (EGLDisplay, EGLConfig, *mut c_void, *const EGLint) -> EGLSurface>;
+  pub type GetPlatformDisplayEXTFn = Option<unsafe extern "C" fn (EGLenum, *mut c_void, *const EGLint) -> EGLDisplay>;
+
+  // loaders
+  pub unsafe fn load_eglCreatePlatformWindowSurfaceEXT () -> CreatePlatformWindowSurfaceEXTFn {
+    transmute(eglGetProcAddress(b"eglCreatePlatformWindowSurfaceEXT\0".as_ptr() as *const _))
+  }
+
+  pub unsafe fn load_eglGetPlatformDisplayEXT () -> GetPlatformDisplayEXTFn {
+    transmute(eglGetProcAddress(b"eglGetPlatformDisplayEXT\0".as_ptr() as *const _))
+  }
+
+  // platforms
+  pub const EGL_PLATFORM_X11_EXT: EGLenum = 0x31d5;
+
+  // platform display attributes
+  pub const EGL_PLATFORM_X11_SCREEN_EXT: EGLint = 0x31d6;
+
+  // extension names
+  pub const EGL_EXT_client_extensions: &str = "EGL_EXT_client_extensions";
+  pub const EGL_EXT_platform_base: &str = "EGL_EXT_platform_base";
+  pub const EGL_EXT_platform_x11: &str = "EGL_EXT_platform_x11";
+}
+
+
+//
+// KHR extensions
+//
+
+
+pub mod khr {
+  use super::{
+    EGLenum,
+    EGLint,
+  };
+
+  // platforms
+  pub const EGL_PLATFORM_X11_KHR: EGLenum = 0x31d5;
+
+  // platform display attributes
+  pub const EGL_PLATFORM_X11_SCREEN_KHR: EGLint = 0x31d6;
+
+  // renderable type mask
+  pub const EGL_OPENGL_ES3_BIT_KHR: EGLint = 0x0040;
+
+  // extension names
+  pub const EGL_KHR_create_context: &str = "EGL_KHR_create_context";
+  pub const EGL_KHR_platform_x11: &str = "EGL_KHR_platform_x11";
+  pub const EGL_KHR_surfaceless_context: &str = "EGL_KHR_surfaceless_context";
+}
+
+
+//
+// MESA extensions
+//
+
+
+pub mod mesa {
+  use super::EGLenum;
+
+  // platforms
+  pub const EGL_PLATFORM_SURFACELESS_MESA: EGLenum = 0x31dd;
+
+  // extension names
+  pub const EGL_MESA_platform_surfaceless: &str = "EGL_MESA_platform_surfaceless";
+}
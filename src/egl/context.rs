@@ -0,0 +1,587 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ffi::CStr;
+use std::mem::zeroed;
+use std::ptr::null_mut;
+
+use libc::{
+  c_char,
+  c_int,
+  c_long,
+};
+
+use ::egl::*;
+use ::egl::ext::*;
+use ::egl::khr::*;
+use ::egl::mesa::*;
+use ::xlib::{
+  Display,
+  VisualID,
+  VisualIDMask,
+  Window,
+  XGetVisualInfo,
+  XVisualInfo,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EglError {
+  NoDisplay,
+  NoConfig,
+  NoVisual,
+  MissingExtension(&'static str),
+  // The eglGetError code of the call that failed.
+  Failed(EGLint),
+}
+
+// An initialized EGL display. It is terminated on drop, so contexts and
+// surfaces created from it must be dropped first.
+pub struct EglDisplay {
+  display: EGLDisplay,
+  version: (EGLint, EGLint),
+  // Obtained with eglGetPlatformDisplayEXT, so window surfaces must be
+  // created with eglCreatePlatformWindowSurfaceEXT.
+  platform: bool,
+}
+
+impl EglDisplay {
+  /// Opens the EGL display for an Xlib connection. When the client supports
+  /// EGL_EXT_platform_x11 or EGL_KHR_platform_x11 the display is obtained with
+  /// eglGetPlatformDisplayEXT for `screen`, otherwise with eglGetDisplay.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open Xlib connection that outlives the returned
+  /// EglDisplay, and `screen` one of its screens.
+  pub unsafe fn from_xlib (display: *mut Display, screen: c_int) -> Result<EglDisplay, EglError> {
+    let client_extensions = EglDisplay::client_extensions();
+    let has_platform_x11 = client_extensions.iter()
+      .any(|name| name == EGL_EXT_platform_x11 || name == EGL_KHR_platform_x11);
+
+    if has_platform_x11 {
+      if let Some(get_platform_display) = load_eglGetPlatformDisplayEXT() {
+        let attrs = [EGL_PLATFORM_X11_SCREEN_KHR, screen, EGL_NONE];
+        return EglDisplay::initialize_platform(get_platform_display(EGL_PLATFORM_X11_KHR, display as *mut _, attrs.as_ptr()));
+      }
+    }
+
+    EglDisplay::initialize(eglGetDisplay(display))
+  }
+
+  /// Opens Mesa's surfaceless platform, which renders to pbuffers or with
+  /// EGL_KHR_surfaceless_context without any window system.
+  ///
+  /// # Safety
+  ///
+  /// Nothing else may call eglTerminate on the surfaceless display while the
+  /// returned EglDisplay is alive; EGL hands every caller the same handle.
+  pub unsafe fn surfaceless () -> Result<EglDisplay, EglError> {
+    if !EglDisplay::client_extensions().iter().any(|name| name == EGL_MESA_platform_surfaceless) {
+      return Err(EglError::MissingExtension(EGL_MESA_platform_surfaceless));
+    }
+
+    match load_eglGetPlatformDisplayEXT() {
+      Some(get_platform_display) => {
+        let attrs = [EGL_NONE];
+        EglDisplay::initialize_platform(get_platform_display(EGL_PLATFORM_SURFACELESS_MESA, null_mut(), attrs.as_ptr()))
+      },
+      None => Err(EglError::MissingExtension(EGL_EXT_platform_base)),
+    }
+  }
+
+  /// Initializes `display`, as returned by eglGetDisplay, and takes ownership
+  /// of it.
+  ///
+  /// # Safety
+  ///
+  /// `display` must come from eglGetDisplay, and nothing else may call
+  /// eglTerminate on it while the EglDisplay is alive.
+  pub unsafe fn initialize (display: EGLDisplay) -> Result<EglDisplay, EglError> {
+    EglDisplay::initialize_with(display, false)
+  }
+
+  /// Like initialize, for a display returned by eglGetPlatformDisplayEXT.
+  ///
+  /// # Safety
+  ///
+  /// `display` must come from eglGetPlatformDisplayEXT, and nothing else may
+  /// call eglTerminate on it while the EglDisplay is alive.
+  pub unsafe fn initialize_platform (display: EGLDisplay) -> Result<EglDisplay, EglError> {
+    EglDisplay::initialize_with(display, true)
+  }
+
+  unsafe fn initialize_with (display: EGLDisplay, platform: bool) -> Result<EglDisplay, EglError> {
+    if display == EGL_NO_DISPLAY {
+      return Err(EglError::NoDisplay);
+    }
+
+    let (mut major, mut minor) = (0, 0);
+    if eglInitialize(display, &mut major, &mut minor) != EGL_TRUE {
+      return Err(EglError::Failed(eglGetError()));
+    }
+
+    Ok(EglDisplay {
+      display,
+      version: (major, minor),
+      platform,
+    })
+  }
+
+  /// Extensions that don't depend on a display, such as the platform
+  /// extensions. Empty without EGL_EXT_client_extensions.
+  ///
+  /// # Safety
+  ///
+  /// Calls eglQueryString without a display, so the EGL library must be
+  /// usable from this thread.
+  pub unsafe fn client_extensions () -> Vec<String> {
+    let string = eglQueryString(EGL_NO_DISPLAY, EGL_EXTENSIONS);
+    if string.is_null() {
+      eglGetError();
+      return Vec::new();
+    }
+
+    split_names(string)
+  }
+
+  pub fn raw (&self) -> EGLDisplay {
+    self.display
+  }
+
+  pub fn version (&self) -> (EGLint, EGLint) {
+    self.version
+  }
+
+  // Whether the display came from eglGetPlatformDisplayEXT.
+  pub fn is_platform (&self) -> bool {
+    self.platform
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn query_string (&self, name: EGLint) -> Option<String> {
+    let string = eglQueryString(self.display, name);
+    if string.is_null() {
+      None
+    } else {
+      Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn extensions (&self) -> Vec<String> {
+    let string = eglQueryString(self.display, EGL_EXTENSIONS);
+    if string.is_null() {
+      return Vec::new();
+    }
+
+    split_names(string)
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn has_extension (&self, name: &str) -> bool {
+    self.extensions().iter().any(|n| n == name)
+  }
+
+  /// The best config matching an attribute list. The EGL_NONE terminator is
+  /// optional.
+  ///
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn choose_config (&self, attrs: &[EGLint]) -> Option<EGLConfig> {
+    self.choose_configs(attrs).into_iter().next()
+  }
+
+  /// Configs matching an attribute list, best match first.
+  ///
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn choose_configs (&self, attrs: &[EGLint]) -> Vec<EGLConfig> {
+    let attrs = terminated(attrs);
+    let mut count = 0;
+    if eglChooseConfig(self.display, attrs.as_ptr(), null_mut(), 0, &mut count) != EGL_TRUE || count <= 0 {
+      return Vec::new();
+    }
+
+    let mut configs = vec![null_mut(); count as usize];
+    if eglChooseConfig(self.display, attrs.as_ptr(), configs.as_mut_ptr(), count, &mut count) != EGL_TRUE {
+      return Vec::new();
+    }
+
+    configs.truncate(count.max(0) as usize);
+    configs
+  }
+
+  /// The first config matching `attrs` whose native visual is `visual_id`,
+  /// e.g. the visual of an existing window.
+  ///
+  /// # Safety
+  ///
+  /// The EglDisplay must not have been terminated by other code, e.g. with
+  /// eglTerminate on the same handle.
+  pub unsafe fn choose_config_for_visual (&self, attrs: &[EGLint], visual_id: VisualID) -> Option<EGLConfig> {
+    self.choose_configs(attrs).into_iter()
+      .find(|&config| self.config_attrib(config, EGL_NATIVE_VISUAL_ID) == Some(visual_id as EGLint))
+  }
+
+  /// # Safety
+  ///
+  /// `config` must be a config of this display.
+  pub unsafe fn config_attrib (&self, config: EGLConfig, attribute: EGLint) -> Option<EGLint> {
+    let mut value = 0;
+    match eglGetConfigAttrib(self.display, config, attribute, &mut value) {
+      EGL_TRUE => Some(value),
+      _ => None,
+    }
+  }
+
+  /// The X visual of `config` (EGL_NATIVE_VISUAL_ID), suitable for creating a
+  /// window to render to. The result must be freed with XFree.
+  ///
+  /// # Safety
+  ///
+  /// `config` must be a config of this display, and `display` the open Xlib
+  /// connection it was created from.
+  pub unsafe fn visual_info (&self, display: *mut Display, config: EGLConfig) -> Result<*mut XVisualInfo, EglError> {
+    let visual_id = match self.config_attrib(config, EGL_NATIVE_VISUAL_ID) {
+      Some(visual_id) if visual_id != 0 => visual_id,
+      _ => { return Err(EglError::NoVisual); },
+    };
+
+    let mut template: XVisualInfo = zeroed();
+    template.visualid = visual_id as VisualID;
+    let mut count = 0;
+    let visual_info = XGetVisualInfo(display, VisualIDMask as c_long, &template, &mut count);
+    if visual_info.is_null() || count < 1 {
+      return Err(EglError::NoVisual);
+    }
+
+    Ok(visual_info)
+  }
+}
+
+impl Drop for EglDisplay {
+  fn drop (&mut self) {
+    unsafe {
+      // Only release the thread's context if it belongs to this display.
+      if eglGetCurrentDisplay() == self.display {
+        eglMakeCurrent(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT);
+      }
+      eglTerminate(self.display);
+    }
+  }
+}
+
+// An EGL window or pbuffer surface, destroyed on drop.
+pub struct EglSurface {
+  display: EGLDisplay,
+  surface: EGLSurface,
+}
+
+impl EglSurface {
+  /// Creates a surface for an X window. The window must have been created
+  /// with the config's native visual (see EglDisplay::visual_info). Platform
+  /// displays go through eglCreatePlatformWindowSurfaceEXT, which takes a
+  /// pointer to the Window rather than the Window itself.
+  ///
+  /// # Safety
+  ///
+  /// `config` must be a config of `display`, and `window` a live window on
+  /// the Xlib connection `display` was created from.
+  pub unsafe fn window (display: &EglDisplay, config: EGLConfig, window: Window, attrs: &[EGLint]) -> Result<EglSurface, EglError> {
+    let attrs = terminated(attrs);
+    let surface = if display.is_platform() {
+      let create_platform_window_surface = match load_eglCreatePlatformWindowSurfaceEXT() {
+        Some(f) => f,
+        None => { return Err(EglError::MissingExtension(EGL_EXT_platform_base)); },
+      };
+      let mut window = window;
+      create_platform_window_surface(display.raw(), config, &mut window as *mut Window as *mut _, attrs.as_ptr())
+    } else {
+      eglCreateWindowSurface(display.raw(), config, window, attrs.as_ptr())
+    };
+    EglSurface::from_raw(display, surface)
+  }
+
+  /// # Safety
+  ///
+  /// `config` must be a config of `display`.
+  pub unsafe fn pbuffer (display: &EglDisplay, config: EGLConfig, width: EGLint, height: EGLint) -> Result<EglSurface, EglError> {
+    let attrs = [EGL_WIDTH, width, EGL_HEIGHT, height, EGL_NONE];
+    let surface = eglCreatePbufferSurface(display.raw(), config, attrs.as_ptr());
+    EglSurface::from_raw(display, surface)
+  }
+
+  /// Takes ownership of `surface`; fails with eglGetError if it is
+  /// EGL_NO_SURFACE.
+  ///
+  /// # Safety
+  ///
+  /// `surface` must have been created on `display` and must not be owned by
+  /// anything else.
+  pub unsafe fn from_raw (display: &EglDisplay, surface: EGLSurface) -> Result<EglSurface, EglError> {
+    if surface == EGL_NO_SURFACE {
+      return Err(EglError::Failed(eglGetError()));
+    }
+
+    Ok(EglSurface {
+      display: display.raw(),
+      surface,
+    })
+  }
+
+  pub fn raw (&self) -> EGLSurface {
+    self.surface
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay the surface was created on must still be alive.
+  pub unsafe fn query (&self, attribute: EGLint) -> Option<EGLint> {
+    let mut value = 0;
+    match eglQuerySurface(self.display, self.surface, attribute, &mut value) {
+      EGL_TRUE => Some(value),
+      _ => None,
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay the surface was created on must still be alive.
+  pub unsafe fn size (&self) -> (EGLint, EGLint) {
+    (self.query(EGL_WIDTH).unwrap_or(0), self.query(EGL_HEIGHT).unwrap_or(0))
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay the surface was created on must still be alive.
+  pub unsafe fn swap_buffers (&self) -> Result<(), EglError> {
+    match eglSwapBuffers(self.display, self.surface) {
+      EGL_TRUE => Ok(()),
+      _ => Err(EglError::Failed(eglGetError())),
+    }
+  }
+
+  /// Sets the swap interval of the surface bound to the current context.
+  ///
+  /// # Safety
+  ///
+  /// The EglDisplay the surface was created on must still be alive, and the
+  /// context current on this thread must draw to this surface.
+  pub unsafe fn set_swap_interval (&self, interval: EGLint) -> Result<(), EglError> {
+    match eglSwapInterval(self.display, interval) {
+      EGL_TRUE => Ok(()),
+      _ => Err(EglError::Failed(eglGetError())),
+    }
+  }
+}
+
+impl Drop for EglSurface {
+  fn drop (&mut self) {
+    unsafe {
+      if eglGetCurrentSurface(EGL_DRAW) == self.surface || eglGetCurrentSurface(EGL_READ) == self.surface {
+        eglMakeCurrent(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT);
+      }
+
+      eglDestroySurface(self.display, self.surface);
+    }
+  }
+}
+
+// An EGL rendering context, unbound (if current) and destroyed on drop.
+pub struct EglContext {
+  display: EGLDisplay,
+  context: EGLContext,
+  config: EGLConfig,
+}
+
+impl EglContext {
+  /// Binds `api` (e.g. EGL_OPENGL_ES_API) and creates a context for it from
+  /// an attribute list such as [EGL_CONTEXT_CLIENT_VERSION, 2].
+  ///
+  /// # Safety
+  ///
+  /// `config` must be a config of `display`, and `share_context`, if any, a
+  /// context of the same display.
+  pub unsafe fn new (display: &EglDisplay, config: EGLConfig, api: EGLenum, attrs: &[EGLint], share_context: Option<&EglContext>) -> Result<EglContext, EglError> {
+    if eglBindAPI(api) != EGL_TRUE {
+      return Err(EglError::Failed(eglGetError()));
+    }
+
+    let attrs = terminated(attrs);
+    let share_context = share_context.map_or(EGL_NO_CONTEXT, |c| c.context);
+    let context = eglCreateContext(display.raw(), config, share_context, attrs.as_ptr());
+    if context == EGL_NO_CONTEXT {
+      return Err(EglError::Failed(eglGetError()));
+    }
+
+    Ok(EglContext {
+      display: display.raw(),
+      context,
+      config,
+    })
+  }
+
+  /// An OpenGL ES context of the given major version.
+  ///
+  /// # Safety
+  ///
+  /// `config` must be a config of `display`.
+  pub unsafe fn gles (display: &EglDisplay, config: EGLConfig, version: EGLint) -> Result<EglContext, EglError> {
+    EglContext::new(display, config, EGL_OPENGL_ES_API, &[EGL_CONTEXT_CLIENT_VERSION, version], None)
+  }
+
+  pub fn raw (&self) -> EGLContext {
+    self.context
+  }
+
+  pub fn config (&self) -> EGLConfig {
+    self.config
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay the context was created on must still be alive, and
+  /// `surface` must belong to it and be compatible with the context's config.
+  pub unsafe fn make_current (&self, surface: &EglSurface) -> Result<(), EglError> {
+    self.make_current_raw(surface.raw(), surface.raw())
+  }
+
+  /// Binds the context without a surface (EGL_KHR_surfaceless_context).
+  ///
+  /// # Safety
+  ///
+  /// The EglDisplay the context was created on must still be alive.
+  pub unsafe fn make_current_surfaceless (&self) -> Result<(), EglError> {
+    self.make_current_raw(EGL_NO_SURFACE, EGL_NO_SURFACE)
+  }
+
+  /// # Safety
+  ///
+  /// The EglDisplay the context was created on must still be alive.
+  pub unsafe fn is_current (&self) -> bool {
+    eglGetCurrentContext() == self.context
+  }
+
+  unsafe fn make_current_raw (&self, draw: EGLSurface, read: EGLSurface) -> Result<(), EglError> {
+    match eglMakeCurrent(self.display, draw, read, self.context) {
+      EGL_TRUE => Ok(()),
+      _ => Err(EglError::Failed(eglGetError())),
+    }
+  }
+}
+
+impl Drop for EglContext {
+  fn drop (&mut self) {
+    unsafe {
+      if self.is_current() {
+        eglMakeCurrent(self.display, EGL_NO_SURFACE, EGL_NO_SURFACE, EGL_NO_CONTEXT);
+      }
+
+      eglDestroyContext(self.display, self.context);
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+unsafe fn split_names (string: *const c_char) -> Vec<String> {
+  CStr::from_ptr(string).to_string_lossy()
+    .split_whitespace()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+// Copies key/value pairs up to the first EGL_NONE key and terminates them.
+// Values may equal EGL_NONE, so only keys are checked. A trailing key
+// without a value is a caller bug and is dropped in release builds.
+fn terminated (attrs: &[EGLint]) -> Vec<EGLint> {
+  let mut terminated = Vec::with_capacity(attrs.len() + 1);
+  for pair in attrs.chunks(2) {
+    if pair[0] == EGL_NONE {
+      break;
+    }
+    debug_assert!(pair.len() == 2, "EGL attribute {:#x} has no value", pair[0]);
+    if pair.len() < 2 {
+      break;
+    }
+    terminated.extend_from_slice(pair);
+  }
+  terminated.push(EGL_NONE);
+  terminated
+}
+
+#[test]
+fn egl_attribute_list_test () {
+  assert_eq!(terminated(&[]), vec![EGL_NONE]);
+  assert_eq!(terminated(&[EGL_WIDTH, 8]), vec![EGL_WIDTH, 8, EGL_NONE]);
+  assert_eq!(terminated(&[EGL_WIDTH, 8, EGL_NONE]), vec![EGL_WIDTH, 8, EGL_NONE]);
+  // A value that happens to equal EGL_NONE doesn't end the list.
+  assert_eq!(terminated(&[EGL_TEXTURE_FORMAT, EGL_NONE, EGL_HEIGHT, 4]), vec![EGL_TEXTURE_FORMAT, EGL_NONE, EGL_HEIGHT, 4, EGL_NONE]);
+  assert_eq!(terminated(&[EGL_WIDTH, 8, EGL_NONE, 0, EGL_HEIGHT, 4]), vec![EGL_WIDTH, 8, EGL_NONE]);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "has no value")]
+fn egl_attribute_list_dangling_key_test () {
+  terminated(&[EGL_WIDTH, 8, EGL_HEIGHT]);
+}
+
+// Uses Mesa's surfaceless platform (llvmpipe when there is no GPU), so it
+// needs no X server, only Mesa's EGL; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn egl_surfaceless_pbuffer_test () {
+  use std::env::set_var;
+
+  unsafe {
+    set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+    let display = EglDisplay::surfaceless().expect("no surfaceless EGL platform");
+    assert!(display.version() >= (1, 4));
+
+    let attrs = [
+      EGL_SURFACE_TYPE, EGL_PBUFFER_BIT,
+      EGL_RENDERABLE_TYPE, EGL_OPENGL_ES2_BIT,
+      EGL_RED_SIZE, 8,
+      EGL_GREEN_SIZE, 8,
+      EGL_BLUE_SIZE, 8,
+      EGL_NONE,
+    ];
+    let config = display.choose_config(&attrs).expect("no ES2 pbuffer config");
+
+    {
+      let surface = EglSurface::pbuffer(&display, config, 16, 8).unwrap();
+      let context = EglContext::gles(&display, config, 2).unwrap();
+      context.make_current(&surface).unwrap();
+      assert!(context.is_current());
+      assert_eq!(surface.size(), (16, 8));
+      surface.swap_buffers().unwrap();
+    }
+
+    assert!(eglGetCurrentContext() == EGL_NO_CONTEXT);
+  }
+}
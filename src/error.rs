@@ -0,0 +1,165 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::sync::Mutex;
+
+use libc::{
+  c_int,
+  c_uchar,
+  c_ulong,
+};
+
+use ::xlib::{
+  Display,
+  ErrorHandler,
+  False,
+  XErrorEvent,
+  XID,
+  XSetErrorHandler,
+  XSync,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrappedError {
+  pub serial: c_ulong,
+  pub error_code: c_uchar,
+  pub request_code: c_uchar,
+  pub minor_code: c_uchar,
+  pub resourceid: XID,
+}
+
+// Collects protocol errors instead of letting the default Xlib handler
+// terminate the process. Xlib error handlers are process-global, so a trap
+// applies to every display until it is dropped. Traps may be nested; each
+// one only sees the errors raised while it is the innermost trap.
+pub struct ErrorTrap {
+  display: *mut Display,
+  previous: ErrorHandler,
+  start: usize,
+}
+
+impl ErrorTrap {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the trap. Nested
+  /// traps must be dropped innermost first, or the wrong error handler is
+  /// restored.
+  pub unsafe fn new (display: *mut Display) -> ErrorTrap {
+    XSync(display, False);
+    let start = mark();
+    let previous = XSetErrorHandler(Some(trap_handler));
+
+    ErrorTrap {
+      display,
+      previous,
+      start,
+    }
+  }
+
+  // Waits for the server to process all pending requests and returns the
+  // errors they raised so far.
+  pub fn errors (&self) -> Vec<TrappedError> {
+    unsafe { XSync(self.display, False); }
+    errors_since(self.start)
+  }
+
+  pub fn finish (self) -> Result<(), Vec<TrappedError>> {
+    let errors = self.errors();
+
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+}
+
+impl Drop for ErrorTrap {
+  fn drop (&mut self) {
+    unsafe {
+      XSync(self.display, False);
+      XSetErrorHandler(self.previous);
+    }
+
+    release(self.start);
+  }
+}
+
+
+//
+// private functions
+//
+
+
+static TRAPPED_ERRORS: Mutex<Vec<TrappedError>> = Mutex::new(Vec::new());
+
+fn lock_errors () -> ::std::sync::MutexGuard<'static, Vec<TrappedError>> {
+  match TRAPPED_ERRORS.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
+
+// A trap owns the errors from its mark to the end of TRAPPED_ERRORS and
+// discards them when it ends, so the enclosing trap never sees them.
+fn mark () -> usize {
+  lock_errors().len()
+}
+
+fn errors_since (start: usize) -> Vec<TrappedError> {
+  lock_errors()[start..].to_vec()
+}
+
+fn release (start: usize) {
+  lock_errors().truncate(start);
+}
+
+unsafe extern "C" fn trap_handler (_display: *mut Display, event: *const XErrorEvent) -> c_int {
+  let event = &*event;
+
+  lock_errors().push(TrappedError {
+    serial: event.serial,
+    error_code: event.error_code,
+    request_code: event.request_code,
+    minor_code: event.minor_code,
+    resourceid: event.resourceid,
+  });
+
+  0
+}
+
+#[test]
+fn error_trap_nesting_test () {
+  use std::mem::zeroed;
+  use std::ptr::null_mut;
+
+  unsafe fn raise (serial: c_ulong) -> TrappedError {
+    let mut event: XErrorEvent = zeroed();
+    event.serial = serial;
+    event.error_code = 10;
+    trap_handler(null_mut(), &event);
+    TrappedError { serial, error_code: 10, request_code: 0, minor_code: 0, resourceid: 0 }
+  }
+
+  unsafe {
+    let outer = mark();
+    let first = raise(1);
+
+    let inner = mark();
+    assert!(errors_since(inner).is_empty());
+    let second = raise(2);
+    assert_eq!(errors_since(inner), vec![second]);
+    release(inner);
+
+    assert_eq!(errors_since(outer), vec![first]);
+    release(outer);
+    assert_eq!(mark(), outer);
+  }
+}
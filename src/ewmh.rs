@@ -0,0 +1,585 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::mem::zeroed;
+
+use libc::{
+  c_int,
+  c_long,
+};
+
+use ::atom::AtomCache;
+use ::property::{
+  PropertyData,
+  get_property,
+  set_atoms,
+  set_cardinals,
+  set_property,
+  set_utf8,
+};
+use ::xlib::*;
+
+pub mod wm;
+
+
+//
+// types
+//
+
+
+// EWMH requests and queries for the windows on one screen; the window
+// manager's side is in `wm`. All _NET atoms are interned up front in a
+// single round trip.
+pub struct Ewmh {
+  display: *mut Display,
+  root: Window,
+  atoms: AtomCache,
+}
+
+impl Ewmh {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the Ewmh, and
+  /// `screen` one of its screens.
+  pub unsafe fn new (display: *mut Display, screen: c_int) -> Ewmh {
+    Ewmh {
+      display,
+      root: XRootWindow(display, screen),
+      atoms: AtomCache::with_names(display, &NET_ATOMS),
+    }
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  pub fn root (&self) -> Window {
+    self.root
+  }
+
+  // The interned atom for one of NET_ATOMS, or 0 for any other name.
+  pub fn atom (&self, name: &str) -> Atom {
+    self.atoms.get(name).unwrap_or(0)
+  }
+
+  pub fn atoms (&mut self) -> &mut AtomCache {
+    &mut self.atoms
+  }
+
+  /// The hints the running window manager claims to support, from
+  /// _NET_SUPPORTED on the root window.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open.
+  pub unsafe fn supported (&self) -> Vec<Atom> {
+    self.get_atoms(self.root, "_NET_SUPPORTED", XA_ATOM)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open.
+  pub unsafe fn is_supported (&self, name: &str) -> bool {
+    let atom = self.atom(name);
+    atom != 0 && self.supported().contains(&atom)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_name (&self, window: Window, name: &str) {
+    set_utf8(self.display, window, self.atom("_NET_WM_NAME"), name);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn name (&self, window: Window) -> Option<String> {
+    let property = get_property(self.display, window, self.atom("_NET_WM_NAME"), self.atom("UTF8_STRING")).ok()??;
+    property.utf8()
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_icon_name (&self, window: Window, name: &str) {
+    set_utf8(self.display, window, self.atom("_NET_WM_ICON_NAME"), name);
+  }
+
+  /// Replaces _NET_WM_ICON with `icons`, typically the same image at a few
+  /// sizes. The window manager picks the best fit.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_icons (&self, window: Window, icons: &[Icon]) {
+    let data = PropertyData::Format32(encode_icons(icons));
+    set_property(self.display, window, self.atom("_NET_WM_ICON"), XA_CARDINAL, &data, PropModeReplace);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn icons (&self, window: Window) -> Vec<Icon> {
+    match get_property(self.display, window, self.atom("_NET_WM_ICON"), XA_CARDINAL) {
+      Ok(Some(property)) => property.u32s().map(decode_icons).unwrap_or_default(),
+      _ => Vec::new(),
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_pid (&self, window: Window, pid: u32) {
+    set_cardinals(self.display, window, self.atom("_NET_WM_PID"), &[pid]);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn pid (&self, window: Window) -> Option<u32> {
+    self.get_cardinal(window, "_NET_WM_PID")
+  }
+
+  /// Most preferred type first; later entries are fallbacks for window
+  /// managers that don't know the earlier ones.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_window_type (&self, window: Window, types: &[WindowType]) {
+    let atoms: Vec<Atom> = types.iter().map(|kind| self.atom(kind.name())).collect();
+    set_atoms(self.display, window, self.atom("_NET_WM_WINDOW_TYPE"), &atoms);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn window_type (&self, window: Window) -> Vec<WindowType> {
+    self.get_atoms(window, "_NET_WM_WINDOW_TYPE", XA_ATOM).into_iter()
+      .filter_map(|atom| WindowType::ALL.iter().cloned().find(|kind| self.atom(kind.name()) == atom))
+      .collect()
+  }
+
+  /// Sets the initial _NET_WM_STATE of a window that isn't mapped yet. Once
+  /// it is mapped, only the window manager may change the property; use
+  /// request_state instead.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_initial_state (&self, window: Window, states: &[WmState]) {
+    let atoms: Vec<Atom> = states.iter().map(|state| self.atom(state.name())).collect();
+    set_atoms(self.display, window, self.atom("_NET_WM_STATE"), &atoms);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn state (&self, window: Window) -> Vec<WmState> {
+    self.get_atoms(window, "_NET_WM_STATE", XA_ATOM).into_iter()
+      .filter_map(|atom| WmState::ALL.iter().cloned().find(|state| self.atom(state.name()) == atom))
+      .collect()
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn has_state (&self, window: Window, state: WmState) -> bool {
+    self.state(window).contains(&state)
+  }
+
+  /// Asks the window manager to change up to two states of a mapped window
+  /// at once, as needed for maximizing in both directions.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn request_state (&self, window: Window, action: StateAction, first: WmState, second: Option<WmState>) -> bool {
+    let second = second.map_or(0, |state| self.atom(state.name()));
+    let data = [action as c_long, self.atom(first.name()) as c_long, second as c_long, SOURCE_APPLICATION, 0];
+    self.send_to_root(window, "_NET_WM_STATE", data)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_fullscreen (&self, window: Window, fullscreen: bool) -> bool {
+    self.request_state(window, StateAction::from(fullscreen), WmState::Fullscreen, None)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_maximized (&self, window: Window, maximized: bool) -> bool {
+    self.request_state(window, StateAction::from(maximized), WmState::MaximizedVert, Some(WmState::MaximizedHorz))
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_above (&self, window: Window, above: bool) -> bool {
+    self.request_state(window, StateAction::from(above), WmState::Above, None)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open.
+  pub unsafe fn active_window (&self) -> Option<Window> {
+    self.get_atoms(self.root, "_NET_ACTIVE_WINDOW", XA_WINDOW).first().cloned().filter(|&window| window != 0)
+  }
+
+  /// Asks the window manager to activate `window`. `time` is the timestamp
+  /// of the user action that caused the request and `current` the
+  /// application's currently active window, if any.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn request_active (&self, window: Window, time: Time, current: Window) -> bool {
+    let data = [SOURCE_APPLICATION, time as c_long, current as c_long, 0, 0];
+    self.send_to_root(window, "_NET_ACTIVE_WINDOW", data)
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn set_bypass_compositor (&self, window: Window, mode: BypassCompositor) {
+    set_cardinals(self.display, window, self.atom("_NET_WM_BYPASS_COMPOSITOR"), &[mode as u32]);
+  }
+
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn bypass_compositor (&self, window: Window) -> BypassCompositor {
+    match self.get_cardinal(window, "_NET_WM_BYPASS_COMPOSITOR") {
+      Some(1) => BypassCompositor::Disable,
+      Some(2) => BypassCompositor::Enable,
+      _ => BypassCompositor::NoPreference,
+    }
+  }
+
+  /// Sends a format 32 client message about `window` to the root window, the
+  /// way EWMH requests reach the window manager.
+  ///
+  /// # Safety
+  ///
+  /// The display given to Ewmh::new must still be open, and `window` must be
+  /// a window on it.
+  pub unsafe fn send_to_root (&self, window: Window, message_type: &str, data: [c_long; 5]) -> bool {
+    let event = client_message(window, self.atom(message_type), data);
+    XSendEvent(self.display, self.root, False, SubstructureRedirectMask | SubstructureNotifyMask, &event) != 0
+  }
+
+  unsafe fn get_atoms (&self, window: Window, property: &str, kind: Atom) -> Vec<Atom> {
+    match get_property(self.display, window, self.atom(property), kind) {
+      Ok(Some(property)) => property.atoms().unwrap_or_default(),
+      _ => Vec::new(),
+    }
+  }
+
+  unsafe fn get_cardinal (&self, window: Window, property: &str) -> Option<u32> {
+    let property = get_property(self.display, window, self.atom(property), XA_CARDINAL).ok()??;
+    property.u32s()?.first().cloned()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BypassCompositor {
+  NoPreference = 0,
+  // Unredirect the window, e.g. for fullscreen games.
+  Disable = 1,
+  Enable = 2,
+}
+
+// A _NET_WM_ICON image: non-premultiplied ARGB pixels, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Icon {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StateAction {
+  Remove = 0,
+  Add = 1,
+  Toggle = 2,
+}
+
+impl From<bool> for StateAction {
+  fn from (add: bool) -> StateAction {
+    if add { StateAction::Add } else { StateAction::Remove }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowType {
+  Desktop,
+  Dock,
+  Toolbar,
+  Menu,
+  Utility,
+  Splash,
+  Dialog,
+  DropdownMenu,
+  PopupMenu,
+  Tooltip,
+  Notification,
+  Combo,
+  Dnd,
+  Normal,
+}
+
+impl WindowType {
+  pub const ALL: [WindowType; 14] = [
+    WindowType::Desktop, WindowType::Dock, WindowType::Toolbar, WindowType::Menu, WindowType::Utility,
+    WindowType::Splash, WindowType::Dialog, WindowType::DropdownMenu, WindowType::PopupMenu, WindowType::Tooltip,
+    WindowType::Notification, WindowType::Combo, WindowType::Dnd, WindowType::Normal,
+  ];
+
+  pub fn name (self) -> &'static str {
+    match self {
+      WindowType::Desktop => "_NET_WM_WINDOW_TYPE_DESKTOP",
+      WindowType::Dock => "_NET_WM_WINDOW_TYPE_DOCK",
+      WindowType::Toolbar => "_NET_WM_WINDOW_TYPE_TOOLBAR",
+      WindowType::Menu => "_NET_WM_WINDOW_TYPE_MENU",
+      WindowType::Utility => "_NET_WM_WINDOW_TYPE_UTILITY",
+      WindowType::Splash => "_NET_WM_WINDOW_TYPE_SPLASH",
+      WindowType::Dialog => "_NET_WM_WINDOW_TYPE_DIALOG",
+      WindowType::DropdownMenu => "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU",
+      WindowType::PopupMenu => "_NET_WM_WINDOW_TYPE_POPUP_MENU",
+      WindowType::Tooltip => "_NET_WM_WINDOW_TYPE_TOOLTIP",
+      WindowType::Notification => "_NET_WM_WINDOW_TYPE_NOTIFICATION",
+      WindowType::Combo => "_NET_WM_WINDOW_TYPE_COMBO",
+      WindowType::Dnd => "_NET_WM_WINDOW_TYPE_DND",
+      WindowType::Normal => "_NET_WM_WINDOW_TYPE_NORMAL",
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WmState {
+  Modal,
+  Sticky,
+  MaximizedVert,
+  MaximizedHorz,
+  Shaded,
+  SkipTaskbar,
+  SkipPager,
+  Hidden,
+  Fullscreen,
+  Above,
+  Below,
+  DemandsAttention,
+  Focused,
+}
+
+impl WmState {
+  pub const ALL: [WmState; 13] = [
+    WmState::Modal, WmState::Sticky, WmState::MaximizedVert, WmState::MaximizedHorz, WmState::Shaded,
+    WmState::SkipTaskbar, WmState::SkipPager, WmState::Hidden, WmState::Fullscreen, WmState::Above,
+    WmState::Below, WmState::DemandsAttention, WmState::Focused,
+  ];
+
+  pub fn name (self) -> &'static str {
+    match self {
+      WmState::Modal => "_NET_WM_STATE_MODAL",
+      WmState::Sticky => "_NET_WM_STATE_STICKY",
+      WmState::MaximizedVert => "_NET_WM_STATE_MAXIMIZED_VERT",
+      WmState::MaximizedHorz => "_NET_WM_STATE_MAXIMIZED_HORZ",
+      WmState::Shaded => "_NET_WM_STATE_SHADED",
+      WmState::SkipTaskbar => "_NET_WM_STATE_SKIP_TASKBAR",
+      WmState::SkipPager => "_NET_WM_STATE_SKIP_PAGER",
+      WmState::Hidden => "_NET_WM_STATE_HIDDEN",
+      WmState::Fullscreen => "_NET_WM_STATE_FULLSCREEN",
+      WmState::Above => "_NET_WM_STATE_ABOVE",
+      WmState::Below => "_NET_WM_STATE_BELOW",
+      WmState::DemandsAttention => "_NET_WM_STATE_DEMANDS_ATTENTION",
+      WmState::Focused => "_NET_WM_STATE_FOCUSED",
+    }
+  }
+}
+
+
+//
+// functions
+//
+
+
+// A format 32 ClientMessage event about `window`, ready for XSendEvent.
+pub fn client_message (window: Window, message_type: Atom, data: [c_long; 5]) -> XEvent {
+  let mut event: XClientMessageEvent = unsafe { zeroed() };
+  event.kind = ClientMessage;
+  event.window = window;
+  event.message_type = message_type;
+  event.format = 32;
+  for (i, &value) in data.iter().enumerate() {
+    event.data.set_long(i, value);
+  }
+  XEvent::from(event)
+}
+
+// Flattens icons into _NET_WM_ICON data: width, height, then the pixels of
+// each icon in turn.
+pub fn encode_icons (icons: &[Icon]) -> Vec<u32> {
+  let mut data = Vec::with_capacity(icons.iter().map(|icon| 2 + icon.pixels.len()).sum());
+  for icon in icons {
+    data.push(icon.width);
+    data.push(icon.height);
+    data.extend_from_slice(&icon.pixels);
+  }
+  data
+}
+
+// Splits _NET_WM_ICON data into icons, stopping at the first truncated or
+// empty entry.
+pub fn decode_icons (mut data: &[u32]) -> Vec<Icon> {
+  let mut icons = Vec::new();
+  while data.len() >= 2 {
+    let (width, height) = (data[0], data[1]);
+    let len = width as usize * height as usize;
+    if len == 0 || data.len() - 2 < len {
+      break;
+    }
+
+    icons.push(Icon { width, height, pixels: data[2..2 + len].to_vec() });
+    data = &data[2 + len..];
+  }
+  icons
+}
+
+
+//
+// constants
+//
+
+
+// The atoms Ewmh interns on creation.
+pub const NET_ATOMS: [&str; 46] = [
+  "UTF8_STRING",
+  "_NET_ACTIVE_WINDOW",
+  "_NET_CLIENT_LIST",
+  "_NET_CLIENT_LIST_STACKING",
+  "_NET_CLOSE_WINDOW",
+  "_NET_CURRENT_DESKTOP",
+  "_NET_FRAME_EXTENTS",
+  "_NET_MOVERESIZE_WINDOW",
+  "_NET_NUMBER_OF_DESKTOPS",
+  "_NET_SUPPORTED",
+  "_NET_SUPPORTING_WM_CHECK",
+  "_NET_WM_BYPASS_COMPOSITOR",
+  "_NET_WM_ICON",
+  "_NET_WM_ICON_NAME",
+  "_NET_WM_NAME",
+  "_NET_WM_PID",
+  "_NET_WM_STATE",
+  "_NET_WM_STATE_ABOVE",
+  "_NET_WM_STATE_BELOW",
+  "_NET_WM_STATE_DEMANDS_ATTENTION",
+  "_NET_WM_STATE_FOCUSED",
+  "_NET_WM_STATE_FULLSCREEN",
+  "_NET_WM_STATE_HIDDEN",
+  "_NET_WM_STATE_MAXIMIZED_HORZ",
+  "_NET_WM_STATE_MAXIMIZED_VERT",
+  "_NET_WM_STATE_MODAL",
+  "_NET_WM_STATE_SHADED",
+  "_NET_WM_STATE_SKIP_PAGER",
+  "_NET_WM_STATE_SKIP_TASKBAR",
+  "_NET_WM_STATE_STICKY",
+  "_NET_WM_WINDOW_TYPE",
+  "_NET_WM_WINDOW_TYPE_COMBO",
+  "_NET_WM_WINDOW_TYPE_DESKTOP",
+  "_NET_WM_WINDOW_TYPE_DIALOG",
+  "_NET_WM_WINDOW_TYPE_DND",
+  "_NET_WM_WINDOW_TYPE_DOCK",
+  "_NET_WM_WINDOW_TYPE_DROPDOWN_MENU",
+  "_NET_WM_WINDOW_TYPE_MENU",
+  "_NET_WM_WINDOW_TYPE_NORMAL",
+  "_NET_WM_WINDOW_TYPE_NOTIFICATION",
+  "_NET_WM_WINDOW_TYPE_POPUP_MENU",
+  "_NET_WM_WINDOW_TYPE_SPLASH",
+  "_NET_WM_WINDOW_TYPE_TOOLBAR",
+  "_NET_WM_WINDOW_TYPE_TOOLTIP",
+  "_NET_WM_WINDOW_TYPE_UTILITY",
+  "_NET_WORKAREA",
+];
+
+// Source indication for requests from normal applications.
+const SOURCE_APPLICATION: c_long = 1;
+
+#[test]
+fn ewmh_icons_test () {
+  let icons = vec![
+    Icon { width: 2, height: 1, pixels: vec![0xff00_00ff, 0x8000_ff00] },
+    Icon { width: 1, height: 1, pixels: vec![0xffff_ffff] },
+  ];
+  let data = encode_icons(&icons);
+  assert_eq!(data, vec![2, 1, 0xff00_00ff, 0x8000_ff00, 1, 1, 0xffff_ffff]);
+  assert_eq!(decode_icons(&data), icons);
+
+  // A truncated trailing icon is dropped.
+  assert_eq!(decode_icons(&data[..6]), &icons[..1]);
+  assert_eq!(decode_icons(&[0, 0, 1]), vec![]);
+
+  let event = XClientMessageEvent::from(client_message(7, 42, [1, 2, 3, 4, 5]));
+  assert_eq!((event.kind, event.window, event.message_type, event.format), (ClientMessage, 7, 42, 32));
+  assert_eq!(event.data.get_long(4), 5);
+}
+
+#[test]
+#[ignore]
+fn ewmh_properties_test () {
+  use std::ptr::null;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let ewmh = Ewmh::new(display, XDefaultScreen(display));
+    let window = XCreateSimpleWindow(display, ewmh.root(), 0, 0, 1, 1, 0, 0, 0);
+
+    ewmh.set_name(window, "Grüße");
+    assert_eq!(ewmh.name(window), Some("Grüße".to_string()));
+
+    ewmh.set_pid(window, 4242);
+    assert_eq!(ewmh.pid(window), Some(4242));
+
+    let icons = vec![Icon { width: 2, height: 2, pixels: vec![0xffff_0000, 0, 0x80ff_ffff, 0xff00_00ff] }];
+    ewmh.set_icons(window, &icons);
+    assert_eq!(ewmh.icons(window), icons);
+
+    ewmh.set_window_type(window, &[WindowType::Dialog, WindowType::Normal]);
+    assert_eq!(ewmh.window_type(window), vec![WindowType::Dialog, WindowType::Normal]);
+
+    ewmh.set_initial_state(window, &[WmState::Fullscreen, WmState::Above]);
+    assert!(ewmh.has_state(window, WmState::Fullscreen));
+    assert!(!ewmh.has_state(window, WmState::Hidden));
+
+    ewmh.set_bypass_compositor(window, BypassCompositor::Disable);
+    assert_eq!(ewmh.bypass_compositor(window), BypassCompositor::Disable);
+
+    XDestroyWindow(display, window);
+    XCloseDisplay(display);
+  }
+}
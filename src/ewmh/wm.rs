@@ -0,0 +1,364 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_int,
+  c_long,
+};
+
+use ::ewmh::{
+  Ewmh,
+  StateAction,
+  WmState,
+};
+use ::property::{
+  PropertyData,
+  set_atoms,
+  set_cardinals,
+  set_property,
+};
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+// A decoded client message asking the window manager to do something.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientRequest {
+  // _NET_WM_STATE: change one or two states at once.
+  State {
+    window: Window,
+    action: StateAction,
+    states: Vec<WmState>,
+    source: RequestSource,
+  },
+  // _NET_MOVERESIZE_WINDOW: only the given fields should change.
+  MoveResize {
+    window: Window,
+    gravity: c_int,
+    x: Option<i32>,
+    y: Option<i32>,
+    width: Option<u32>,
+    height: Option<u32>,
+    source: RequestSource,
+  },
+  // _NET_CLOSE_WINDOW
+  Close {
+    window: Window,
+    time: Time,
+    source: RequestSource,
+  },
+}
+
+// Frame border widths, as published in _NET_FRAME_EXTENTS.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Extents {
+  pub left: u32,
+  pub right: u32,
+  pub top: u32,
+  pub bottom: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RequestSource {
+  // Old clients that predate source indication.
+  Unknown,
+  Application,
+  // Pagers, taskbars and other tools acting on the user's behalf.
+  Pager,
+}
+
+impl RequestSource {
+  fn from_raw (raw: c_long) -> RequestSource {
+    match raw {
+      1 => RequestSource::Application,
+      2 => RequestSource::Pager,
+      _ => RequestSource::Unknown,
+    }
+  }
+}
+
+impl StateAction {
+  pub fn from_raw (raw: c_long) -> Option<StateAction> {
+    match raw {
+      0 => Some(StateAction::Remove),
+      1 => Some(StateAction::Add),
+      2 => Some(StateAction::Toggle),
+      _ => None,
+    }
+  }
+
+  // Applies the action for `state` to a window's list of states.
+  pub fn apply (self, states: &mut Vec<WmState>, state: WmState) {
+    let present = states.contains(&state);
+    match self {
+      StateAction::Add if !present => states.push(state),
+      StateAction::Remove => states.retain(|&s| s != state),
+      StateAction::Toggle if present => states.retain(|&s| s != state),
+      StateAction::Toggle => states.push(state),
+      _ => {},
+    }
+  }
+}
+
+impl Ewmh {
+  /// Publishes _NET_SUPPORTED, interning any of `names` not yet known in a
+  /// single round trip.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_supported (&mut self, names: &[&str]) {
+    self.atoms.intern_all(names);
+    let supported: Vec<Atom> = names.iter().map(|name| self.atoms.atom(name)).collect();
+    set_atoms(self.display, self.root, self.atom("_NET_SUPPORTED"), &supported);
+  }
+
+  /// Creates the unmapped child window that proves a compliant window
+  /// manager is running, and points _NET_SUPPORTING_WM_CHECK at it from both
+  /// the root and itself. Destroy it when the window manager exits.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn create_wm_check (&self, wm_name: &str) -> Window {
+    let check = XCreateSimpleWindow(self.display, self.root, -1, -1, 1, 1, 0, 0, 0);
+    let property = self.atom("_NET_SUPPORTING_WM_CHECK");
+    set_window(self.display, check, property, check);
+    set_window(self.display, self.root, property, check);
+    self.set_name(check, wm_name);
+    check
+  }
+
+  /// All managed windows in mapping order, oldest first.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_client_list (&self, windows: &[Window]) {
+    set_windows(self.display, self.root, self.atom("_NET_CLIENT_LIST"), windows);
+  }
+
+  /// All managed windows in stacking order, bottom to top.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_client_list_stacking (&self, windows: &[Window]) {
+    set_windows(self.display, self.root, self.atom("_NET_CLIENT_LIST_STACKING"), windows);
+  }
+
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_active_window (&self, window: Option<Window>) {
+    set_window(self.display, self.root, self.atom("_NET_ACTIVE_WINDOW"), window.unwrap_or(0));
+  }
+
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_number_of_desktops (&self, count: u32) {
+    set_cardinals(self.display, self.root, self.atom("_NET_NUMBER_OF_DESKTOPS"), &[count]);
+  }
+
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_current_desktop (&self, index: u32) {
+    set_cardinals(self.display, self.root, self.atom("_NET_CURRENT_DESKTOP"), &[index]);
+  }
+
+  /// One area per desktop: the screen minus docks and panels.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open.
+  pub unsafe fn set_workarea (&self, areas: &[Rect]) {
+    let data: Vec<u32> = areas.iter()
+      .flat_map(|area| vec![area.x as u32, area.y as u32, area.width, area.height])
+      .collect();
+    set_cardinals(self.display, self.root, self.atom("_NET_WORKAREA"), &data);
+  }
+
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open and `window` must exist on it.
+  pub unsafe fn set_frame_extents (&self, window: Window, extents: Extents) {
+    let data = [extents.left, extents.right, extents.top, extents.bottom];
+    set_cardinals(self.display, window, self.atom("_NET_FRAME_EXTENTS"), &data);
+  }
+
+  /// Publishes the states the window manager has applied to `window`.
+  ///
+  /// # Safety
+  ///
+  /// The Ewmh's display must still be open and `window` must exist on it.
+  pub unsafe fn set_state (&self, window: Window, states: &[WmState]) {
+    self.set_initial_state(window, states);
+  }
+
+  // Decodes a client message sent to the root window, or None if it isn't
+  // a well-formed request this module knows about.
+  pub fn decode_request (&self, event: &XClientMessageEvent) -> Option<ClientRequest> {
+    if event.format != 32 || event.message_type == 0 {
+      return None;
+    }
+
+    let kind = event.message_type;
+    if kind == self.atom("_NET_WM_STATE") {
+      let action = StateAction::from_raw(event.data.get_long(0))?;
+      let states: Vec<WmState> = [event.data.get_long(1), event.data.get_long(2)].iter()
+        .filter_map(|&atom| WmState::ALL.iter().cloned().find(|state| atom != 0 && self.atom(state.name()) == atom as Atom))
+        .collect();
+      if states.is_empty() {
+        return None;
+      }
+      Some(ClientRequest::State {
+        window: event.window,
+        action,
+        states,
+        source: RequestSource::from_raw(event.data.get_long(3)),
+      })
+    } else if kind == self.atom("_NET_MOVERESIZE_WINDOW") {
+      Some(decode_moveresize(event.window, &event.data))
+    } else if kind == self.atom("_NET_CLOSE_WINDOW") {
+      Some(ClientRequest::Close {
+        window: event.window,
+        time: event.data.get_long(0) as Time,
+        source: RequestSource::from_raw(event.data.get_long(1)),
+      })
+    } else {
+      None
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+// data.l[0] packs the gravity in bits 0-7, which of x, y, width and height
+// are present in bits 8-11 and the source indication in bits 12-15.
+fn decode_moveresize (window: Window, data: &ClientMessageData) -> ClientRequest {
+  let flags = data.get_long(0);
+  let present = |bit: c_long| flags & (1 << (8 + bit)) != 0;
+  ClientRequest::MoveResize {
+    window,
+    gravity: (flags & 0xff) as c_int,
+    x: if present(0) { Some(data.get_long(1) as i32) } else { None },
+    y: if present(1) { Some(data.get_long(2) as i32) } else { None },
+    width: if present(2) { Some(data.get_long(3) as u32) } else { None },
+    height: if present(3) { Some(data.get_long(4) as u32) } else { None },
+    source: RequestSource::from_raw((flags >> 12) & 0xf),
+  }
+}
+
+unsafe fn set_window (display: *mut Display, window: Window, property: Atom, value: Window) {
+  set_windows(display, window, property, &[value]);
+}
+
+unsafe fn set_windows (display: *mut Display, window: Window, property: Atom, windows: &[Window]) {
+  let data = PropertyData::Format32(windows.iter().map(|&window| window as u32).collect());
+  set_property(display, window, property, XA_WINDOW, &data, PropModeReplace);
+}
+
+#[test]
+fn ewmh_wm_moveresize_test () {
+  use std::mem::zeroed;
+
+  let mut data: ClientMessageData = unsafe { zeroed() };
+  data.set_long(0, 1 | 1 << 8 | 1 << 10 | 2 << 12);
+  data.set_long(1, -20);
+  data.set_long(2, 99);
+  data.set_long(3, 640);
+  assert_eq!(decode_moveresize(5, &data), ClientRequest::MoveResize {
+    window: 5,
+    gravity: 1,
+    x: Some(-20),
+    y: None,
+    width: Some(640),
+    height: None,
+    source: RequestSource::Pager,
+  });
+
+  let mut states = vec![WmState::Above];
+  StateAction::Toggle.apply(&mut states, WmState::Fullscreen);
+  StateAction::Add.apply(&mut states, WmState::Fullscreen);
+  StateAction::Toggle.apply(&mut states, WmState::Above);
+  assert_eq!(states, vec![WmState::Fullscreen]);
+  StateAction::Remove.apply(&mut states, WmState::Fullscreen);
+  assert!(states.is_empty());
+  assert_eq!(StateAction::from_raw(3), None);
+}
+
+#[test]
+#[ignore]
+fn ewmh_wm_publish_test () {
+  use std::ptr::null;
+  use ::ewmh::client_message;
+  use ::property::get_property;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let mut ewmh = Ewmh::new(display, XDefaultScreen(display));
+    let check = ewmh.create_wm_check("test-wm");
+    let client = XCreateSimpleWindow(display, ewmh.root(), 0, 0, 1, 1, 0, 0, 0);
+
+    ewmh.set_supported(&["_NET_WM_STATE", "_NET_WM_STATE_FULLSCREEN", "_NET_CLOSE_WINDOW"]);
+    assert!(ewmh.is_supported("_NET_WM_STATE_FULLSCREEN"));
+    assert!(!ewmh.is_supported("_NET_WORKAREA"));
+
+    let wm_check = get_property(display, ewmh.root(), ewmh.atom("_NET_SUPPORTING_WM_CHECK"), XA_WINDOW).unwrap().unwrap();
+    assert_eq!(wm_check.atoms(), Some(vec![check]));
+    assert_eq!(ewmh.name(check), Some("test-wm".to_string()));
+
+    ewmh.set_client_list(&[client]);
+    ewmh.set_active_window(Some(client));
+    assert_eq!(ewmh.active_window(), Some(client));
+
+    ewmh.set_workarea(&[Rect { x: 0, y: 24, width: 800, height: 576 }]);
+    let workarea = get_property(display, ewmh.root(), ewmh.atom("_NET_WORKAREA"), XA_CARDINAL).unwrap().unwrap();
+    assert_eq!(workarea.u32s(), Some(&[0, 24, 800, 576][..]));
+
+    ewmh.set_frame_extents(client, Extents { left: 1, right: 1, top: 20, bottom: 1 });
+    let extents = get_property(display, client, ewmh.atom("_NET_FRAME_EXTENTS"), XA_CARDINAL).unwrap().unwrap();
+    assert_eq!(extents.u32s(), Some(&[1, 1, 20, 1][..]));
+
+    let fullscreen = ewmh.atom("_NET_WM_STATE_FULLSCREEN") as c_long;
+    let event = client_message(client, ewmh.atom("_NET_WM_STATE"), [1, fullscreen, 0, 1, 0]);
+    assert_eq!(ewmh.decode_request(&XClientMessageEvent::from(event)), Some(ClientRequest::State {
+      window: client,
+      action: StateAction::Add,
+      states: vec![WmState::Fullscreen],
+      source: RequestSource::Application,
+    }));
+
+    let event = client_message(client, ewmh.atom("_NET_CLOSE_WINDOW"), [1234, 2, 0, 0, 0]);
+    assert_eq!(ewmh.decode_request(&XClientMessageEvent::from(event)), Some(ClientRequest::Close {
+      window: client,
+      time: 1234,
+      source: RequestSource::Pager,
+    }));
+
+    XDestroyWindow(display, client);
+    XDestroyWindow(display, check);
+    XCloseDisplay(display);
+  }
+}
@@ -6,15 +6,27 @@ use libc::{
   c_char,
   c_int,
   c_uint,
+  c_ulong,
 };
 
 use ::xlib::{
   Bool,
   Display,
   Drawable,
+  Font,
+  Pixmap,
+  Window,
+  XID,
   XVisualInfo,
 };
 
+pub mod context;
+pub mod extensions;
+pub mod fbconfig;
+pub mod offscreen;
+pub mod swap;
+pub mod texture_from_pixmap;
+
 
 //
 // functions
@@ -24,47 +36,109 @@ use ::xlib::{
 #[link(name="GL")]
 extern "C" {
   pub fn glXChooseFBConfig (display: *mut Display, screen: c_int, attr: *const c_int, nitems: *mut c_int) -> *mut GLXFBConfig;
-  // glXChooseVisual
-  // glXCopyContext
+  pub fn glXChooseVisual (display: *mut Display, screen: c_int, attr: *mut c_int) -> *mut XVisualInfo;
+  pub fn glXCopyContext (display: *mut Display, src: GLXContext, dest: GLXContext, mask: c_ulong);
   pub fn glXCreateContext (display: *mut Display, visual_info: *const XVisualInfo, share_context: GLXContext, direct: Bool) -> GLXContext;
-  // glXCreateGLXPixmap
-  // glXCreateNewContext
-  // glXCreatePbuffer
-  // glXCreatePixmap
-  // glXCreateWindow
+  pub fn glXCreateGLXPixmap (display: *mut Display, visual_info: *mut XVisualInfo, pixmap: Pixmap) -> GLXPixmap;
+  pub fn glXCreateNewContext (display: *mut Display, fbconfig: GLXFBConfig, render_type: c_int, share_context: GLXContext, direct: Bool) -> GLXContext;
+  pub fn glXCreatePbuffer (display: *mut Display, fbconfig: GLXFBConfig, attr: *const c_int) -> GLXPbuffer;
+  pub fn glXCreatePixmap (display: *mut Display, fbconfig: GLXFBConfig, pixmap: Pixmap, attr: *const c_int) -> GLXPixmap;
+  pub fn glXCreateWindow (display: *mut Display, fbconfig: GLXFBConfig, window: Window, attr: *const c_int) -> GLXWindow;
   pub fn glXDestroyContext (display: *mut Display, context: GLXContext);
-  // glXDestroyGLXPixmap
-  // glXDestroyPbuffer
-  // glXDestroyPixmap
-  // glXDestroyWindow
-  // glXGet
-  // glXGetClientString
-  // glXGetConfig
+  pub fn glXDestroyGLXPixmap (display: *mut Display, pixmap: GLXPixmap);
+  pub fn glXDestroyPbuffer (display: *mut Display, pbuffer: GLXPbuffer);
+  pub fn glXDestroyPixmap (display: *mut Display, pixmap: GLXPixmap);
+  pub fn glXDestroyWindow (display: *mut Display, window: GLXWindow);
+  pub fn glXGetClientString (display: *mut Display, name: c_int) -> *const c_char;
+  pub fn glXGetConfig (display: *mut Display, visual_info: *mut XVisualInfo, attr: c_int, value: *mut c_int) -> c_int;
   pub fn glXGetCurrentContext () -> GLXContext;
   pub fn glXGetCurrentDisplay () -> *mut Display;
   pub fn glXGetCurrentDrawable () -> GLXDrawable;
-  // glXGetCurrentReadDrawable
-  // glXGetFBConfigAttrib
-  // glXGetFBConfigs
+  pub fn glXGetCurrentReadDrawable () -> GLXDrawable;
+  pub fn glXGetFBConfigAttrib (display: *mut Display, fbconfig: GLXFBConfig, attr: c_int, value: *mut c_int) -> c_int;
+  pub fn glXGetFBConfigs (display: *mut Display, screen: c_int, nelements: *mut c_int) -> *mut GLXFBConfig;
   pub fn glXGetProcAddress (name: *const c_char) -> Option<unsafe extern "C" fn ()>;
-  // glXGetSelectedEvent
+  pub fn glXGetSelectedEvent (display: *mut Display, drawable: GLXDrawable, mask: *mut c_ulong);
   pub fn glXGetVisualFromFBConfig (display: *mut Display, fbconfig: GLXFBConfig) -> *mut XVisualInfo;
-  // glXIsDirect
-  // glXMakeContextCurrent
+  pub fn glXIsDirect (display: *mut Display, context: GLXContext) -> Bool;
+  pub fn glXMakeContextCurrent (display: *mut Display, draw: GLXDrawable, read: GLXDrawable, context: GLXContext) -> Bool;
   pub fn glXMakeCurrent (display: *mut Display, drawable: GLXDrawable, context: GLXContext) -> Bool;
-  // glXNewCommandSGI
-  // glXQuery
-  // glXQueryContext
-  pub fn glXQueryDrawable (display: *mut Display, drawable: GLXDrawable, attribute: c_int, value: *mut c_uint) -> c_int;
-  // glXQueryExtension
-  // glXQueryExtensionsString
-  // glXQueryServerString
-  // glXQueryVersion
-  // glXSelectEvent
+  pub fn glXQueryContext (display: *mut Display, context: GLXContext, attribute: c_int, value: *mut c_int) -> c_int;
+  pub fn glXQueryDrawable (display: *mut Display, drawable: GLXDrawable, attribute: c_int, value: *mut c_uint);
+  pub fn glXQueryExtension (display: *mut Display, error_base: *mut c_int, event_base: *mut c_int) -> Bool;
+  pub fn glXQueryExtensionsString (display: *mut Display, screen: c_int) -> *const c_char;
+  pub fn glXQueryServerString (display: *mut Display, screen: c_int, name: c_int) -> *const c_char;
+  pub fn glXQueryVersion (display: *mut Display, major: *mut c_int, minor: *mut c_int) -> Bool;
+  pub fn glXSelectEvent (display: *mut Display, drawable: GLXDrawable, mask: c_ulong);
   pub fn glXSwapBuffers (display: *mut Display, drawable: GLXDrawable);
-  // glXUseXFont
-  // glXWaitGL
-  // glXWaitX
+  pub fn glXUseXFont (font: Font, first: c_int, count: c_int, list_base: c_int);
+  pub fn glXWaitGL ();
+  pub fn glXWaitX ();
+}
+
+// Needs a GLX-capable server at $DISPLAY, e.g. Xvfb with Mesa's software
+// rasterizer; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn glx_pbuffer_test () {
+  use std::env::set_var;
+  use std::ptr::null;
+  use ::xlib::{
+    True,
+    XCloseDisplay,
+    XDefaultScreen,
+    XFree,
+    XOpenDisplay,
+  };
+
+  unsafe {
+    set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let mut error_base = 0;
+    let mut event_base = 0;
+    let (mut major, mut minor) = (0, 0);
+    assert!(glXQueryExtension(display, &mut error_base, &mut event_base) == True);
+    assert!(glXQueryVersion(display, &mut major, &mut minor) == True);
+    assert!((major, minor) >= (1, 3));
+    assert!(!glXGetClientString(display, GLX_VENDOR).is_null());
+
+    let screen = XDefaultScreen(display);
+    let attrs = [
+      GLX_DRAWABLE_TYPE, GLX_PBUFFER_BIT,
+      GLX_RENDER_TYPE, GLX_RGBA_BIT,
+      GLX_RED_SIZE, 8,
+      GLX_GREEN_SIZE, 8,
+      GLX_BLUE_SIZE, 8,
+      0,
+    ];
+    let mut nconfigs = 0;
+    let configs = glXChooseFBConfig(display, screen, attrs.as_ptr(), &mut nconfigs);
+    assert!(!configs.is_null() && nconfigs > 0);
+    let config = *configs;
+    XFree(configs as *mut _);
+
+    let pbuffer_attrs = [GLX_PBUFFER_WIDTH, 16, GLX_PBUFFER_HEIGHT, 8, 0];
+    let pbuffer = glXCreatePbuffer(display, config, pbuffer_attrs.as_ptr());
+    assert!(pbuffer != 0);
+
+    let context = glXCreateNewContext(display, config, GLX_RGBA_TYPE, 0 as GLXContext, True);
+    assert!(!context.is_null());
+    assert!(glXMakeContextCurrent(display, pbuffer, pbuffer, context) == True);
+    assert!(glXGetCurrentReadDrawable() == pbuffer);
+
+    let mut width = 0;
+    let mut height = 0;
+    glXQueryDrawable(display, pbuffer, GLX_WIDTH, &mut width);
+    glXQueryDrawable(display, pbuffer, GLX_HEIGHT, &mut height);
+    assert_eq!((width, height), (16, 8));
+
+    glXMakeContextCurrent(display, 0, 0, 0 as GLXContext);
+    glXDestroyContext(display, context);
+    glXDestroyPbuffer(display, pbuffer);
+    XCloseDisplay(display);
+  }
 }
 
 
@@ -74,17 +148,21 @@ extern "C" {
 
 
 // ID types
+pub type GLXContextID = XID;
 pub type GLXDrawable = Drawable;
+pub type GLXPbuffer = XID;
+pub type GLXPixmap = XID;
+pub type GLXWindow = XID;
 
 // opaque pointers
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct GLXContext_Rec;
+pub struct GLXContext_Rec { _private: [u8; 0] }
 pub type GLXContext = *mut GLXContext_Rec;
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct GLXFBConfig_Rec;
+pub struct GLXFBConfig_Rec { _private: [u8; 0] }
 pub type GLXFBConfig = *mut GLXFBConfig_Rec;
 
 
@@ -93,18 +171,64 @@ pub type GLXFBConfig = *mut GLXFBConfig_Rec;
 //
 
 
+// buffer clobber mask
+pub const GLX_FRONT_LEFT_BUFFER_BIT: c_uint = 0x0000_0001;
+pub const GLX_FRONT_RIGHT_BUFFER_BIT: c_uint = 0x0000_0002;
+pub const GLX_BACK_LEFT_BUFFER_BIT: c_uint = 0x0000_0004;
+pub const GLX_BACK_RIGHT_BUFFER_BIT: c_uint = 0x0000_0008;
+pub const GLX_AUX_BUFFERS_BIT: c_uint = 0x0000_0010;
+pub const GLX_DEPTH_BUFFER_BIT: c_uint = 0x0000_0020;
+pub const GLX_STENCIL_BUFFER_BIT: c_uint = 0x0000_0040;
+pub const GLX_ACCUM_BUFFER_BIT: c_uint = 0x0000_0080;
+
+// client/server strings
+pub const GLX_VENDOR: c_int = 1;
+pub const GLX_VERSION: c_int = 2;
+pub const GLX_EXTENSIONS: c_int = 3;
+
 // config caveats
 pub const GLX_SLOW_CONFIG: c_int = 0x8001;
 pub const GLX_NON_CONFORMANT_CONFIG: c_int = 0x800d;
 
+// drawable attributes
+pub const GLX_PRESERVED_CONTENTS: c_int = 0x801b;
+pub const GLX_LARGEST_PBUFFER: c_int = 0x801c;
+pub const GLX_WIDTH: c_int = 0x801d;
+pub const GLX_HEIGHT: c_int = 0x801e;
+pub const GLX_EVENT_MASK: c_int = 0x801f;
+pub const GLX_PBUFFER_HEIGHT: c_int = 0x8040;
+pub const GLX_PBUFFER_WIDTH: c_int = 0x8041;
+
 // drawable type mask
 pub const GLX_WINDOW_BIT: c_int = 0x0001;
 pub const GLX_PIXMAP_BIT: c_int = 0x0002;
 pub const GLX_PBUFFER_BIT: c_int = 0x0004;
 
+// error codes
+pub const GLX_BAD_SCREEN: c_int = 1;
+pub const GLX_BAD_ATTRIBUTE: c_int = 2;
+pub const GLX_NO_EXTENSION: c_int = 3;
+pub const GLX_BAD_VISUAL: c_int = 4;
+pub const GLX_BAD_CONTEXT: c_int = 5;
+pub const GLX_BAD_VALUE: c_int = 6;
+pub const GLX_BAD_ENUM: c_int = 7;
+
+// event drawable types
+pub const GLX_WINDOW: c_int = 0x8022;
+pub const GLX_PBUFFER: c_int = 0x8023;
+
+// event mask
+pub const GLX_PBUFFER_CLOBBER_MASK: c_ulong = 0x0800_0000;
+
+// event types
+pub const GLX_DAMAGED: c_int = 0x8020;
+pub const GLX_SAVED: c_int = 0x8021;
+
 // framebuffer attributes
+pub const GLX_USE_GL: c_int = 0x0001;
 pub const GLX_BUFFER_SIZE: c_int = 0x0002;
 pub const GLX_LEVEL: c_int = 0x0003;
+pub const GLX_RGBA: c_int = 0x0004;
 pub const GLX_DOUBLEBUFFER: c_int = 0x0005;
 pub const GLX_STEREO: c_int = 0x0006;
 pub const GLX_AUX_BUFFERS: c_int = 0x0007;
@@ -126,10 +250,17 @@ pub const GLX_TRANSPARENT_RED_VALUE: c_int = 0x0025;
 pub const GLX_TRANSPARENT_GREEN_VALUE: c_int = 0x0026;
 pub const GLX_TRANSPARENT_BLUE_VALUE: c_int = 0x0027;
 pub const GLX_TRANSPARENT_ALPHA_VALUE: c_int = 0x0028;
+pub const GLX_VISUAL_ID: c_int = 0x800b;
+pub const GLX_SCREEN: c_int = 0x800c;
 pub const GLX_DRAWABLE_TYPE: c_int = 0x8010;
 pub const GLX_RENDER_TYPE: c_int = 0x8011;
 pub const GLX_X_RENDERABLE: c_int = 0x8012;
 pub const GLX_FBCONFIG_ID: c_int = 0x8013;
+pub const GLX_MAX_PBUFFER_WIDTH: c_int = 0x8016;
+pub const GLX_MAX_PBUFFER_HEIGHT: c_int = 0x8017;
+pub const GLX_MAX_PBUFFER_PIXELS: c_int = 0x8018;
+pub const GLX_SAMPLE_BUFFERS: c_int = 0x0001_86a0;
+pub const GLX_SAMPLES: c_int = 0x0001_86a1;
 
 // misc
 pub const GLX_DONT_CARE: c_int = -1;
@@ -139,6 +270,10 @@ pub const GLX_NONE: c_int = 0x8000;
 pub const GLX_RGBA_BIT: c_int = 0x0001;
 pub const GLX_COLOR_INDEX_BIT: c_int = 0x0002;
 
+// render types
+pub const GLX_RGBA_TYPE: c_int = 0x8014;
+pub const GLX_COLOR_INDEX_TYPE: c_int = 0x8015;
+
 // transparent types
 pub const GLX_TRANSPARENT_RGB: c_int = 0x8008;
 pub const GLX_TRANSPARENT_INDEX: c_int = 0x8009;
@@ -157,22 +292,72 @@ pub const GLX_STATIC_GRAY: c_int = 0x8007;
 //
 
 
+#[allow(clippy::missing_safety_doc)]
 pub mod arb {
+  use std::mem::transmute;
+
   use libc::c_int;
 
+  use ::xlib::{
+    Bool,
+    Display,
+  };
+
+  use super::{
+    glXGetProcAddress,
+    GLXContext,
+    GLXFBConfig,
+  };
+
+  // function pointer types
+  pub type CreateContextAttribsARBFn = Option<unsafe extern "C" fn (*mut Display, GLXFBConfig, GLXContext, Bool, *const c_int) -> GLXContext>;
+
+  // loaders
+  pub unsafe fn load_glXCreateContextAttribsARB () -> CreateContextAttribsARBFn {
+    transmute(glXGetProcAddress(b"glXCreateContextAttribsARB\0".as_ptr() as *const _))
+  }
+
   // context attributes
   pub const GLX_CONTEXT_MAJOR_VERSION_ARB: c_int = 0x2091;
   pub const GLX_CONTEXT_MINOR_VERSION_ARB: c_int = 0x2092;
   pub const GLX_CONTEXT_FLAGS_ARB: c_int = 0x2094;
+  pub const GLX_CONTEXT_RELEASE_BEHAVIOR_ARB: c_int = 0x2097;
+  pub const GLX_CONTEXT_OPENGL_NO_ERROR_ARB: c_int = 0x31b3;
+  pub const GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB: c_int = 0x8256;
   pub const GLX_CONTEXT_PROFILE_MASK_ARB: c_int = 0x9126;
 
   // context flags
   pub const GLX_CONTEXT_DEBUG_BIT_ARB: c_int = 0x0001;
   pub const GLX_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB: c_int = 0x0002;
+  pub const GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB: c_int = 0x0004;
+  pub const GLX_CONTEXT_RESET_ISOLATION_BIT_ARB: c_int = 0x0008;
 
   // context profile mask
   pub const GLX_CONTEXT_CORE_PROFILE_BIT_ARB: c_int = 0x0001;
   pub const GLX_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB: c_int = 0x0002;
+
+  // context release behaviors
+  pub const GLX_CONTEXT_RELEASE_BEHAVIOR_NONE_ARB: c_int = 0;
+  pub const GLX_CONTEXT_RELEASE_BEHAVIOR_FLUSH_ARB: c_int = 0x2098;
+
+  // context reset notification strategies
+  pub const GLX_LOSE_CONTEXT_ON_RESET_ARB: c_int = 0x8252;
+  pub const GLX_NO_RESET_NOTIFICATION_ARB: c_int = 0x8261;
+
+  // framebuffer attributes
+  pub const GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB: c_int = 0x20b2;
+  pub const GLX_SAMPLE_BUFFERS_ARB: c_int = 0x0001_86a0;
+  pub const GLX_SAMPLES_ARB: c_int = 0x0001_86a1;
+
+  // extension names
+  pub const GLX_ARB_context_flush_control: &str = "GLX_ARB_context_flush_control";
+  pub const GLX_ARB_create_context: &str = "GLX_ARB_create_context";
+  pub const GLX_ARB_create_context_no_error: &str = "GLX_ARB_create_context_no_error";
+  pub const GLX_ARB_create_context_profile: &str = "GLX_ARB_create_context_profile";
+  pub const GLX_ARB_create_context_robustness: &str = "GLX_ARB_create_context_robustness";
+  pub const GLX_ARB_framebuffer_sRGB: &str = "GLX_ARB_framebuffer_sRGB";
+  pub const GLX_ARB_multisample: &str = "GLX_ARB_multisample";
+  pub const GLX_ARB_robustness_application_isolation: &str = "GLX_ARB_robustness_application_isolation";
 }
 
 
@@ -181,10 +366,191 @@ pub mod arb {
 //
 
 
+#[allow(clippy::missing_safety_doc)]
 pub mod ext {
+  use std::mem::transmute;
+
   use libc::c_int;
 
+  use ::xlib::Display;
+
+  use super::{
+    glXGetProcAddress,
+    GLXDrawable,
+  };
+
+  // function pointer types
+  pub type BindTexImageEXTFn = Option<unsafe extern "C" fn (*mut Display, GLXDrawable, c_int, *const c_int)>;
+  pub type ReleaseTexImageEXTFn = Option<unsafe extern "C" fn (*mut Display, GLXDrawable, c_int)>;
+  pub type SwapIntervalEXTFn = Option<unsafe extern "C" fn (*mut Display, GLXDrawable, c_int)>;
+
+  // loaders
+  pub unsafe fn load_glXBindTexImageEXT () -> BindTexImageEXTFn {
+    transmute(glXGetProcAddress(b"glXBindTexImageEXT\0".as_ptr() as *const _))
+  }
+
+  pub unsafe fn load_glXReleaseTexImageEXT () -> ReleaseTexImageEXTFn {
+    transmute(glXGetProcAddress(b"glXReleaseTexImageEXT\0".as_ptr() as *const _))
+  }
+
+  pub unsafe fn load_glXSwapIntervalEXT () -> SwapIntervalEXTFn {
+    transmute(glXGetProcAddress(b"glXSwapIntervalEXT\0".as_ptr() as *const _))
+  }
+
+  // bind texture buffers
+  pub const GLX_FRONT_LEFT_EXT: c_int = 0x20de;
+  pub const GLX_FRONT_RIGHT_EXT: c_int = 0x20df;
+  pub const GLX_BACK_LEFT_EXT: c_int = 0x20e0;
+  pub const GLX_BACK_RIGHT_EXT: c_int = 0x20e1;
+  pub const GLX_FRONT_EXT: c_int = GLX_FRONT_LEFT_EXT;
+  pub const GLX_BACK_EXT: c_int = GLX_BACK_LEFT_EXT;
+  pub const GLX_AUX0_EXT: c_int = 0x20e2;
+  pub const GLX_AUX1_EXT: c_int = 0x20e3;
+  pub const GLX_AUX2_EXT: c_int = 0x20e4;
+  pub const GLX_AUX3_EXT: c_int = 0x20e5;
+  pub const GLX_AUX4_EXT: c_int = 0x20e6;
+  pub const GLX_AUX5_EXT: c_int = 0x20e7;
+  pub const GLX_AUX6_EXT: c_int = 0x20e8;
+  pub const GLX_AUX7_EXT: c_int = 0x20e9;
+  pub const GLX_AUX8_EXT: c_int = 0x20ea;
+  pub const GLX_AUX9_EXT: c_int = 0x20eb;
+
+  // context profile mask
+  pub const GLX_CONTEXT_ES_PROFILE_BIT_EXT: c_int = 0x0004;
+  pub const GLX_CONTEXT_ES2_PROFILE_BIT_EXT: c_int = 0x0004;
+
   // drawable attributes
+  pub const GLX_TEXTURE_FORMAT_EXT: c_int = 0x20d5;
+  pub const GLX_TEXTURE_TARGET_EXT: c_int = 0x20d6;
+  pub const GLX_MIPMAP_TEXTURE_EXT: c_int = 0x20d7;
   pub const GLX_SWAP_INTERVAL_EXT: c_int = 0x20f1;
   pub const GLX_MAX_SWAP_INTERVAL_EXT: c_int = 0x20f2;
+  pub const GLX_LATE_SWAPS_TEAR_EXT: c_int = 0x20f3;
+
+  // framebuffer attributes
+  pub const GLX_FRAMEBUFFER_SRGB_CAPABLE_EXT: c_int = 0x20b2;
+  pub const GLX_BIND_TO_TEXTURE_RGB_EXT: c_int = 0x20d0;
+  pub const GLX_BIND_TO_TEXTURE_RGBA_EXT: c_int = 0x20d1;
+  pub const GLX_BIND_TO_MIPMAP_TEXTURE_EXT: c_int = 0x20d2;
+  pub const GLX_BIND_TO_TEXTURE_TARGETS_EXT: c_int = 0x20d3;
+  pub const GLX_Y_INVERTED_EXT: c_int = 0x20d4;
+
+  // texture formats
+  pub const GLX_TEXTURE_FORMAT_NONE_EXT: c_int = 0x20d8;
+  pub const GLX_TEXTURE_FORMAT_RGB_EXT: c_int = 0x20d9;
+  pub const GLX_TEXTURE_FORMAT_RGBA_EXT: c_int = 0x20da;
+
+  // texture target mask
+  pub const GLX_TEXTURE_1D_BIT_EXT: c_int = 0x0001;
+  pub const GLX_TEXTURE_2D_BIT_EXT: c_int = 0x0002;
+  pub const GLX_TEXTURE_RECTANGLE_BIT_EXT: c_int = 0x0004;
+
+  // texture targets
+  pub const GLX_TEXTURE_1D_EXT: c_int = 0x20db;
+  pub const GLX_TEXTURE_2D_EXT: c_int = 0x20dc;
+  pub const GLX_TEXTURE_RECTANGLE_EXT: c_int = 0x20dd;
+
+  // extension names
+  pub const GLX_EXT_create_context_es_profile: &str = "GLX_EXT_create_context_es_profile";
+  pub const GLX_EXT_create_context_es2_profile: &str = "GLX_EXT_create_context_es2_profile";
+  pub const GLX_EXT_framebuffer_sRGB: &str = "GLX_EXT_framebuffer_sRGB";
+  pub const GLX_EXT_swap_control: &str = "GLX_EXT_swap_control";
+  pub const GLX_EXT_swap_control_tear: &str = "GLX_EXT_swap_control_tear";
+  pub const GLX_EXT_texture_from_pixmap: &str = "GLX_EXT_texture_from_pixmap";
+}
+
+
+//
+// MESA extensions
+//
+
+
+#[allow(clippy::missing_safety_doc)]
+pub mod mesa {
+  use std::mem::transmute;
+
+  use libc::{
+    c_int,
+    c_uint,
+  };
+
+  use super::glXGetProcAddress;
+
+  // function pointer types
+  pub type GetSwapIntervalMESAFn = Option<unsafe extern "C" fn () -> c_int>;
+  pub type SwapIntervalMESAFn = Option<unsafe extern "C" fn (c_uint) -> c_int>;
+
+  // loaders
+  pub unsafe fn load_glXGetSwapIntervalMESA () -> GetSwapIntervalMESAFn {
+    transmute(glXGetProcAddress(b"glXGetSwapIntervalMESA\0".as_ptr() as *const _))
+  }
+
+  pub unsafe fn load_glXSwapIntervalMESA () -> SwapIntervalMESAFn {
+    transmute(glXGetProcAddress(b"glXSwapIntervalMESA\0".as_ptr() as *const _))
+  }
+
+  // extension names
+  pub const GLX_MESA_swap_control: &str = "GLX_MESA_swap_control";
+}
+
+
+//
+// SGI extensions
+//
+
+
+#[allow(clippy::missing_safety_doc)]
+pub mod sgi {
+  use std::mem::transmute;
+
+  use libc::c_int;
+
+  use super::glXGetProcAddress;
+
+  // function pointer types
+  pub type SwapIntervalSGIFn = Option<unsafe extern "C" fn (c_int) -> c_int>;
+
+  // loaders
+  pub unsafe fn load_glXSwapIntervalSGI () -> SwapIntervalSGIFn {
+    transmute(glXGetProcAddress(b"glXSwapIntervalSGI\0".as_ptr() as *const _))
+  }
+
+  // extension names
+  pub const GLX_SGI_swap_control: &str = "GLX_SGI_swap_control";
+}
+
+
+//
+// generated bindings
+//
+
+
+// Every GLX extension function and constant from the Khronos registry,
+// generated by build.rs, except for the extensions gl_generator can't
+// translate: GLX_EXT_import_context, GLX_MESA_pixmap_colormap,
+// GLX_SGIX_dmbuffer, GLX_SGIX_video_source and GLX_SUN_get_transparent_index.
+// The hand-written bindings above remain the stable API; these are for
+// extensions not covered there. Clippy lints are off for the generated code.
+#[cfg(feature="glx-generated")]
+#[allow(dead_code, non_camel_case_types, unused_imports, clippy::all)]
+pub mod generated {
+  include!(concat!(env!("OUT_DIR"), "/glx_generated.rs"));
+
+  // Resolves every generated entry point through glXGetProcAddress.
+  pub unsafe fn load () -> Glx {
+    use std::ffi::CString;
+    use std::ptr::null;
+
+    Glx::load_with(|name| {
+      let name = match CString::new(name) {
+        Ok(name) => name,
+        Err(_) => { return null(); },
+      };
+
+      match super::glXGetProcAddress(name.as_ptr()) {
+        Some(f) => f as *const __gl_imports::raw::c_void,
+        None => null(),
+      }
+    })
+  }
 }
@@ -0,0 +1,533 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ptr::null_mut;
+
+use libc::c_int;
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::glx::*;
+use ::glx::arb::*;
+use ::glx::ext::*;
+use ::glx::extensions::GlxExtensions;
+use ::xlib::{
+  Bool,
+  Display,
+  False,
+  True,
+  XFree,
+  XVisualInfo,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlProfile {
+  Core,
+  Compatibility,
+  Es2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReleaseBehavior {
+  None,
+  Flush,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResetNotification {
+  NoNotification,
+  LoseContextOnReset,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GlContextError {
+  NoFBConfig,
+  MissingExtension(&'static str),
+  CreateFailed(Vec<TrappedError>),
+}
+
+pub struct GlContextBuilder {
+  fbconfig_attrs: Vec<(c_int, c_int)>,
+  version: Option<(c_int, c_int)>,
+  profile: Option<GlProfile>,
+  debug: bool,
+  forward_compatible: bool,
+  robust: bool,
+  reset_notification: Option<ResetNotification>,
+  release_behavior: Option<ReleaseBehavior>,
+  no_error: bool,
+  srgb: bool,
+  samples: Option<c_int>,
+  direct: bool,
+  share_context: GLXContext,
+}
+
+impl GlContextBuilder {
+  pub fn new () -> GlContextBuilder {
+    GlContextBuilder {
+      fbconfig_attrs: vec![
+        (GLX_X_RENDERABLE, True),
+        (GLX_DRAWABLE_TYPE, GLX_WINDOW_BIT),
+        (GLX_RENDER_TYPE, GLX_RGBA_BIT),
+        (GLX_X_VISUAL_TYPE, GLX_TRUE_COLOR),
+        (GLX_RED_SIZE, 8),
+        (GLX_GREEN_SIZE, 8),
+        (GLX_BLUE_SIZE, 8),
+        (GLX_ALPHA_SIZE, 8),
+        (GLX_DEPTH_SIZE, 24),
+        (GLX_STENCIL_SIZE, 8),
+        (GLX_DOUBLEBUFFER, True),
+      ],
+      version: None,
+      profile: None,
+      debug: false,
+      forward_compatible: false,
+      robust: false,
+      reset_notification: None,
+      release_behavior: None,
+      no_error: false,
+      srgb: false,
+      samples: None,
+      direct: true,
+      share_context: null_mut(),
+    }
+  }
+
+  // Sets a glXChooseFBConfig attribute, replacing any earlier value for it.
+  // Passing GLX_DONT_CARE removes a default constraint.
+  pub fn fbconfig_attr (mut self, attr: c_int, value: c_int) -> GlContextBuilder {
+    self.fbconfig_attrs.retain(|&(a, _)| a != attr);
+    self.fbconfig_attrs.push((attr, value));
+    self
+  }
+
+  pub fn version (mut self, major: c_int, minor: c_int) -> GlContextBuilder {
+    self.version = Some((major, minor));
+    self
+  }
+
+  pub fn profile (mut self, profile: GlProfile) -> GlContextBuilder {
+    self.profile = Some(profile);
+    self
+  }
+
+  pub fn debug (mut self, debug: bool) -> GlContextBuilder {
+    self.debug = debug;
+    self
+  }
+
+  pub fn forward_compatible (mut self, forward_compatible: bool) -> GlContextBuilder {
+    self.forward_compatible = forward_compatible;
+    self
+  }
+
+  pub fn robust (mut self, robust: bool) -> GlContextBuilder {
+    self.robust = robust;
+    self
+  }
+
+  pub fn reset_notification (mut self, strategy: ResetNotification) -> GlContextBuilder {
+    self.reset_notification = Some(strategy);
+    self
+  }
+
+  pub fn release_behavior (mut self, behavior: ReleaseBehavior) -> GlContextBuilder {
+    self.release_behavior = Some(behavior);
+    self
+  }
+
+  pub fn no_error (mut self, no_error: bool) -> GlContextBuilder {
+    self.no_error = no_error;
+    self
+  }
+
+  // Requires an sRGB-capable framebuffer.
+  pub fn srgb (mut self, srgb: bool) -> GlContextBuilder {
+    self.srgb = srgb;
+    self
+  }
+
+  // Requires a multisampled framebuffer with at least `samples` samples.
+  pub fn samples (mut self, samples: c_int) -> GlContextBuilder {
+    self.samples = Some(samples);
+    self
+  }
+
+  pub fn direct (mut self, direct: bool) -> GlContextBuilder {
+    self.direct = direct;
+    self
+  }
+
+  pub fn share_context (mut self, context: GLXContext) -> GlContextBuilder {
+    self.share_context = context;
+    self
+  }
+
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn choose_fbconfig (&self, display: *mut Display, screen: c_int) -> Option<GLXFBConfig> {
+    let mut attrs = Vec::with_capacity(self.fbconfig_attrs.len() * 2 + 5);
+    for &(attr, value) in self.fbconfig_attrs.iter() {
+      attrs.push(attr);
+      attrs.push(value);
+    }
+    if self.srgb {
+      attrs.push(GLX_FRAMEBUFFER_SRGB_CAPABLE_ARB);
+      attrs.push(True);
+    }
+    if let Some(samples) = self.samples {
+      attrs.push(GLX_SAMPLE_BUFFERS_ARB);
+      attrs.push(1);
+      attrs.push(GLX_SAMPLES_ARB);
+      attrs.push(samples);
+    }
+    attrs.push(0);
+
+    let mut count = 0;
+    let configs = glXChooseFBConfig(display, screen, attrs.as_ptr(), &mut count);
+    if configs.is_null() {
+      return None;
+    }
+
+    let config = if count > 0 { Some(*configs) } else { None };
+    XFree(configs as *mut _);
+    config
+  }
+
+  /// Chooses an FBConfig and creates a context for it through
+  /// glXCreateContextAttribsARB. If GLX_ARB_create_context is unavailable or
+  /// the server rejects the requested version, profile or debug and
+  /// forward-compatible flags, a legacy context is created with
+  /// glXCreateContext instead; check `GlContext::is_legacy`. Everything else
+  /// (robustness, release behavior, no-error, ES2, sRGB, multisampling) is
+  /// never dropped silently: it fails with MissingExtension up front if the
+  /// screen lacks the extension, or with CreateFailed if the server rejects
+  /// it.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the context, and
+  /// `screen` one of its screens.
+  pub unsafe fn build (&self, display: *mut Display, screen: c_int) -> Result<GlContext, GlContextError> {
+    let extensions = GlxExtensions::query(display, screen);
+    self.check_extensions(display, &extensions)?;
+
+    match self.choose_fbconfig(display, screen) {
+      Some(fbconfig) => self.create(display, &extensions, fbconfig),
+      None => Err(GlContextError::NoFBConfig),
+    }
+  }
+
+  /// Like `build`, but for an FBConfig the caller already chose, e.g. one
+  /// shared with an offscreen surface.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the context, and
+  /// `fbconfig` a config of `screen` on it.
+  pub unsafe fn build_with_fbconfig (&self, display: *mut Display, screen: c_int, fbconfig: GLXFBConfig) -> Result<GlContext, GlContextError> {
+    let extensions = GlxExtensions::query(display, screen);
+    self.check_extensions(display, &extensions)?;
+    self.create(display, &extensions, fbconfig)
+  }
+
+  /// Returns the first extension required by the requested attributes that
+  /// `extensions` lacks.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection.
+  pub unsafe fn check_extensions (&self, display: *mut Display, extensions: &GlxExtensions) -> Result<(), GlContextError> {
+    let mut required = Vec::new();
+
+    if self.needs_arb_create_context() {
+      required.push(GLX_ARB_create_context);
+    }
+    if self.profile == Some(GlProfile::Es2) {
+      required.push(GLX_EXT_create_context_es2_profile);
+    }
+    if self.robust || self.reset_notification.is_some() {
+      required.push(GLX_ARB_create_context_robustness);
+    }
+    if self.release_behavior.is_some() {
+      required.push(GLX_ARB_context_flush_control);
+    }
+    if self.no_error {
+      required.push(GLX_ARB_create_context_no_error);
+    }
+
+    for &name in required.iter() {
+      if !extensions.has(name) {
+        return Err(GlContextError::MissingExtension(name));
+      }
+    }
+
+    if self.srgb && !extensions.has(GLX_ARB_framebuffer_sRGB) && !extensions.has(GLX_EXT_framebuffer_sRGB) {
+      return Err(GlContextError::MissingExtension(GLX_ARB_framebuffer_sRGB));
+    }
+
+    if self.samples.is_some() && !extensions.has(GLX_ARB_multisample) {
+      let (mut major, mut minor) = (0, 0);
+      glXQueryVersion(display, &mut major, &mut minor);
+      if (major, minor) < (1, 4) {
+        return Err(GlContextError::MissingExtension(GLX_ARB_multisample));
+      }
+    }
+
+    Ok(())
+  }
+
+  // True if any attribute was requested that a legacy glXCreateContext
+  // context can't provide, so falling back to one would silently drop it.
+  fn needs_arb_create_context (&self) -> bool {
+    self.profile == Some(GlProfile::Es2)
+      || self.robust
+      || self.reset_notification.is_some()
+      || self.release_behavior.is_some()
+      || self.no_error
+  }
+
+  unsafe fn create (&self, display: *mut Display, extensions: &GlxExtensions, fbconfig: GLXFBConfig) -> Result<GlContext, GlContextError> {
+    let direct = if self.direct { True } else { False };
+    let mut errors = Vec::new();
+
+    let create = if extensions.has(GLX_ARB_create_context) {
+      load_glXCreateContextAttribsARB()
+    } else {
+      None
+    };
+
+    if let Some(create) = create {
+      let attrs = self.context_attrs();
+      let trap = ErrorTrap::new(display);
+      let context = create(display, fbconfig, self.share_context, direct, attrs.as_ptr());
+
+      match trap.finish() {
+        Ok(()) if !context.is_null() => {
+          return Ok(GlContext::from_raw(display, context, fbconfig, false));
+        },
+        Ok(()) => {},
+        Err(e) => {
+          if !context.is_null() {
+            glXDestroyContext(display, context);
+          }
+          errors = e;
+        },
+      }
+    }
+
+    if self.needs_arb_create_context() {
+      return Err(GlContextError::CreateFailed(errors));
+    }
+
+    match create_legacy(display, fbconfig, self.share_context, direct) {
+      Ok(context) => Ok(GlContext::from_raw(display, context, fbconfig, true)),
+      Err(e) => {
+        errors.extend(e);
+        Err(GlContextError::CreateFailed(errors))
+      },
+    }
+  }
+
+  fn context_attrs (&self) -> Vec<c_int> {
+    let mut attrs = Vec::new();
+
+    // GLX defaults to version 1.0, which the ES2 profile rejects.
+    let version = match (self.version, self.profile) {
+      (None, Some(GlProfile::Es2)) => Some((2, 0)),
+      (version, _) => version,
+    };
+
+    if let Some((major, minor)) = version {
+      attrs.push(GLX_CONTEXT_MAJOR_VERSION_ARB);
+      attrs.push(major);
+      attrs.push(GLX_CONTEXT_MINOR_VERSION_ARB);
+      attrs.push(minor);
+    }
+
+    if let Some(profile) = self.profile {
+      attrs.push(GLX_CONTEXT_PROFILE_MASK_ARB);
+      attrs.push(match profile {
+        GlProfile::Core => GLX_CONTEXT_CORE_PROFILE_BIT_ARB,
+        GlProfile::Compatibility => GLX_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB,
+        GlProfile::Es2 => GLX_CONTEXT_ES2_PROFILE_BIT_EXT,
+      });
+    }
+
+    if let Some(strategy) = self.reset_notification {
+      attrs.push(GLX_CONTEXT_RESET_NOTIFICATION_STRATEGY_ARB);
+      attrs.push(match strategy {
+        ResetNotification::NoNotification => GLX_NO_RESET_NOTIFICATION_ARB,
+        ResetNotification::LoseContextOnReset => GLX_LOSE_CONTEXT_ON_RESET_ARB,
+      });
+    }
+
+    if let Some(behavior) = self.release_behavior {
+      attrs.push(GLX_CONTEXT_RELEASE_BEHAVIOR_ARB);
+      attrs.push(match behavior {
+        ReleaseBehavior::None => GLX_CONTEXT_RELEASE_BEHAVIOR_NONE_ARB,
+        ReleaseBehavior::Flush => GLX_CONTEXT_RELEASE_BEHAVIOR_FLUSH_ARB,
+      });
+    }
+
+    if self.no_error {
+      attrs.push(GLX_CONTEXT_OPENGL_NO_ERROR_ARB);
+      attrs.push(True);
+    }
+
+    let mut flags = 0;
+    if self.debug { flags |= GLX_CONTEXT_DEBUG_BIT_ARB; }
+    if self.forward_compatible { flags |= GLX_CONTEXT_FORWARD_COMPATIBLE_BIT_ARB; }
+    if self.robust { flags |= GLX_CONTEXT_ROBUST_ACCESS_BIT_ARB; }
+    if flags != 0 {
+      attrs.push(GLX_CONTEXT_FLAGS_ARB);
+      attrs.push(flags);
+    }
+
+    attrs.push(0);
+    attrs
+  }
+}
+
+impl Default for GlContextBuilder {
+  fn default () -> GlContextBuilder {
+    GlContextBuilder::new()
+  }
+}
+
+pub struct GlContext {
+  display: *mut Display,
+  context: GLXContext,
+  fbconfig: GLXFBConfig,
+  legacy: bool,
+}
+
+impl GlContext {
+  /// Takes ownership of `context`; it is destroyed when the GlContext drops.
+  ///
+  /// # Safety
+  ///
+  /// `context` must have been created on `display` for `fbconfig` and must
+  /// not be owned by anything else. `display` must outlive the GlContext.
+  pub unsafe fn from_raw (display: *mut Display, context: GLXContext, fbconfig: GLXFBConfig, legacy: bool) -> GlContext {
+    GlContext {
+      display,
+      context,
+      fbconfig,
+      legacy,
+    }
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  pub fn raw (&self) -> GLXContext {
+    self.context
+  }
+
+  pub fn fbconfig (&self) -> GLXFBConfig {
+    self.fbconfig
+  }
+
+  // True if the context was created with glXCreateContext because the
+  // requested version, profile or flags could not be honoured.
+  pub fn is_legacy (&self) -> bool {
+    self.legacy
+  }
+
+  /// The returned visual must be released with XFree.
+  ///
+  /// # Safety
+  ///
+  /// The context's display must still be open.
+  pub unsafe fn visual_info (&self) -> *mut XVisualInfo {
+    glXGetVisualFromFBConfig(self.display, self.fbconfig)
+  }
+
+  /// # Safety
+  ///
+  /// The context's display must still be open, and `drawable` must be
+  /// compatible with the context's FBConfig.
+  pub unsafe fn make_current (&self, drawable: GLXDrawable) -> Bool {
+    glXMakeCurrent(self.display, drawable, self.context)
+  }
+
+  /// Binds the context with separate draw and read drawables (GLX 1.3).
+  ///
+  /// # Safety
+  ///
+  /// The context's display must still be open, and both drawables must be
+  /// compatible with the context's FBConfig.
+  pub unsafe fn make_context_current (&self, draw: GLXDrawable, read: GLXDrawable) -> Bool {
+    glXMakeContextCurrent(self.display, draw, read, self.context)
+  }
+
+  /// # Safety
+  ///
+  /// The context's display must still be open.
+  pub unsafe fn is_current (&self) -> bool {
+    glXGetCurrentContext() == self.context
+  }
+}
+
+impl Drop for GlContext {
+  fn drop (&mut self) {
+    unsafe {
+      if self.is_current() {
+        glXMakeContextCurrent(self.display, 0, 0, null_mut());
+      }
+
+      glXDestroyContext(self.display, self.context);
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+unsafe fn create_legacy (display: *mut Display, fbconfig: GLXFBConfig, share_context: GLXContext, direct: Bool) -> Result<GLXContext, Vec<TrappedError>> {
+  let visual_info = glXGetVisualFromFBConfig(display, fbconfig);
+  if visual_info.is_null() {
+    return Err(Vec::new());
+  }
+
+  let trap = ErrorTrap::new(display);
+  let context = glXCreateContext(display, visual_info, share_context, direct);
+  XFree(visual_info as *mut _);
+
+  match trap.finish() {
+    Ok(()) if !context.is_null() => Ok(context),
+    Ok(()) => Err(Vec::new()),
+    Err(errors) => {
+      if !context.is_null() {
+        glXDestroyContext(display, context);
+      }
+      Err(errors)
+    },
+  }
+}
+
+#[test]
+fn glx_context_attrs_test () {
+  let es2 = [GLX_CONTEXT_MAJOR_VERSION_ARB, 2, GLX_CONTEXT_MINOR_VERSION_ARB, 0, GLX_CONTEXT_PROFILE_MASK_ARB, GLX_CONTEXT_ES2_PROFILE_BIT_EXT, 0];
+  assert_eq!(GlContextBuilder::new().profile(GlProfile::Es2).context_attrs(), es2.to_vec());
+
+  let es3 = GlContextBuilder::new().profile(GlProfile::Es2).version(3, 1).context_attrs();
+  assert_eq!(&es3[..4], &[GLX_CONTEXT_MAJOR_VERSION_ARB, 3, GLX_CONTEXT_MINOR_VERSION_ARB, 1]);
+
+  assert_eq!(GlContextBuilder::new().profile(GlProfile::Core).context_attrs(), vec![GLX_CONTEXT_PROFILE_MASK_ARB, GLX_CONTEXT_CORE_PROFILE_BIT_ARB, 0]);
+}
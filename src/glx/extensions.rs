@@ -0,0 +1,60 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ffi::CStr;
+
+use libc::c_int;
+
+use ::glx::glXQueryExtensionsString;
+use ::xlib::Display;
+
+
+//
+// types
+//
+
+
+// The set of GLX extensions usable on a screen, as reported by
+// glXQueryExtensionsString.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GlxExtensions {
+  names: Vec<String>,
+}
+
+impl GlxExtensions {
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn query (display: *mut Display, screen: c_int) -> GlxExtensions {
+    let string = glXQueryExtensionsString(display, screen);
+    if string.is_null() {
+      return GlxExtensions::default();
+    }
+
+    GlxExtensions::parse(&CStr::from_ptr(string).to_string_lossy())
+  }
+
+  pub fn parse (string: &str) -> GlxExtensions {
+    GlxExtensions {
+      names: string.split_whitespace().map(|s| s.to_string()).collect(),
+    }
+  }
+
+  pub fn has (&self, name: &str) -> bool {
+    self.names.iter().any(|n| n == name)
+  }
+
+  pub fn names (&self) -> &[String] {
+    &self.names
+  }
+}
+
+#[test]
+fn glx_extensions_parse_test () {
+  let extensions = GlxExtensions::parse(" GLX_ARB_create_context GLX_EXT_swap_control\nGLX_EXT_swap_control_tear ");
+  assert!(extensions.has("GLX_EXT_swap_control"));
+  assert!(extensions.has("GLX_EXT_swap_control_tear"));
+  assert!(!extensions.has("GLX_ARB_create"));
+  assert_eq!(extensions.names().len(), 3);
+}
@@ -0,0 +1,419 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::slice::from_raw_parts;
+
+use libc::{
+  c_char,
+  c_int,
+};
+
+use ::glx::*;
+use ::xlib::{
+  Display,
+  VisualID,
+  XFree,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigCaveat {
+  None,
+  Slow,
+  NonConformant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransparentType {
+  None,
+  Rgb,
+  Index,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VisualType {
+  None,
+  TrueColor,
+  DirectColor,
+  PseudoColor,
+  StaticColor,
+  GrayScale,
+  StaticGray,
+}
+
+// A snapshot of every framebuffer attribute of a GLXFBConfig. Attributes the
+// implementation doesn't know (e.g. GLX_SAMPLES before GLX 1.4) read as 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FbConfig {
+  pub raw: GLXFBConfig,
+  pub fbconfig_id: c_int,
+  pub visual_id: VisualID,
+  pub screen: c_int,
+  pub buffer_size: c_int,
+  pub level: c_int,
+  pub doublebuffer: bool,
+  pub stereo: bool,
+  pub aux_buffers: c_int,
+  pub red_size: c_int,
+  pub green_size: c_int,
+  pub blue_size: c_int,
+  pub alpha_size: c_int,
+  pub depth_size: c_int,
+  pub stencil_size: c_int,
+  pub accum_red_size: c_int,
+  pub accum_green_size: c_int,
+  pub accum_blue_size: c_int,
+  pub accum_alpha_size: c_int,
+  pub sample_buffers: c_int,
+  pub samples: c_int,
+  pub config_caveat: ConfigCaveat,
+  pub x_visual_type: VisualType,
+  pub x_renderable: bool,
+  pub drawable_type: c_int,
+  pub render_type: c_int,
+  pub transparent_type: TransparentType,
+  pub transparent_index_value: c_int,
+  pub transparent_red_value: c_int,
+  pub transparent_green_value: c_int,
+  pub transparent_blue_value: c_int,
+  pub transparent_alpha_value: c_int,
+  pub max_pbuffer_width: c_int,
+  pub max_pbuffer_height: c_int,
+  pub max_pbuffer_pixels: c_int,
+}
+
+impl FbConfig {
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `raw` one of its configs.
+  pub unsafe fn from_raw (display: *mut Display, raw: GLXFBConfig) -> FbConfig {
+    let get = |attr| get_attrib(display, raw, attr);
+
+    FbConfig {
+      raw,
+      fbconfig_id: get(GLX_FBCONFIG_ID),
+      visual_id: get(GLX_VISUAL_ID) as VisualID,
+      screen: get(GLX_SCREEN),
+      buffer_size: get(GLX_BUFFER_SIZE),
+      level: get(GLX_LEVEL),
+      doublebuffer: get(GLX_DOUBLEBUFFER) != 0,
+      stereo: get(GLX_STEREO) != 0,
+      aux_buffers: get(GLX_AUX_BUFFERS),
+      red_size: get(GLX_RED_SIZE),
+      green_size: get(GLX_GREEN_SIZE),
+      blue_size: get(GLX_BLUE_SIZE),
+      alpha_size: get(GLX_ALPHA_SIZE),
+      depth_size: get(GLX_DEPTH_SIZE),
+      stencil_size: get(GLX_STENCIL_SIZE),
+      accum_red_size: get(GLX_ACCUM_RED_SIZE),
+      accum_green_size: get(GLX_ACCUM_GREEN_SIZE),
+      accum_blue_size: get(GLX_ACCUM_BLUE_SIZE),
+      accum_alpha_size: get(GLX_ACCUM_ALPHA_SIZE),
+      sample_buffers: get(GLX_SAMPLE_BUFFERS),
+      samples: get(GLX_SAMPLES),
+      config_caveat: match get(GLX_CONFIG_CAVEAT) {
+        GLX_SLOW_CONFIG => ConfigCaveat::Slow,
+        GLX_NON_CONFORMANT_CONFIG => ConfigCaveat::NonConformant,
+        _ => ConfigCaveat::None,
+      },
+      x_visual_type: match get(GLX_X_VISUAL_TYPE) {
+        GLX_TRUE_COLOR => VisualType::TrueColor,
+        GLX_DIRECT_COLOR => VisualType::DirectColor,
+        GLX_PSEUDO_COLOR => VisualType::PseudoColor,
+        GLX_STATIC_COLOR => VisualType::StaticColor,
+        GLX_GRAY_SCALE => VisualType::GrayScale,
+        GLX_STATIC_GRAY => VisualType::StaticGray,
+        _ => VisualType::None,
+      },
+      x_renderable: get(GLX_X_RENDERABLE) != 0,
+      drawable_type: get(GLX_DRAWABLE_TYPE),
+      render_type: get(GLX_RENDER_TYPE),
+      transparent_type: match get(GLX_TRANSPARENT_TYPE) {
+        GLX_TRANSPARENT_RGB => TransparentType::Rgb,
+        GLX_TRANSPARENT_INDEX => TransparentType::Index,
+        _ => TransparentType::None,
+      },
+      transparent_index_value: get(GLX_TRANSPARENT_INDEX_VALUE),
+      transparent_red_value: get(GLX_TRANSPARENT_RED_VALUE),
+      transparent_green_value: get(GLX_TRANSPARENT_GREEN_VALUE),
+      transparent_blue_value: get(GLX_TRANSPARENT_BLUE_VALUE),
+      transparent_alpha_value: get(GLX_TRANSPARENT_ALPHA_VALUE),
+      max_pbuffer_width: get(GLX_MAX_PBUFFER_WIDTH),
+      max_pbuffer_height: get(GLX_MAX_PBUFFER_HEIGHT),
+      max_pbuffer_pixels: get(GLX_MAX_PBUFFER_PIXELS),
+    }
+  }
+
+  /// Every FBConfig on `screen`, in the server's order (glXGetFBConfigs).
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn all (display: *mut Display, screen: c_int) -> Vec<FbConfig> {
+    let mut count = 0;
+    let configs = glXGetFBConfigs(display, screen, &mut count);
+    collect_configs(display, configs, count)
+  }
+
+  /// FBConfigs matching an attribute list, best match first
+  /// (glXChooseFBConfig). The 0 terminator is optional.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn choose (display: *mut Display, screen: c_int, attrs: &[c_int]) -> Vec<FbConfig> {
+    let attrs = terminated(attrs);
+    let mut count = 0;
+    let configs = glXChooseFBConfig(display, screen, attrs.as_ptr(), &mut count);
+    collect_configs(display, configs, count)
+  }
+
+  /// # Safety
+  ///
+  /// `display` must be the open connection the config belongs to.
+  pub unsafe fn attrib (&self, display: *mut Display, attr: c_int) -> Option<c_int> {
+    let mut value = 0;
+    match glXGetFBConfigAttrib(display, self.raw, attr, &mut value) {
+      0 => Some(value),
+      _ => None,
+    }
+  }
+
+  pub fn supports_window (&self) -> bool {
+    self.drawable_type & GLX_WINDOW_BIT != 0
+  }
+
+  pub fn supports_pixmap (&self) -> bool {
+    self.drawable_type & GLX_PIXMAP_BIT != 0
+  }
+
+  pub fn supports_pbuffer (&self) -> bool {
+    self.drawable_type & GLX_PBUFFER_BIT != 0
+  }
+
+  pub fn supports_rgba (&self) -> bool {
+    self.render_type & GLX_RGBA_BIT != 0
+  }
+}
+
+
+//
+// functions
+//
+
+
+/// Formats a report in the spirit of `glxinfo -v`: server and client GLX
+/// strings, the extension list and a table of every FBConfig on `screen`.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `screen` one of its screens.
+pub unsafe fn glx_info (display: *mut Display, screen: c_int) -> String {
+  let mut out = String::new();
+  let (mut major, mut minor) = (0, 0);
+  glXQueryVersion(display, &mut major, &mut minor);
+
+  let _ = writeln!(out, "GLX version: {}.{}", major, minor);
+  let _ = writeln!(out, "server glx vendor string: {}", cstr(glXQueryServerString(display, screen, GLX_VENDOR)));
+  let _ = writeln!(out, "server glx version string: {}", cstr(glXQueryServerString(display, screen, GLX_VERSION)));
+  let _ = writeln!(out, "client glx vendor string: {}", cstr(glXGetClientString(display, GLX_VENDOR)));
+  let _ = writeln!(out, "client glx version string: {}", cstr(glXGetClientString(display, GLX_VERSION)));
+  let _ = writeln!(out, "GLX extensions:");
+  for name in cstr(glXQueryExtensionsString(display, screen)).split_whitespace() {
+    let _ = writeln!(out, "    {}", name);
+  }
+  let _ = writeln!(out);
+
+  out.push_str(&format_fbconfigs(&FbConfig::all(display, screen)));
+  out
+}
+
+pub fn format_fbconfigs (configs: &[FbConfig]) -> String {
+  let mut out = String::new();
+
+  let _ = writeln!(out, "{} GLX FBConfigs:", configs.len());
+  let _ = writeln!(out, "   id   visual  vt drw   bf lv db st ax  r  g  b  a dp sc  ar ag ab aa ms ns caveat");
+  let _ = writeln!(out, "-----  -------  -- ---  --- -- -- -- -- -- -- -- -- -- --  -- -- -- -- -- -- ------");
+
+  for c in configs.iter() {
+    let _ = writeln!(out, "0x{:03x}  0x{:05x}  {} {}{}{}  {:3} {:2} {:>2} {:>2} {:2} {:2} {:2} {:2} {:2} {:2} {:2}  {:2} {:2} {:2} {:2} {:2} {:2} {}",
+      c.fbconfig_id,
+      c.visual_id,
+      match c.x_visual_type {
+        VisualType::None => "--",
+        VisualType::TrueColor => "tc",
+        VisualType::DirectColor => "dc",
+        VisualType::PseudoColor => "pc",
+        VisualType::StaticColor => "sc",
+        VisualType::GrayScale => "gs",
+        VisualType::StaticGray => "sg",
+      },
+      if c.supports_window() { "w" } else { "." },
+      if c.supports_pixmap() { "p" } else { "." },
+      if c.supports_pbuffer() { "b" } else { "." },
+      c.buffer_size,
+      c.level,
+      if c.doublebuffer { "y" } else { "." },
+      if c.stereo { "y" } else { "." },
+      c.aux_buffers,
+      c.red_size,
+      c.green_size,
+      c.blue_size,
+      c.alpha_size,
+      c.depth_size,
+      c.stencil_size,
+      c.accum_red_size,
+      c.accum_green_size,
+      c.accum_blue_size,
+      c.accum_alpha_size,
+      c.sample_buffers,
+      c.samples,
+      match c.config_caveat {
+        ConfigCaveat::None => "None",
+        ConfigCaveat::Slow => "Slow",
+        ConfigCaveat::NonConformant => "Ncon",
+      });
+  }
+
+  out
+}
+
+
+//
+// private functions
+//
+
+
+unsafe fn collect_configs (display: *mut Display, configs: *mut GLXFBConfig, count: c_int) -> Vec<FbConfig> {
+  if configs.is_null() {
+    return Vec::new();
+  }
+
+  let result = from_raw_parts(configs, count.max(0) as usize).iter()
+    .map(|&raw| FbConfig::from_raw(display, raw))
+    .collect();
+  XFree(configs as *mut _);
+  result
+}
+
+unsafe fn cstr (s: *const c_char) -> String {
+  if s.is_null() {
+    String::new()
+  } else {
+    CStr::from_ptr(s).to_string_lossy().into_owned()
+  }
+}
+
+// Copies key/value pairs up to the first 0 key and terminates them. Values
+// may be 0, so only keys are checked.
+fn terminated (attrs: &[c_int]) -> Vec<c_int> {
+  let mut terminated = Vec::with_capacity(attrs.len() + 1);
+  for pair in attrs.chunks(2) {
+    if pair[0] == 0 {
+      break;
+    }
+    debug_assert!(pair.len() == 2, "GLX attribute {:#x} has no value", pair[0]);
+    if pair.len() < 2 {
+      break;
+    }
+    terminated.extend_from_slice(pair);
+  }
+  terminated.push(0);
+  terminated
+}
+
+unsafe fn get_attrib (display: *mut Display, config: GLXFBConfig, attr: c_int) -> c_int {
+  let mut value = 0;
+  match glXGetFBConfigAttrib(display, config, attr, &mut value) {
+    0 => value,
+    _ => 0,
+  }
+}
+
+#[test]
+fn fbconfig_attribute_list_test () {
+  assert_eq!(terminated(&[]), vec![0]);
+  assert_eq!(terminated(&[GLX_DOUBLEBUFFER, 1]), vec![GLX_DOUBLEBUFFER, 1, 0]);
+  assert_eq!(terminated(&[GLX_DOUBLEBUFFER, 0, GLX_RED_SIZE, 8, 0]), vec![GLX_DOUBLEBUFFER, 0, GLX_RED_SIZE, 8, 0]);
+  assert_eq!(terminated(&[GLX_RED_SIZE, 8, 0, GLX_BLUE_SIZE, 8]), vec![GLX_RED_SIZE, 8, 0]);
+}
+
+#[test]
+fn format_fbconfigs_test () {
+  use std::ptr::null_mut;
+
+  let rgba = FbConfig {
+    raw: null_mut(),
+    fbconfig_id: 0x21,
+    visual_id: 0x22,
+    screen: 0,
+    buffer_size: 32,
+    level: 0,
+    doublebuffer: true,
+    stereo: false,
+    aux_buffers: 0,
+    red_size: 8,
+    green_size: 8,
+    blue_size: 8,
+    alpha_size: 8,
+    depth_size: 24,
+    stencil_size: 8,
+    accum_red_size: 0,
+    accum_green_size: 0,
+    accum_blue_size: 0,
+    accum_alpha_size: 0,
+    sample_buffers: 0,
+    samples: 0,
+    config_caveat: ConfigCaveat::None,
+    x_visual_type: VisualType::TrueColor,
+    x_renderable: true,
+    drawable_type: GLX_WINDOW_BIT | GLX_PIXMAP_BIT,
+    render_type: GLX_RGBA_BIT,
+    transparent_type: TransparentType::None,
+    transparent_index_value: 0,
+    transparent_red_value: 0,
+    transparent_green_value: 0,
+    transparent_blue_value: 0,
+    transparent_alpha_value: 0,
+    max_pbuffer_width: 0,
+    max_pbuffer_height: 0,
+    max_pbuffer_pixels: 0,
+  };
+  let pbuffer = FbConfig {
+    fbconfig_id: 0x1a3,
+    visual_id: 0,
+    buffer_size: 24,
+    doublebuffer: false,
+    alpha_size: 0,
+    depth_size: 16,
+    stencil_size: 0,
+    accum_red_size: 16,
+    sample_buffers: 1,
+    samples: 4,
+    config_caveat: ConfigCaveat::Slow,
+    x_visual_type: VisualType::None,
+    x_renderable: false,
+    drawable_type: GLX_PBUFFER_BIT,
+    ..rgba
+  };
+
+  let lines: Vec<String> = format_fbconfigs(&[rgba, pbuffer]).lines().map(String::from).collect();
+  assert_eq!(lines.len(), 5);
+  assert_eq!(lines[0], "2 GLX FBConfigs:");
+  assert_eq!(lines[3], "0x021  0x00022  tc wp.   32  0  y  .  0  8  8  8  8 24  8   0  0  0  0  0  0 None");
+  assert_eq!(lines[4], "0x1a3  0x00000  -- ..b   24  0  .  .  0  8  8  8  0 16  0  16  0  0  0  1  4 Slow");
+  // Rows line up with the header.
+  for line in &lines[2..] {
+    assert_eq!(line.rfind(' '), lines[1].rfind(' '));
+  }
+
+  assert_eq!(format_fbconfigs(&[]).lines().next(), Some("0 GLX FBConfigs:"));
+}
@@ -0,0 +1,309 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ptr::null_mut;
+
+use libc::{
+  c_int,
+  c_uint,
+};
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::glx::*;
+use ::glx::context::{
+  GlContext,
+  GlContextBuilder,
+  GlContextError,
+};
+use ::xlib::{
+  Bool,
+  Display,
+  False,
+  Pixmap,
+  XCreatePixmap,
+  XFree,
+  XFreePixmap,
+  XRootWindow,
+  XVisualInfo,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OffscreenError {
+  NoFBConfig,
+  NoVisual,
+  MissingExtension(&'static str),
+  CreateFailed(Vec<TrappedError>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SurfaceKind {
+  Pbuffer,
+  Pixmap,
+  LegacyPixmap,
+}
+
+// A headless GLX drawable: either a pbuffer or an X pixmap wrapped in a GLX
+// pixmap. The drawable (and the X pixmap, if one was created) is destroyed
+// on drop, after unbinding it if it is current on this thread.
+pub struct OffscreenSurface {
+  display: *mut Display,
+  fbconfig: GLXFBConfig,
+  drawable: GLXDrawable,
+  pixmap: Pixmap,
+  kind: SurfaceKind,
+}
+
+impl OffscreenSurface {
+  // A context builder preset for pbuffer rendering. Build the context and
+  // the surface from the same FBConfig so they are compatible.
+  pub fn pbuffer_builder () -> GlContextBuilder {
+    GlContextBuilder::new()
+      .fbconfig_attr(GLX_X_RENDERABLE, GLX_DONT_CARE)
+      .fbconfig_attr(GLX_X_VISUAL_TYPE, GLX_DONT_CARE)
+      .fbconfig_attr(GLX_DOUBLEBUFFER, GLX_DONT_CARE)
+      .fbconfig_attr(GLX_DRAWABLE_TYPE, GLX_PBUFFER_BIT)
+  }
+
+  // A context builder preset for GLX pixmap rendering. Pixmaps are never
+  // double-buffered and need an X visual for their depth.
+  pub fn pixmap_builder () -> GlContextBuilder {
+    GlContextBuilder::new()
+      .fbconfig_attr(GLX_DOUBLEBUFFER, False)
+      .fbconfig_attr(GLX_DRAWABLE_TYPE, GLX_PIXMAP_BIT)
+  }
+
+  /// Chooses a pbuffer-capable FBConfig, creates a context and a pbuffer for
+  /// it, and returns both.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the context and
+  /// surface, and `screen` one of its screens.
+  pub unsafe fn with_context (display: *mut Display, screen: c_int, width: c_uint, height: c_uint) -> Result<(GlContext, OffscreenSurface), OffscreenError> {
+    let builder = OffscreenSurface::pbuffer_builder();
+    let fbconfig = match builder.choose_fbconfig(display, screen) {
+      Some(fbconfig) => fbconfig,
+      None => { return Err(OffscreenError::NoFBConfig); },
+    };
+
+    let surface = OffscreenSurface::pbuffer(display, fbconfig, width, height)?;
+
+    match builder.build_with_fbconfig(display, screen, fbconfig) {
+      Ok(context) => Ok((context, surface)),
+      Err(GlContextError::NoFBConfig) => Err(OffscreenError::NoFBConfig),
+      Err(GlContextError::MissingExtension(name)) => Err(OffscreenError::MissingExtension(name)),
+      Err(GlContextError::CreateFailed(errors)) => Err(OffscreenError::CreateFailed(errors)),
+    }
+  }
+
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the surface, and
+  /// `fbconfig` one of its configs.
+  pub unsafe fn pbuffer (display: *mut Display, fbconfig: GLXFBConfig, width: c_uint, height: c_uint) -> Result<OffscreenSurface, OffscreenError> {
+    let attrs = [
+      GLX_PBUFFER_WIDTH, width as c_int,
+      GLX_PBUFFER_HEIGHT, height as c_int,
+      GLX_PRESERVED_CONTENTS, 1,
+      0,
+    ];
+
+    let trap = ErrorTrap::new(display);
+    let pbuffer = glXCreatePbuffer(display, fbconfig, attrs.as_ptr());
+    let surface = OffscreenSurface {
+      display,
+      fbconfig,
+      drawable: pbuffer,
+      pixmap: 0,
+      kind: SurfaceKind::Pbuffer,
+    };
+
+    finish_create(trap, surface)
+  }
+
+  /// Creates an X pixmap matching the FBConfig's visual depth and wraps it in
+  /// a GLX pixmap with glXCreatePixmap.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the surface, and
+  /// `fbconfig` one of its configs.
+  pub unsafe fn pixmap (display: *mut Display, fbconfig: GLXFBConfig, width: c_uint, height: c_uint) -> Result<OffscreenSurface, OffscreenError> {
+    let visual_info = glXGetVisualFromFBConfig(display, fbconfig);
+    if visual_info.is_null() {
+      return Err(OffscreenError::NoVisual);
+    }
+
+    let depth = (*visual_info).depth;
+    let screen = (*visual_info).screen;
+    XFree(visual_info as *mut _);
+
+    let trap = ErrorTrap::new(display);
+    let pixmap = XCreatePixmap(display, XRootWindow(display, screen), width, height, depth);
+    let drawable = glXCreatePixmap(display, fbconfig, pixmap, [0].as_ptr());
+    let surface = OffscreenSurface {
+      display,
+      fbconfig,
+      drawable,
+      pixmap,
+      kind: SurfaceKind::Pixmap,
+    };
+
+    finish_create(trap, surface)
+  }
+
+  /// GLX 1.2 path: creates an X pixmap for `visual_info` and wraps it with
+  /// glXCreateGLXPixmap. Use with contexts from glXCreateContext.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the surface, and
+  /// `visual_info` must point to a visual of it.
+  pub unsafe fn legacy_pixmap (display: *mut Display, visual_info: *mut XVisualInfo, width: c_uint, height: c_uint) -> Result<OffscreenSurface, OffscreenError> {
+    if visual_info.is_null() {
+      return Err(OffscreenError::NoVisual);
+    }
+
+    let trap = ErrorTrap::new(display);
+    let root = XRootWindow(display, (*visual_info).screen);
+    let pixmap = XCreatePixmap(display, root, width, height, (*visual_info).depth);
+    let drawable = glXCreateGLXPixmap(display, visual_info, pixmap);
+    let surface = OffscreenSurface {
+      display,
+      fbconfig: null_mut(),
+      drawable,
+      pixmap,
+      kind: SurfaceKind::LegacyPixmap,
+    };
+
+    finish_create(trap, surface)
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  pub fn drawable (&self) -> GLXDrawable {
+    self.drawable
+  }
+
+  // The underlying X pixmap, or 0 for pbuffers.
+  pub fn x_pixmap (&self) -> Pixmap {
+    self.pixmap
+  }
+
+  // Null for surfaces created with `legacy_pixmap`.
+  pub fn fbconfig (&self) -> GLXFBConfig {
+    self.fbconfig
+  }
+
+  /// # Safety
+  ///
+  /// The surface's display must still be open.
+  pub unsafe fn query (&self, attribute: c_int) -> c_uint {
+    let mut value = 0;
+    glXQueryDrawable(self.display, self.drawable, attribute, &mut value);
+    value
+  }
+
+  /// # Safety
+  ///
+  /// The surface's display must still be open.
+  pub unsafe fn size (&self) -> (c_uint, c_uint) {
+    (self.query(GLX_WIDTH), self.query(GLX_HEIGHT))
+  }
+
+  /// # Safety
+  ///
+  /// The surface's display must still be open, and `context` must have been
+  /// created on it.
+  pub unsafe fn make_current (&self, context: &GlContext) -> Bool {
+    match self.kind {
+      SurfaceKind::LegacyPixmap => context.make_current(self.drawable),
+      _ => context.make_context_current(self.drawable, self.drawable),
+    }
+  }
+}
+
+impl Drop for OffscreenSurface {
+  fn drop (&mut self) {
+    unsafe {
+      if self.drawable != 0 {
+        if glXGetCurrentDrawable() == self.drawable || glXGetCurrentReadDrawable() == self.drawable {
+          glXMakeContextCurrent(self.display, 0, 0, null_mut());
+        }
+
+        match self.kind {
+          SurfaceKind::Pbuffer => glXDestroyPbuffer(self.display, self.drawable),
+          SurfaceKind::Pixmap => glXDestroyPixmap(self.display, self.drawable),
+          SurfaceKind::LegacyPixmap => glXDestroyGLXPixmap(self.display, self.drawable),
+        }
+      }
+
+      if self.pixmap != 0 {
+        XFreePixmap(self.display, self.pixmap);
+      }
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+// Returns the surface if creating it raised no errors. Otherwise the
+// partially created resources are released while the trap is still
+// installed, so errors from the cleanup are swallowed too.
+unsafe fn finish_create (trap: ErrorTrap, surface: OffscreenSurface) -> Result<OffscreenSurface, OffscreenError> {
+  let errors = trap.errors();
+  if errors.is_empty() && surface.drawable != 0 {
+    return Ok(surface);
+  }
+
+  drop(surface);
+  drop(trap);
+  Err(OffscreenError::CreateFailed(errors))
+}
+
+// Needs a server with pbuffer-capable GLX FBConfigs at $DISPLAY; run with
+// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn offscreen_pbuffer_test () {
+  use std::env::set_var;
+  use std::ptr::null;
+  use ::xlib::{
+    True,
+    XCloseDisplay,
+    XDefaultScreen,
+    XOpenDisplay,
+  };
+
+  unsafe {
+    set_var("LIBGL_ALWAYS_SOFTWARE", "1");
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    {
+      let (context, surface) = OffscreenSurface::with_context(display, XDefaultScreen(display), 64, 32).unwrap();
+      assert_eq!(surface.size(), (64, 32));
+      assert!(surface.make_current(&context) == True);
+      assert!(glXGetCurrentDrawable() == surface.drawable());
+    }
+
+    assert!(glXGetCurrentContext().is_null());
+    XCloseDisplay(display);
+  }
+}
@@ -0,0 +1,245 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_int,
+  c_uint,
+};
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::glx::{
+  glXQueryDrawable,
+  GLX_FBCONFIG_ID,
+  GLXDrawable,
+};
+use ::glx::ext::*;
+use ::glx::extensions::GlxExtensions;
+use ::glx::fbconfig::FbConfig;
+use ::glx::mesa::*;
+use ::glx::sgi::*;
+use ::xlib::{
+  Display,
+  Window,
+  XGetGeometry,
+  XRootWindow,
+  XScreenCount,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwapControl {
+  Ext,
+  Mesa,
+  Sgi,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SwapIntervalError {
+  // None of the available extensions accepts the requested interval.
+  // Negative (adaptive) intervals need GLX_EXT_swap_control_tear, and SGI
+  // rejects an interval of 0.
+  Unsupported,
+  // The drawable is neither an X window or pixmap nor a GLX drawable.
+  BadDrawable,
+  Failed(c_int),
+  Trapped(Vec<TrappedError>),
+}
+
+
+//
+// functions
+//
+
+
+/// Sets the swap interval for `drawable` using the first of
+/// GLX_EXT_swap_control, GLX_MESA_swap_control and GLX_SGI_swap_control that
+/// can express `interval`. A negative interval requests adaptive vsync: swaps
+/// wait for |interval| vblanks unless a frame is late, in which case they tear.
+/// The MESA and SGI extensions act on the current context's drawable, so the
+/// context should be current on `drawable`. The extensions checked are those
+/// of the drawable's screen.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `drawable` a window or GLX
+/// drawable on it.
+pub unsafe fn set_swap_interval (display: *mut Display, drawable: GLXDrawable, interval: c_int) -> Result<SwapControl, SwapIntervalError> {
+  let screen = match drawable_screen(display, drawable) {
+    Some(screen) => screen,
+    None => { return Err(SwapIntervalError::BadDrawable); },
+  };
+
+  let extensions = GlxExtensions::query(display, screen);
+  set_swap_interval_with(&extensions, display, drawable, interval)
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `drawable` a window or GLX
+/// drawable on it.
+pub unsafe fn set_swap_interval_with (extensions: &GlxExtensions, display: *mut Display, drawable: GLXDrawable, interval: c_int) -> Result<SwapControl, SwapIntervalError> {
+  if interval < 0 && !extensions.has(GLX_EXT_swap_control_tear) {
+    return Err(SwapIntervalError::Unsupported);
+  }
+
+  if extensions.has(GLX_EXT_swap_control) {
+    if let Some(swap_interval) = load_glXSwapIntervalEXT() {
+      let trap = ErrorTrap::new(display);
+      swap_interval(display, drawable, interval);
+      return match trap.finish() {
+        Ok(()) => Ok(SwapControl::Ext),
+        Err(errors) => Err(SwapIntervalError::Trapped(errors)),
+      };
+    }
+  }
+
+  if interval < 0 {
+    return Err(SwapIntervalError::Unsupported);
+  }
+
+  if extensions.has(GLX_MESA_swap_control) {
+    if let Some(swap_interval) = load_glXSwapIntervalMESA() {
+      return match swap_interval(interval as c_uint) {
+        0 => Ok(SwapControl::Mesa),
+        e => Err(SwapIntervalError::Failed(e)),
+      };
+    }
+  }
+
+  if interval > 0 && extensions.has(GLX_SGI_swap_control) {
+    if let Some(swap_interval) = load_glXSwapIntervalSGI() {
+      return match swap_interval(interval) {
+        0 => Ok(SwapControl::Sgi),
+        e => Err(SwapIntervalError::Failed(e)),
+      };
+    }
+  }
+
+  Err(SwapIntervalError::Unsupported)
+}
+
+/// Returns the swap interval in effect for `drawable`, or None if neither
+/// GLX_EXT_swap_control nor GLX_MESA_swap_control is available on its screen.
+/// Adaptive vsync is reported as a negative interval.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `drawable` a window or GLX
+/// drawable on it.
+pub unsafe fn get_swap_interval (display: *mut Display, drawable: GLXDrawable) -> Option<c_int> {
+  let extensions = GlxExtensions::query(display, drawable_screen(display, drawable)?);
+
+  if extensions.has(GLX_EXT_swap_control) {
+    let mut interval = 0;
+    glXQueryDrawable(display, drawable, GLX_SWAP_INTERVAL_EXT, &mut interval);
+
+    if extensions.has(GLX_EXT_swap_control_tear) {
+      let mut late_swaps_tear = 0;
+      glXQueryDrawable(display, drawable, GLX_LATE_SWAPS_TEAR_EXT, &mut late_swaps_tear);
+      if late_swaps_tear != 0 {
+        return Some(-(interval as c_int));
+      }
+    }
+
+    return Some(interval as c_int);
+  }
+
+  if extensions.has(GLX_MESA_swap_control) {
+    if let Some(get_swap_interval) = load_glXGetSwapIntervalMESA() {
+      return Some(get_swap_interval());
+    }
+  }
+
+  None
+}
+
+
+//
+// private functions
+//
+
+
+// The screen `drawable` belongs to: X windows and pixmaps are found by their
+// root window, GLX windows and pbuffers by their FBConfig.
+unsafe fn drawable_screen (display: *mut Display, drawable: GLXDrawable) -> Option<c_int> {
+  let trap = ErrorTrap::new(display);
+  let mut root: Window = 0;
+  let (mut x, mut y, mut width, mut height, mut border_width, mut depth) = (0, 0, 0, 0, 0, 0);
+  let status = XGetGeometry(display, drawable, &mut root, &mut x, &mut y, &mut width, &mut height, &mut border_width, &mut depth);
+  if trap.finish().is_ok() && status != 0 {
+    return (0..XScreenCount(display)).find(|&screen| XRootWindow(display, screen) == root);
+  }
+
+  let trap = ErrorTrap::new(display);
+  let mut fbconfig_id = 0;
+  glXQueryDrawable(display, drawable, GLX_FBCONFIG_ID, &mut fbconfig_id);
+  if trap.finish().is_err() || fbconfig_id == 0 {
+    return None;
+  }
+
+  (0..XScreenCount(display)).find(|&screen| {
+    FbConfig::all(display, screen).iter().any(|config| config.fbconfig_id == fbconfig_id as c_int)
+  })
+}
+
+#[test]
+fn swap_interval_fallback_test () {
+  use std::ptr::null_mut;
+
+  // None of these reach the server.
+  unsafe {
+    let none = GlxExtensions::default();
+    assert_eq!(set_swap_interval_with(&none, null_mut(), 0, 1), Err(SwapIntervalError::Unsupported));
+
+    let ext = GlxExtensions::parse("GLX_EXT_swap_control");
+    assert_eq!(set_swap_interval_with(&ext, null_mut(), 0, -1), Err(SwapIntervalError::Unsupported));
+
+    let sgi = GlxExtensions::parse("GLX_SGI_swap_control");
+    assert_eq!(set_swap_interval_with(&sgi, null_mut(), 0, 0), Err(SwapIntervalError::Unsupported));
+    assert_eq!(set_swap_interval_with(&sgi, null_mut(), 0, -2), Err(SwapIntervalError::Unsupported));
+  }
+}
+
+// Needs a server at $DISPLAY; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn swap_interval_drawable_screen_test () {
+  use std::ptr::null;
+  use ::xlib::{
+    XCloseDisplay,
+    XCreatePixmap,
+    XCreateSimpleWindow,
+    XDestroyWindow,
+    XFreePixmap,
+    XOpenDisplay,
+  };
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let screen = XScreenCount(display) - 1;
+    let root = XRootWindow(display, screen);
+    let window = XCreateSimpleWindow(display, root, 0, 0, 8, 8, 0, 0, 0);
+    let pixmap = XCreatePixmap(display, root, 8, 8, 1);
+    assert_eq!(drawable_screen(display, root), Some(screen));
+    assert_eq!(drawable_screen(display, window), Some(screen));
+    assert_eq!(drawable_screen(display, pixmap), Some(screen));
+    assert_eq!(drawable_screen(display, 0), None);
+    assert_eq!(set_swap_interval(display, 0, 1), Err(SwapIntervalError::BadDrawable));
+    assert_eq!(get_swap_interval(display, 0), None);
+
+    XFreePixmap(display, pixmap);
+    XDestroyWindow(display, window);
+    XCloseDisplay(display);
+  }
+}
@@ -0,0 +1,255 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ptr::{
+  null,
+  null_mut,
+};
+
+use libc::{
+  c_int,
+  c_uint,
+};
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::glx::*;
+use ::glx::ext::*;
+use ::glx::extensions::GlxExtensions;
+use ::glx::fbconfig::FbConfig;
+use ::xlib::{
+  Display,
+  Pixmap,
+  VisualID,
+  XFree,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TexturePixmapError {
+  NoFBConfig,
+  MissingExtension(&'static str),
+  CreateFailed(Vec<TrappedError>),
+}
+
+// An FBConfig able to bind pixmaps of a given depth as textures, along with
+// the texture format and target to create the GLX pixmap with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TexturePixmapConfig {
+  pub fbconfig: GLXFBConfig,
+  pub format: c_int,
+  pub target: c_int,
+  pub mipmap: bool,
+  pub y_inverted: bool,
+}
+
+impl TexturePixmapConfig {
+  /// Picks an FBConfig whose visual has `depth` bits and which can bind to an
+  /// RGB (depth 24) or RGBA (depth 32) texture. If `visual_id` is given, a
+  /// config for that exact visual is preferred. GL_TEXTURE_2D is used when the
+  /// config supports it, GL_TEXTURE_RECTANGLE otherwise.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn choose (display: *mut Display, screen: c_int, depth: c_uint, visual_id: Option<VisualID>) -> Option<TexturePixmapConfig> {
+    let mut best: Option<(FbConfig, TexturePixmapConfig)> = None;
+
+    for config in FbConfig::all(display, screen) {
+      if !config.supports_pixmap() || !config.supports_rgba() || visual_depth(display, config.raw) != Some(depth) {
+        continue;
+      }
+
+      let attrib = |attr| config.attrib(display, attr).unwrap_or(0);
+      let format = if depth == 32 && attrib(GLX_BIND_TO_TEXTURE_RGBA_EXT) != 0 {
+        GLX_TEXTURE_FORMAT_RGBA_EXT
+      } else if attrib(GLX_BIND_TO_TEXTURE_RGB_EXT) != 0 {
+        GLX_TEXTURE_FORMAT_RGB_EXT
+      } else {
+        continue;
+      };
+
+      let targets = attrib(GLX_BIND_TO_TEXTURE_TARGETS_EXT);
+      let target = if targets & GLX_TEXTURE_2D_BIT_EXT != 0 {
+        GLX_TEXTURE_2D_EXT
+      } else if targets & GLX_TEXTURE_RECTANGLE_BIT_EXT != 0 {
+        GLX_TEXTURE_RECTANGLE_EXT
+      } else {
+        continue;
+      };
+
+      let candidate = TexturePixmapConfig {
+        fbconfig: config.raw,
+        format,
+        target,
+        mipmap: attrib(GLX_BIND_TO_MIPMAP_TEXTURE_EXT) != 0,
+        y_inverted: attrib(GLX_Y_INVERTED_EXT) != 0,
+      };
+
+      if visual_id == Some(config.visual_id) {
+        return Some(candidate);
+      }
+
+      // Without an exact visual match, prefer the leanest config: no depth,
+      // stencil or multisample buffers and single buffering.
+      let better = match best {
+        Some((ref current, _)) => cost(&config) < cost(current),
+        None => true,
+      };
+      if better {
+        best = Some((config, candidate));
+      }
+    }
+
+    best.map(|(_, candidate)| candidate)
+  }
+}
+
+// A GLX pixmap created from an existing X pixmap that can be bound as the
+// contents of a GL texture with GLX_EXT_texture_from_pixmap. The X pixmap
+// is owned by the caller and must outlive this; the GLX pixmap is released
+// (if bound) and destroyed on drop.
+pub struct TexturePixmap {
+  display: *mut Display,
+  drawable: GLXPixmap,
+  config: TexturePixmapConfig,
+  bind: unsafe extern "C" fn (*mut Display, GLXDrawable, c_int, *const c_int),
+  release: unsafe extern "C" fn (*mut Display, GLXDrawable, c_int),
+  bound: bool,
+}
+
+impl TexturePixmap {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the TexturePixmap,
+  /// and `pixmap` a pixmap on `screen` with the config's depth.
+  pub unsafe fn new (display: *mut Display, screen: c_int, pixmap: Pixmap, config: &TexturePixmapConfig) -> Result<TexturePixmap, TexturePixmapError> {
+    if !GlxExtensions::query(display, screen).has(GLX_EXT_texture_from_pixmap) {
+      return Err(TexturePixmapError::MissingExtension(GLX_EXT_texture_from_pixmap));
+    }
+
+    let (bind, release) = match (load_glXBindTexImageEXT(), load_glXReleaseTexImageEXT()) {
+      (Some(bind), Some(release)) => (bind, release),
+      _ => { return Err(TexturePixmapError::MissingExtension(GLX_EXT_texture_from_pixmap)); },
+    };
+
+    let attrs = [
+      GLX_TEXTURE_TARGET_EXT, config.target,
+      GLX_TEXTURE_FORMAT_EXT, config.format,
+      GLX_MIPMAP_TEXTURE_EXT, config.mipmap as c_int,
+      0,
+    ];
+
+    let trap = ErrorTrap::new(display);
+    let drawable = glXCreatePixmap(display, config.fbconfig, pixmap, attrs.as_ptr());
+    let errors = trap.errors();
+    if !errors.is_empty() || drawable == 0 {
+      if drawable != 0 {
+        glXDestroyPixmap(display, drawable);
+      }
+      drop(trap);
+      return Err(TexturePixmapError::CreateFailed(errors));
+    }
+
+    Ok(TexturePixmap {
+      display,
+      drawable,
+      config: *config,
+      bind,
+      release,
+      bound: false,
+    })
+  }
+
+  /// Chooses a config for `depth` (see TexturePixmapConfig::choose) and wraps
+  /// `pixmap` with it.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the TexturePixmap,
+  /// and `pixmap` a pixmap on `screen` that is `depth` bits deep.
+  pub unsafe fn from_pixmap (display: *mut Display, screen: c_int, pixmap: Pixmap, depth: c_uint, visual_id: Option<VisualID>) -> Result<TexturePixmap, TexturePixmapError> {
+    match TexturePixmapConfig::choose(display, screen, depth, visual_id) {
+      Some(config) => TexturePixmap::new(display, screen, pixmap, &config),
+      None => Err(TexturePixmapError::NoFBConfig),
+    }
+  }
+
+  pub fn drawable (&self) -> GLXPixmap {
+    self.drawable
+  }
+
+  pub fn config (&self) -> &TexturePixmapConfig {
+    &self.config
+  }
+
+  pub fn is_bound (&self) -> bool {
+    self.bound
+  }
+
+  /// Binds the pixmap's front-left buffer to the texture currently bound to
+  /// `config().target` in the current context.
+  ///
+  /// # Safety
+  ///
+  /// The pixmap's display must still be open, and a GL context on it must be
+  /// current on this thread.
+  pub unsafe fn bind (&mut self) {
+    (self.bind)(self.display, self.drawable, GLX_FRONT_LEFT_EXT, null());
+    self.bound = true;
+  }
+
+  /// # Safety
+  ///
+  /// The pixmap's display must still be open, and a GL context on it must be
+  /// current on this thread.
+  pub unsafe fn release (&mut self) {
+    if self.bound {
+      (self.release)(self.display, self.drawable, GLX_FRONT_LEFT_EXT);
+      self.bound = false;
+    }
+  }
+}
+
+impl Drop for TexturePixmap {
+  fn drop (&mut self) {
+    unsafe {
+      self.release();
+
+      if glXGetCurrentDrawable() == self.drawable || glXGetCurrentReadDrawable() == self.drawable {
+        glXMakeContextCurrent(self.display, 0, 0, null_mut());
+      }
+
+      glXDestroyPixmap(self.display, self.drawable);
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+fn cost (config: &FbConfig) -> (c_int, c_int, c_int, bool) {
+  (config.depth_size, config.stencil_size, config.samples, config.doublebuffer)
+}
+
+unsafe fn visual_depth (display: *mut Display, fbconfig: GLXFBConfig) -> Option<c_uint> {
+  let visual_info = glXGetVisualFromFBConfig(display, fbconfig);
+  if visual_info.is_null() {
+    return None;
+  }
+
+  let depth = (*visual_info).depth;
+  XFree(visual_info as *mut _);
+  Some(depth)
+}
@@ -0,0 +1,181 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::slice::from_raw_parts;
+
+use libc::{
+  c_int,
+  c_uint,
+};
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::keysym::XK_Num_Lock;
+use ::xlib::{
+  Display,
+  GrabModeAsync,
+  KeyCode,
+  KeySym,
+  LockMask,
+  True,
+  Window,
+  XFreeModifiermap,
+  XGetModifierMapping,
+  XGrabKey,
+  XKeysymToKeycode,
+  XUngrabKey,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HotkeyError {
+  NoKeycode(KeySym),
+  GrabFailed(Vec<TrappedError>),
+  UngrabFailed(Vec<TrappedError>),
+}
+
+
+//
+// functions
+//
+
+
+/// Returns the modifier mask currently bound to Num_Lock, or 0 if Num_Lock is
+/// not mapped to any modifier.
+///
+/// # Safety
+///
+/// `display` must be an open connection.
+pub unsafe fn num_lock_mask (display: *mut Display) -> c_uint {
+  let keycode = XKeysymToKeycode(display, XK_Num_Lock as KeySym);
+  if keycode == 0 {
+    return 0;
+  }
+
+  let modmap = XGetModifierMapping(display);
+  if modmap.is_null() {
+    return 0;
+  }
+
+  let per_mod = (*modmap).max_keypermod as usize;
+  let keycodes = from_raw_parts((*modmap).modifiermap as *const KeyCode, 8 * per_mod);
+  let mask = modifier_mask(keycodes, per_mod, keycode);
+
+  XFreeModifiermap(modmap);
+  mask
+}
+
+/// Returns every combination of `modifiers` with Caps Lock and Num Lock, so a
+/// key grab fires regardless of the lock state.
+///
+/// # Safety
+///
+/// `display` must be an open connection.
+pub unsafe fn lock_permutations (display: *mut Display, modifiers: c_uint) -> Vec<c_uint> {
+  lock_masks(modifiers, num_lock_mask(display))
+}
+
+/// Grabs `keysym` with `modifiers` on `window` under every lock-modifier
+/// permutation. If any grab fails (typically BadAccess because another client
+/// already owns the combination), the grabs that were made are released and
+/// the trapped errors are returned.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn grab_hotkey (display: *mut Display, window: Window, keysym: KeySym, modifiers: c_uint) -> Result<(), HotkeyError> {
+  let keycode = XKeysymToKeycode(display, keysym);
+  if keycode == 0 {
+    return Err(HotkeyError::NoKeycode(keysym));
+  }
+
+  let masks = lock_permutations(display, modifiers);
+  let trap = ErrorTrap::new(display);
+
+  for &mask in masks.iter() {
+    XGrabKey(display, keycode as c_int, mask, window, True, GrabModeAsync, GrabModeAsync);
+  }
+
+  match trap.finish() {
+    Ok(()) => Ok(()),
+    Err(errors) => {
+      let trap = ErrorTrap::new(display);
+      for &mask in masks.iter() {
+        XUngrabKey(display, keycode as c_int, mask, window);
+      }
+      drop(trap);
+      Err(HotkeyError::GrabFailed(errors))
+    },
+  }
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn ungrab_hotkey (display: *mut Display, window: Window, keysym: KeySym, modifiers: c_uint) -> Result<(), HotkeyError> {
+  let keycode = XKeysymToKeycode(display, keysym);
+  if keycode == 0 {
+    return Err(HotkeyError::NoKeycode(keysym));
+  }
+
+  let trap = ErrorTrap::new(display);
+
+  for mask in lock_permutations(display, modifiers) {
+    XUngrabKey(display, keycode as c_int, mask, window);
+  }
+
+  trap.finish().map_err(HotkeyError::UngrabFailed)
+}
+
+
+//
+// private functions
+//
+
+
+// The mask of the modifier `keycode` is bound to in an XModifierKeymap's
+// table of 8 rows of `per_mod` keycodes, or 0.
+fn modifier_mask (keycodes: &[KeyCode], per_mod: usize, keycode: KeyCode) -> c_uint {
+  match keycodes.iter().position(|&k| k == keycode) {
+    Some(i) => 1 << (i / per_mod),
+    None => 0,
+  }
+}
+
+fn lock_masks (modifiers: c_uint, num_lock: c_uint) -> Vec<c_uint> {
+  let mut masks = vec![modifiers, modifiers | LockMask];
+
+  if num_lock != 0 && num_lock != LockMask {
+    masks.push(modifiers | num_lock);
+    masks.push(modifiers | num_lock | LockMask);
+  }
+
+  masks
+}
+
+#[test]
+fn hotkey_lock_masks_test () {
+  use ::xlib::{
+    ControlMask,
+    Mod2Mask,
+  };
+
+  assert_eq!(lock_masks(ControlMask, Mod2Mask), vec![ControlMask, ControlMask | LockMask, ControlMask | Mod2Mask, ControlMask | Mod2Mask | LockMask]);
+  // Num Lock unmapped, or bound to the Lock modifier itself.
+  assert_eq!(lock_masks(0, 0), vec![0, LockMask]);
+  assert_eq!(lock_masks(ControlMask, LockMask), vec![ControlMask, ControlMask | LockMask]);
+
+  // Two keycodes per modifier: Shift, Lock, Control, Mod1, Mod2, ...
+  let keycodes = [50, 62, 66, 0, 37, 105, 64, 108, 77, 0, 0, 0, 0, 0, 0, 0];
+  assert_eq!(modifier_mask(&keycodes, 2, 77), Mod2Mask);
+  assert_eq!(modifier_mask(&keycodes, 2, 66), LockMask);
+  assert_eq!(modifier_mask(&keycodes, 2, 38), 0);
+}
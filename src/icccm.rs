@@ -0,0 +1,489 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::ffi::{
+  CString,
+  NulError,
+};
+use std::mem::zeroed;
+use std::ptr::null_mut;
+use std::slice::from_raw_parts;
+
+use libc::{
+  c_char,
+  c_int,
+  c_void,
+};
+
+use ::atom::AtomCache;
+use ::property::{
+  PropertyData,
+  set_property,
+};
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+// Collects the ICCCM properties a client sets on its top-level window
+// before mapping it, and writes them all with `apply`:
+//
+//   WmHints::new()
+//     .title("Editor")
+//     .class("editor", "Editor")
+//     .min_size(320, 240)
+//     .protocol("WM_DELETE_WINDOW")
+//     .apply(display, window)?;
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WmHints {
+  title: Option<String>,
+  icon_name: Option<String>,
+  class: Option<(String, String)>,
+  command: Option<Vec<String>>,
+  position: Option<(c_int, c_int)>,
+  size: Option<(c_int, c_int)>,
+  min_size: Option<(c_int, c_int)>,
+  max_size: Option<(c_int, c_int)>,
+  base_size: Option<(c_int, c_int)>,
+  resize_inc: Option<(c_int, c_int)>,
+  aspect: Option<((c_int, c_int), (c_int, c_int))>,
+  gravity: Option<c_int>,
+  input: Option<bool>,
+  initial_state: Option<c_int>,
+  window_group: Option<Window>,
+  urgent: bool,
+  transient_for: Option<Window>,
+  protocols: Vec<String>,
+}
+
+impl WmHints {
+  pub fn new () -> WmHints {
+    WmHints::default()
+  }
+
+  // Sets WM_NAME and _NET_WM_NAME.
+  pub fn title (mut self, title: &str) -> WmHints {
+    self.title = Some(title.to_string());
+    self
+  }
+
+  // Sets WM_ICON_NAME and _NET_WM_ICON_NAME.
+  pub fn icon_name (mut self, icon_name: &str) -> WmHints {
+    self.icon_name = Some(icon_name.to_string());
+    self
+  }
+
+  pub fn class (mut self, instance: &str, class: &str) -> WmHints {
+    self.class = Some((instance.to_string(), class.to_string()));
+    self
+  }
+
+  pub fn command (mut self, argv: &[&str]) -> WmHints {
+    self.command = Some(argv.iter().map(|arg| arg.to_string()).collect());
+    self
+  }
+
+  // A user-specified position, which the window manager should honour.
+  pub fn position (mut self, x: c_int, y: c_int) -> WmHints {
+    self.position = Some((x, y));
+    self
+  }
+
+  // A user-specified size, which the window manager should honour.
+  pub fn size (mut self, width: c_int, height: c_int) -> WmHints {
+    self.size = Some((width, height));
+    self
+  }
+
+  pub fn min_size (mut self, width: c_int, height: c_int) -> WmHints {
+    self.min_size = Some((width, height));
+    self
+  }
+
+  pub fn max_size (mut self, width: c_int, height: c_int) -> WmHints {
+    self.max_size = Some((width, height));
+    self
+  }
+
+  // Min and max size together, so the window cannot be resized.
+  pub fn fixed_size (self, width: c_int, height: c_int) -> WmHints {
+    self.min_size(width, height).max_size(width, height)
+  }
+
+  pub fn base_size (mut self, width: c_int, height: c_int) -> WmHints {
+    self.base_size = Some((width, height));
+    self
+  }
+
+  pub fn resize_inc (mut self, width: c_int, height: c_int) -> WmHints {
+    self.resize_inc = Some((width, height));
+    self
+  }
+
+  // Aspect ratios as (numerator, denominator) pairs.
+  pub fn aspect (mut self, min: (c_int, c_int), max: (c_int, c_int)) -> WmHints {
+    self.aspect = Some((min, max));
+    self
+  }
+
+  pub fn gravity (mut self, gravity: c_int) -> WmHints {
+    self.gravity = Some(gravity);
+    self
+  }
+
+  // Whether the window relies on the window manager to give it focus.
+  pub fn input (mut self, input: bool) -> WmHints {
+    self.input = Some(input);
+    self
+  }
+
+  // NormalState or IconicState.
+  pub fn initial_state (mut self, state: c_int) -> WmHints {
+    self.initial_state = Some(state);
+    self
+  }
+
+  pub fn window_group (mut self, leader: Window) -> WmHints {
+    self.window_group = Some(leader);
+    self
+  }
+
+  pub fn urgent (mut self, urgent: bool) -> WmHints {
+    self.urgent = urgent;
+    self
+  }
+
+  pub fn transient_for (mut self, owner: Window) -> WmHints {
+    self.transient_for = Some(owner);
+    self
+  }
+
+  // Adds a WM_PROTOCOLS entry, such as WM_DELETE_WINDOW or WM_TAKE_FOCUS.
+  pub fn protocol (mut self, name: &str) -> WmHints {
+    if !self.protocols.iter().any(|protocol| protocol == name) {
+      self.protocols.push(name.to_string());
+    }
+    self
+  }
+
+  // WM_NORMAL_HINTS, or None if no size constraint was given.
+  pub fn size_hints (&self) -> Option<XSizeHints> {
+    let mut hints: XSizeHints = unsafe { zeroed() };
+    if let Some((x, y)) = self.position {
+      hints.flags |= USPosition;
+      hints.x = x;
+      hints.y = y;
+    }
+    if let Some((width, height)) = self.size {
+      hints.flags |= USSize;
+      hints.width = width;
+      hints.height = height;
+    }
+    if let Some((width, height)) = self.min_size {
+      hints.flags |= PMinSize;
+      hints.min_width = width;
+      hints.min_height = height;
+    }
+    if let Some((width, height)) = self.max_size {
+      hints.flags |= PMaxSize;
+      hints.max_width = width;
+      hints.max_height = height;
+    }
+    if let Some((width, height)) = self.base_size {
+      hints.flags |= PBaseSize;
+      hints.base_width = width;
+      hints.base_height = height;
+    }
+    if let Some((width, height)) = self.resize_inc {
+      hints.flags |= PResizeInc;
+      hints.width_inc = width;
+      hints.height_inc = height;
+    }
+    if let Some(((min_x, min_y), (max_x, max_y))) = self.aspect {
+      hints.flags |= PAspect;
+      hints.min_aspect = AspectRatio { x: min_x, y: min_y };
+      hints.max_aspect = AspectRatio { x: max_x, y: max_y };
+    }
+    if let Some(gravity) = self.gravity {
+      hints.flags |= PWinGravity;
+      hints.win_gravity = gravity;
+    }
+
+    match hints.flags {
+      0 => None,
+      _ => Some(hints),
+    }
+  }
+
+  // WM_HINTS, or None if none of its fields were given.
+  pub fn wm_hints (&self) -> Option<XWMHints> {
+    let mut hints: XWMHints = unsafe { zeroed() };
+    if let Some(input) = self.input {
+      hints.flags |= InputHint;
+      hints.input = input as Bool;
+    }
+    if let Some(state) = self.initial_state {
+      hints.flags |= StateHint;
+      hints.initial_state = state;
+    }
+    if let Some(leader) = self.window_group {
+      hints.flags |= WindowGroupHint;
+      hints.window_group = leader;
+    }
+    if self.urgent {
+      hints.flags |= XUrgencyHint;
+    }
+
+    match hints.flags {
+      0 => None,
+      _ => Some(hints),
+    }
+  }
+
+  // WM_CLASS and WM_COMMAND are NUL-separated lists, so none of their
+  // strings may contain a NUL itself.
+  fn class_strings (&self) -> Result<Option<(CString, CString)>, NulError> {
+    match self.class {
+      Some((ref instance, ref class)) => Ok(Some((CString::new(instance.as_str())?, CString::new(class.as_str())?))),
+      None => Ok(None),
+    }
+  }
+
+  fn command_strings (&self) -> Result<Option<Vec<CString>>, NulError> {
+    match self.command {
+      Some(ref command) => command.iter().map(|arg| CString::new(arg.as_str())).collect::<Result<Vec<_>, _>>().map(Some),
+      None => Ok(None),
+    }
+  }
+
+  /// Writes every hint that was set. Atoms for the titles and protocols are
+  /// interned in a single round trip. Fails without writing anything if the
+  /// class or command contains a NUL.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `window` a window on it.
+  pub unsafe fn apply (&self, display: *mut Display, window: Window) -> Result<(), NulError> {
+    let class = self.class_strings()?;
+    let command = self.command_strings()?;
+
+    let mut names = vec!["UTF8_STRING", "_NET_WM_NAME", "_NET_WM_ICON_NAME"];
+    names.extend(self.protocols.iter().map(|name| name.as_str()));
+    let mut atoms = AtomCache::with_names(display, &names);
+
+    if let Some(ref title) = self.title {
+      let net_wm_name = atoms.atom("_NET_WM_NAME");
+      set_text(display, window, &mut atoms, XA_WM_NAME, net_wm_name, title);
+    }
+    if let Some(ref icon_name) = self.icon_name {
+      let net_wm_icon_name = atoms.atom("_NET_WM_ICON_NAME");
+      set_text(display, window, &mut atoms, XA_WM_ICON_NAME, net_wm_icon_name, icon_name);
+    }
+
+    if let Some((instance, class)) = class {
+      let hint = XClassHint {
+        res_name: instance.as_ptr() as *mut c_char,
+        res_class: class.as_ptr() as *mut c_char,
+      };
+      XSetClassHint(display, window, &hint);
+    }
+
+    if let Some(args) = command {
+      let mut argv: Vec<*mut c_char> = args.iter().map(|arg| arg.as_ptr() as *mut c_char).collect();
+      XSetCommand(display, window, argv.as_mut_ptr(), argv.len() as c_int);
+    }
+
+    if let Some(hints) = self.size_hints() {
+      XSetWMNormalHints(display, window, &hints);
+    }
+    if let Some(hints) = self.wm_hints() {
+      XSetWMHints(display, window, &hints);
+    }
+    if let Some(owner) = self.transient_for {
+      XSetTransientForHint(display, window, owner);
+    }
+
+    if !self.protocols.is_empty() {
+      let protocols: Vec<Atom> = self.protocols.iter().map(|name| atoms.atom(name)).collect();
+      XSetWMProtocols(display, window, protocols.as_ptr(), protocols.len() as c_int);
+    }
+
+    Ok(())
+  }
+}
+
+
+//
+// functions
+//
+
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn get_wm_hints (display: *mut Display, window: Window) -> Option<XWMHints> {
+  let hints = XGetWMHints(display, window);
+  if hints.is_null() {
+    return None;
+  }
+
+  let copy = *hints;
+  XFree(hints as *mut c_void);
+  Some(copy)
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn get_wm_normal_hints (display: *mut Display, window: Window) -> Option<XSizeHints> {
+  let mut hints: XSizeHints = zeroed();
+  let mut supplied = 0;
+  match XGetWMNormalHints(display, window, &mut hints, &mut supplied) {
+    0 => None,
+    _ => Some(hints),
+  }
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn get_wm_protocols (display: *mut Display, window: Window) -> Vec<Atom> {
+  let mut protocols = null_mut();
+  let mut count = 0;
+  if XGetWMProtocols(display, window, &mut protocols, &mut count) == 0 || protocols.is_null() {
+    return Vec::new();
+  }
+
+  let list = from_raw_parts(protocols, count as usize).to_vec();
+  XFree(protocols as *mut c_void);
+  list
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn get_transient_for (display: *mut Display, window: Window) -> Option<Window> {
+  let mut owner = 0;
+  match XGetTransientForHint(display, window, &mut owner) {
+    0 => None,
+    _ => Some(owner),
+  }
+}
+
+/// Sets or clears XUrgencyHint while keeping the rest of WM_HINTS.
+///
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn set_urgency (display: *mut Display, window: Window, urgent: bool) {
+  let mut hints = get_wm_hints(display, window).unwrap_or_else(|| zeroed());
+  if urgent {
+    hints.flags |= XUrgencyHint;
+  } else {
+    hints.flags &= !XUrgencyHint;
+  }
+  XSetWMHints(display, window, &hints);
+}
+
+
+//
+// private functions
+//
+
+
+// Writes `text` to an ICCCM property and its EWMH UTF-8 twin. The ICCCM
+// property gets type STRING when the text is ASCII and UTF8_STRING otherwise.
+unsafe fn set_text (display: *mut Display, window: Window, atoms: &mut AtomCache, property: Atom, net_property: Atom, text: &str) {
+  let utf8_string = atoms.atom("UTF8_STRING");
+  let data = PropertyData::Format8(text.as_bytes().to_vec());
+  let kind = if text.is_ascii() { XA_STRING } else { utf8_string };
+  set_property(display, window, property, kind, &data, PropModeReplace);
+  set_property(display, window, net_property, utf8_string, &data, PropModeReplace);
+}
+
+#[test]
+fn icccm_hints_builder_test () {
+  let hints = WmHints::new()
+    .title("Editor")
+    .fixed_size(640, 480)
+    .resize_inc(8, 16)
+    .gravity(10)
+    .urgent(true)
+    .input(true)
+    .protocol("WM_DELETE_WINDOW")
+    .protocol("WM_DELETE_WINDOW");
+
+  let size = hints.size_hints().unwrap();
+  assert_eq!(size.flags, PMinSize | PMaxSize | PResizeInc | PWinGravity);
+  assert_eq!((size.min_width, size.min_height, size.max_width, size.max_height), (640, 480, 640, 480));
+  assert_eq!((size.width_inc, size.height_inc, size.win_gravity), (8, 16, 10));
+
+  let wm = hints.wm_hints().unwrap();
+  assert_eq!(wm.flags, InputHint | XUrgencyHint);
+  assert_eq!(wm.input, True);
+  assert_eq!(hints.protocols, vec!["WM_DELETE_WINDOW".to_string()]);
+
+  assert!(WmHints::new().title("Editor").size_hints().is_none());
+  assert!(WmHints::new().title("Editor").wm_hints().is_none());
+
+  let named = WmHints::new().class("editor", "Editor").command(&["editor", "-n"]);
+  assert!(named.class_strings().unwrap().is_some());
+  assert_eq!(named.command_strings().unwrap().map(|args| args.len()), Some(2));
+  assert!(WmHints::new().class("edi\0tor", "Editor").class_strings().is_err());
+  assert!(WmHints::new().class("editor", "Edi\0tor").class_strings().is_err());
+  assert!(WmHints::new().command(&["editor", "file\0name"]).command_strings().is_err());
+}
+
+#[test]
+#[ignore]
+fn icccm_apply_test () {
+  use std::ptr::null;
+  use ::property::get_property;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let root = XDefaultRootWindow(display);
+    let owner = XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+    let window = XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+    WmHints::new()
+      .title("Grüße")
+      .class("editor", "Editor")
+      .min_size(320, 240)
+      .initial_state(IconicState)
+      .transient_for(owner)
+      .protocol("WM_DELETE_WINDOW")
+      .apply(display, window)
+      .unwrap();
+
+    let mut atoms = AtomCache::new(display);
+    let name = get_property(display, window, XA_WM_NAME, AnyPropertyType).unwrap().unwrap();
+    assert_eq!(name.kind, atoms.atom("UTF8_STRING"));
+    assert_eq!(name.utf8(), Some("Grüße".to_string()));
+
+    let class = get_property(display, window, XA_WM_CLASS, XA_STRING).unwrap().unwrap();
+    assert_eq!(class.strings(), Some(vec!["editor".to_string(), "Editor".to_string()]));
+
+    let size = get_wm_normal_hints(display, window).unwrap();
+    assert_eq!((size.flags & PMinSize, size.min_width, size.min_height), (PMinSize, 320, 240));
+    let wm = get_wm_hints(display, window).unwrap();
+    assert_eq!((wm.flags, wm.initial_state), (StateHint, IconicState));
+
+    set_urgency(display, window, true);
+    assert_eq!(get_wm_hints(display, window).unwrap().flags, StateHint | XUrgencyHint);
+    set_urgency(display, window, false);
+    assert_eq!(get_wm_hints(display, window).unwrap().flags, StateHint);
+
+    assert_eq!(get_transient_for(display, window), Some(owner));
+    assert_eq!(get_wm_protocols(display, window), vec![atoms.atom("WM_DELETE_WINDOW")]);
+
+    XDestroyWindow(display, window);
+    XDestroyWindow(display, owner);
+    XCloseDisplay(display);
+  }
+}
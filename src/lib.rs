@@ -10,8 +10,25 @@
 
 extern crate libc;
 
+pub mod atom;
+pub mod clipboard;
+pub mod cursor;
+#[cfg(feature="egl")]
+pub mod egl;
+pub mod error;
+pub mod ewmh;
 pub mod glx;
+pub mod hotkey;
+pub mod icccm;
 pub mod keysym;
+pub mod property;
+pub mod protocols;
 pub mod xcursor;
+pub mod xdnd;
+pub mod xembed;
 pub mod xf86vmode;
 pub mod xlib;
+#[cfg(feature="xrandr")]
+pub mod xrandr;
+#[cfg(feature="xsync")]
+pub mod xsync;
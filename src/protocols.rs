@@ -0,0 +1,307 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_int,
+  c_long,
+};
+
+use ::atom::AtomCache;
+use ::property::set_cardinals;
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+// Registers WM_PROTOCOLS on a window and turns the window manager's
+// protocol messages into WmProtocol values. Pings are answered as they
+// arrive; sync requests only need update_sync_counter once the window has
+// been redrawn at its new size.
+//
+//   let mut protocols = ProtocolDispatcher::new(display, window, &["WM_DELETE_WINDOW", "_NET_WM_PING"]);
+//   ...
+//   if let Some(WmProtocol::DeleteWindow) = protocols.handle_event(&event) {
+//     break;
+//   }
+pub struct ProtocolDispatcher {
+  display: *mut Display,
+  window: Window,
+  root: Window,
+  atoms: AtomCache,
+  counter: XID,
+  sync_value: Option<i64>,
+}
+
+impl ProtocolDispatcher {
+  /// Sets WM_PROTOCOLS on `window` to `names`, replacing any list set with
+  /// icccm::WmHints. Listing _NET_WM_SYNC_REQUEST creates the counter and
+  /// publishes it in _NET_WM_SYNC_REQUEST_COUNTER; the protocol is left out
+  /// if the crate is built without the xsync feature or the server lacks the
+  /// SYNC extension.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the dispatcher, and
+  /// `window` a window on it.
+  pub unsafe fn new (display: *mut Display, window: Window, names: &[&str]) -> ProtocolDispatcher {
+    let mut atoms = AtomCache::with_names(display, &ATOMS);
+    atoms.intern_all(names);
+
+    let mut counter = 0;
+    if names.contains(&"_NET_WM_SYNC_REQUEST") {
+      counter = create_counter(display);
+      if counter != 0 {
+        set_cardinals(display, window, atoms.atom("_NET_WM_SYNC_REQUEST_COUNTER"), &[counter as u32]);
+      }
+    }
+
+    let protocols: Vec<Atom> = names.iter()
+      .filter(|&&name| counter != 0 || name != "_NET_WM_SYNC_REQUEST")
+      .map(|name| atoms.atom(name))
+      .collect();
+    XSetWMProtocols(display, window, protocols.as_ptr(), protocols.len() as c_int);
+
+    ProtocolDispatcher {
+      display,
+      window,
+      root: root_of(display, window),
+      atoms,
+      counter,
+      sync_value: None,
+    }
+  }
+
+  pub fn window (&self) -> Window {
+    self.window
+  }
+
+  // The _NET_WM_SYNC_REQUEST counter, if one was created.
+  pub fn counter (&self) -> Option<XID> {
+    if self.counter != 0 { Some(self.counter) } else { None }
+  }
+
+  /// Decodes a WM_PROTOCOLS client message sent to the window. _NET_WM_PING
+  /// is reflected to the root window before it is returned.
+  ///
+  /// # Safety
+  ///
+  /// The dispatcher's display must still be open, and `event` must have been
+  /// read from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<WmProtocol> {
+    if event.kind() != ClientMessage {
+      return None;
+    }
+    let message = XClientMessageEvent::from(*event);
+    if message.window != self.window || message.format != 32 || message.message_type != self.atoms.atom("WM_PROTOCOLS") {
+      return None;
+    }
+
+    let protocol = message.data.get_long(0) as Atom;
+    let time = message.data.get_long(1) as Time;
+    if protocol == self.atoms.atom("WM_DELETE_WINDOW") {
+      Some(WmProtocol::DeleteWindow)
+    } else if protocol == self.atoms.atom("WM_TAKE_FOCUS") {
+      Some(WmProtocol::TakeFocus(time))
+    } else if protocol == self.atoms.atom("_NET_WM_PING") {
+      let mut reply = message;
+      reply.window = self.root;
+      let reply = XEvent::from(reply);
+      XSendEvent(self.display, self.root, False, SubstructureNotifyMask | SubstructureRedirectMask, &reply);
+      XFlush(self.display);
+      Some(WmProtocol::Ping(time))
+    } else if protocol == self.atoms.atom("_NET_WM_SYNC_REQUEST") && self.counter != 0 {
+      let value = sync_value(message.data.get_long(2), message.data.get_long(3));
+      self.sync_value = Some(value);
+      Some(WmProtocol::SyncRequest { time, value })
+    } else {
+      None
+    }
+  }
+
+  /// Tells the window manager the last sync request has been handled: call
+  /// after the ConfigureNotify that followed it has been processed and the
+  /// window repainted.
+  ///
+  /// # Safety
+  ///
+  /// The dispatcher's display must still be open.
+  pub unsafe fn update_sync_counter (&mut self) {
+    if let Some(value) = self.sync_value.take() {
+      set_counter(self.display, self.counter, value);
+      XFlush(self.display);
+    }
+  }
+}
+
+impl Drop for ProtocolDispatcher {
+  fn drop (&mut self) {
+    if self.counter != 0 {
+      unsafe {
+        destroy_counter(self.display, self.counter);
+      }
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WmProtocol {
+  // The user asked to close the window.
+  DeleteWindow,
+  // Set the input focus, using this timestamp.
+  TakeFocus(Time),
+  // Already answered; the timestamp is the window manager's.
+  Ping(Time),
+  // The window manager is about to resize the window and wants the counter
+  // set to `value` once it has been redrawn.
+  SyncRequest { time: Time, value: i64 },
+}
+
+
+//
+// constants
+//
+
+
+const ATOMS: [&str; 6] = [
+  "WM_DELETE_WINDOW",
+  "WM_PROTOCOLS",
+  "WM_TAKE_FOCUS",
+  "_NET_WM_PING",
+  "_NET_WM_SYNC_REQUEST",
+  "_NET_WM_SYNC_REQUEST_COUNTER",
+];
+
+
+//
+// private functions
+//
+
+
+// A counter starting at zero, or 0 if SYNC isn't available.
+#[cfg(feature="xsync")]
+unsafe fn create_counter (display: *mut Display) -> XID {
+  use ::xsync::*;
+
+  let (mut event_base, mut error_base) = (0, 0);
+  if XSyncQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+    return 0;
+  }
+  let (mut major, mut minor) = (SYNC_MAJOR_VERSION, SYNC_MINOR_VERSION);
+  if XSyncInitialize(display, &mut major, &mut minor) == 0 {
+    return 0;
+  }
+  XSyncCreateCounter(display, XSyncValue::default())
+}
+
+#[cfg(not(feature="xsync"))]
+unsafe fn create_counter (_display: *mut Display) -> XID {
+  0
+}
+
+#[cfg(feature="xsync")]
+unsafe fn destroy_counter (display: *mut Display, counter: XID) {
+  ::xsync::XSyncDestroyCounter(display, counter);
+}
+
+#[cfg(not(feature="xsync"))]
+unsafe fn destroy_counter (_display: *mut Display, _counter: XID) {
+}
+
+#[cfg(feature="xsync")]
+unsafe fn set_counter (display: *mut Display, counter: XID, value: i64) {
+  ::xsync::XSyncSetCounter(display, counter, ::xsync::XSyncValue::from(value));
+}
+
+#[cfg(not(feature="xsync"))]
+unsafe fn set_counter (_display: *mut Display, _counter: XID, _value: i64) {
+}
+
+// Joins the low and high halves sent in a _NET_WM_SYNC_REQUEST.
+fn sync_value (low: c_long, high: c_long) -> i64 {
+  ((high as i32 as i64) << 32) | (low as u32 as i64)
+}
+
+unsafe fn root_of (display: *mut Display, window: Window) -> Window {
+  let mut attributes: XWindowAttributes = ::std::mem::zeroed();
+  if XGetWindowAttributes(display, window, &mut attributes) == 0 {
+    return XDefaultRootWindow(display);
+  }
+  attributes.root
+}
+
+#[test]
+fn sync_value_test () {
+  assert_eq!(sync_value(5, 2), 0x2_0000_0005);
+  assert_eq!(sync_value(0xffff_ffff, -1), -1);
+  assert_eq!(sync_value(0, 0x8000_0000), i64::MIN);
+}
+
+#[test]
+#[ignore]
+fn protocol_dispatch_test () {
+  use std::ptr::null;
+  use ::ewmh::client_message;
+  use ::icccm::get_wm_protocols;
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let root = XDefaultRootWindow(display);
+    let window = XCreateSimpleWindow(display, root, 0, 0, 10, 10, 0, 0, 0);
+    let names = ["WM_DELETE_WINDOW", "WM_TAKE_FOCUS", "_NET_WM_PING", "_NET_WM_SYNC_REQUEST"];
+    let mut protocols = ProtocolDispatcher::new(display, window, &names);
+    let mut atoms = AtomCache::with_names(display, &ATOMS);
+    let advertised = get_wm_protocols(display, window);
+    assert!(advertised.contains(&atoms.atom("WM_DELETE_WINDOW")));
+    assert_eq!(advertised.contains(&atoms.atom("_NET_WM_SYNC_REQUEST")), protocols.counter().is_some());
+
+    // Stand in for the window manager, watching the root for ping replies.
+    XSelectInput(display, root, SubstructureNotifyMask);
+    let wm_protocols = atoms.atom("WM_PROTOCOLS");
+    let send = |protocol: Atom, data: [c_long; 4]| {
+      let event = client_message(window, wm_protocols, [protocol as c_long, data[0], data[1], data[2], data[3]]);
+      XSendEvent(display, window, False, NoEventMask, &event);
+    };
+    send(atoms.atom("WM_DELETE_WINDOW"), [10, 0, 0, 0]);
+    send(atoms.atom("WM_TAKE_FOCUS"), [11, 0, 0, 0]);
+    send(atoms.atom("_NET_WM_PING"), [12, window as c_long, 0, 0]);
+    send(atoms.atom("_NET_WM_SYNC_REQUEST"), [13, 7, 1, 0]);
+    XSync(display, False);
+
+    let mut dispatched = Vec::new();
+    let mut pong = None;
+    while XPending(display) > 0 {
+      let mut event: XEvent = ::std::mem::zeroed();
+      XNextEvent(display, &mut event);
+      let message = XClientMessageEvent::from(event);
+      if event.kind() == ClientMessage && message.window == root {
+        pong = Some(message.data.get_long(1));
+      }
+      dispatched.extend(protocols.handle_event(&event));
+      XSync(display, False);
+    }
+
+    assert_eq!(&dispatched[..3], &[WmProtocol::DeleteWindow, WmProtocol::TakeFocus(11), WmProtocol::Ping(12)]);
+    assert_eq!(pong, Some(12));
+
+    #[cfg(feature="xsync")]
+    {
+      if let Some(counter) = protocols.counter() {
+        assert_eq!(dispatched[3], WmProtocol::SyncRequest { time: 13, value: 0x1_0000_0007 });
+        protocols.update_sync_counter();
+        let mut value = ::xsync::XSyncValue::default();
+        ::xsync::XSyncQueryCounter(display, counter, &mut value);
+        assert_eq!(i64::from(value), 0x1_0000_0007);
+      }
+    }
+
+    drop(protocols);
+    XDestroyWindow(display, window);
+    XCloseDisplay(display);
+  }
+}
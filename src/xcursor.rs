@@ -2,15 +2,229 @@
 // The X11 libraries are available under the MIT license.
 // These bindings are public domain.
 
-use libc::c_char;
+use libc::{
+  c_char,
+  c_int,
+  c_long,
+  c_uchar,
+  c_uint,
+  c_void,
+  FILE,
+};
 
 use ::xlib::{
   Cursor,
   Display,
+  Drawable,
+  Font,
+  Pixmap,
+  XColor,
+  XImage,
 };
 
+pub mod file;
+pub mod image;
+
+
+//
+// functions
+//
+
 
 #[link(name="Xcursor")]
 extern "C" {
+  pub fn XcursorAnimateCreate (cursors: *mut XcursorCursors) -> *mut XcursorAnimate;
+  pub fn XcursorAnimateDestroy (animate: *mut XcursorAnimate);
+  pub fn XcursorAnimateNext (animate: *mut XcursorAnimate) -> Cursor;
+  pub fn XcursorCommentCreate (comment_type: XcursorUInt, length: c_int) -> *mut XcursorComment;
+  pub fn XcursorCommentDestroy (comment: *mut XcursorComment);
+  pub fn XcursorCommentsCreate (size: c_int) -> *mut XcursorComments;
+  pub fn XcursorCommentsDestroy (comments: *mut XcursorComments);
+  pub fn XcursorCursorsCreate (display: *mut Display, size: c_int) -> *mut XcursorCursors;
+  pub fn XcursorCursorsDestroy (cursors: *mut XcursorCursors);
+  pub fn XcursorFileLoad (file: *mut FILE, commentsp: *mut *mut XcursorComments, imagesp: *mut *mut XcursorImages) -> XcursorBool;
+  pub fn XcursorFileLoadAllImages (file: *mut FILE) -> *mut XcursorImages;
+  pub fn XcursorFileLoadImage (file: *mut FILE, size: c_int) -> *mut XcursorImage;
+  pub fn XcursorFileLoadImages (file: *mut FILE, size: c_int) -> *mut XcursorImages;
+  pub fn XcursorFileSave (file: *mut FILE, comments: *const XcursorComments, images: *const XcursorImages) -> XcursorBool;
+  pub fn XcursorFileSaveImages (file: *mut FILE, images: *const XcursorImages) -> XcursorBool;
+  pub fn XcursorFilenameLoad (filename: *const c_char, commentsp: *mut *mut XcursorComments, imagesp: *mut *mut XcursorImages) -> XcursorBool;
+  pub fn XcursorFilenameLoadAllImages (filename: *const c_char) -> *mut XcursorImages;
+  pub fn XcursorFilenameLoadCursor (display: *mut Display, filename: *const c_char) -> Cursor;
+  pub fn XcursorFilenameLoadCursors (display: *mut Display, filename: *const c_char) -> *mut XcursorCursors;
+  pub fn XcursorFilenameLoadImage (filename: *const c_char, size: c_int) -> *mut XcursorImage;
+  pub fn XcursorFilenameLoadImages (filename: *const c_char, size: c_int) -> *mut XcursorImages;
+  pub fn XcursorFilenameSave (filename: *const c_char, comments: *const XcursorComments, images: *const XcursorImages) -> XcursorBool;
+  pub fn XcursorFilenameSaveImages (filename: *const c_char, images: *const XcursorImages) -> XcursorBool;
+  pub fn XcursorGetDefaultSize (display: *mut Display) -> c_int;
+  pub fn XcursorGetTheme (display: *mut Display) -> *mut c_char;
+  pub fn XcursorGetThemeCore (display: *mut Display) -> XcursorBool;
+  pub fn XcursorImageCreate (width: c_int, height: c_int) -> *mut XcursorImage;
+  pub fn XcursorImageDestroy (image: *mut XcursorImage);
+  pub fn XcursorImageHash (image: *mut XImage, hash: *mut c_uchar);
+  pub fn XcursorImageLoadCursor (display: *mut Display, image: *const XcursorImage) -> Cursor;
+  pub fn XcursorImagesCreate (size: c_int) -> *mut XcursorImages;
+  pub fn XcursorImagesDestroy (images: *mut XcursorImages);
+  pub fn XcursorImagesLoadCursor (display: *mut Display, images: *const XcursorImages) -> Cursor;
+  pub fn XcursorImagesLoadCursors (display: *mut Display, images: *const XcursorImages) -> *mut XcursorCursors;
+  pub fn XcursorImagesSetName (images: *mut XcursorImages, name: *const c_char);
   pub fn XcursorLibraryLoadCursor (display: *mut Display, name: *const c_char) -> Cursor;
+  pub fn XcursorLibraryLoadCursors (display: *mut Display, name: *const c_char) -> *mut XcursorCursors;
+  pub fn XcursorLibraryLoadImage (name: *const c_char, theme: *const c_char, size: c_int) -> *mut XcursorImage;
+  pub fn XcursorLibraryLoadImages (name: *const c_char, theme: *const c_char, size: c_int) -> *mut XcursorImages;
+  pub fn XcursorLibraryPath () -> *const c_char;
+  pub fn XcursorLibraryShape (library: *const c_char) -> c_int;
+  pub fn XcursorNoticeCreateBitmap (display: *mut Display, pid: Pixmap, width: c_uint, height: c_uint);
+  pub fn XcursorNoticePutBitmap (display: *mut Display, draw: Drawable, image: *mut XImage);
+  pub fn XcursorSetDefaultSize (display: *mut Display, size: c_int) -> XcursorBool;
+  pub fn XcursorSetTheme (display: *mut Display, theme: *const c_char) -> XcursorBool;
+  pub fn XcursorSetThemeCore (display: *mut Display, theme_core: XcursorBool) -> XcursorBool;
+  pub fn XcursorShapeLoadCursor (display: *mut Display, shape: c_uint) -> Cursor;
+  pub fn XcursorShapeLoadCursors (display: *mut Display, shape: c_uint) -> *mut XcursorCursors;
+  pub fn XcursorShapeLoadImage (shape: c_uint, theme: *const c_char, size: c_int) -> *mut XcursorImage;
+  pub fn XcursorShapeLoadImages (shape: c_uint, theme: *const c_char, size: c_int) -> *mut XcursorImages;
+  pub fn XcursorSupportsAnim (display: *mut Display) -> XcursorBool;
+  pub fn XcursorSupportsARGB (display: *mut Display) -> XcursorBool;
+  pub fn XcursorTryShapeBitmapCursor (display: *mut Display, source: Pixmap, mask: Pixmap, foreground: *mut XColor, background: *mut XColor, x: c_uint, y: c_uint) -> Cursor;
+  pub fn XcursorTryShapeCursor (display: *mut Display, source_font: Font, mask_font: Font, source_char: c_uint, mask_char: c_uint, foreground: *const XColor, background: *const XColor) -> Cursor;
+  pub fn XcursorXcFileLoad (file: *mut XcursorFile, commentsp: *mut *mut XcursorComments, imagesp: *mut *mut XcursorImages) -> XcursorBool;
+  pub fn XcursorXcFileLoadAllImages (file: *mut XcursorFile) -> *mut XcursorImages;
+  pub fn XcursorXcFileLoadImage (file: *mut XcursorFile, size: c_int) -> *mut XcursorImage;
+  pub fn XcursorXcFileLoadImages (file: *mut XcursorFile, size: c_int) -> *mut XcursorImages;
+  pub fn XcursorXcFileSave (file: *mut XcursorFile, comments: *const XcursorComments, images: *const XcursorImages) -> XcursorBool;
+}
+
+
+//
+// types
+//
+
+
+pub type XcursorBool = c_int;
+pub type XcursorDim = XcursorUInt;
+pub type XcursorPixel = XcursorUInt;
+pub type XcursorUInt = c_uint;
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorAnimate {
+  pub cursors: *mut XcursorCursors,
+  pub sequence: c_int,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorChunkHeader {
+  pub header: XcursorUInt,
+  pub type_: XcursorUInt,
+  pub subtype: XcursorUInt,
+  pub version: XcursorUInt,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorComment {
+  pub version: XcursorUInt,
+  pub comment_type: XcursorUInt,
+  pub comment: *mut c_char,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorComments {
+  pub ncomment: c_int,
+  pub comments: *mut *mut XcursorComment,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorCursors {
+  pub dpy: *mut Display,
+  pub ref_: c_int,
+  pub ncursor: c_int,
+  pub cursors: *mut Cursor,
+}
+
+// Callbacks for XcursorXcFile* to read and write cursor files from any
+// source. `seek` follows fseek's `whence` convention.
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XcursorFile {
+  pub closure: *mut c_void,
+  pub read: Option<unsafe extern "C" fn (*mut XcursorFile, *mut c_uchar, c_int) -> c_int>,
+  pub write: Option<unsafe extern "C" fn (*mut XcursorFile, *mut c_uchar, c_int) -> c_int>,
+  pub seek: Option<unsafe extern "C" fn (*mut XcursorFile, c_long, c_int) -> c_int>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorFileHeader {
+  pub magic: XcursorUInt,
+  pub header: XcursorUInt,
+  pub version: XcursorUInt,
+  pub ntoc: XcursorUInt,
+  pub tocs: *mut XcursorFileToc,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorFileToc {
+  pub type_: XcursorUInt,
+  pub subtype: XcursorUInt,
+  pub position: XcursorUInt,
+}
+
+// `pixels` holds width * height premultiplied ARGB values, row by row.
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorImage {
+  pub version: XcursorUInt,
+  pub size: XcursorDim,
+  pub width: XcursorDim,
+  pub height: XcursorDim,
+  pub xhot: XcursorDim,
+  pub yhot: XcursorDim,
+  pub delay: XcursorUInt,
+  pub pixels: *mut XcursorPixel,
 }
+
+// The frames of an animated cursor, or the sizes of a themed one.
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XcursorImages {
+  pub nimage: c_int,
+  pub images: *mut *mut XcursorImage,
+  pub name: *mut c_char,
+}
+
+
+//
+// constants
+//
+
+
+// comment types
+pub const XCURSOR_COMMENT_COPYRIGHT: XcursorUInt = 1;
+pub const XCURSOR_COMMENT_LICENSE: XcursorUInt = 2;
+pub const XCURSOR_COMMENT_OTHER: XcursorUInt = 3;
+
+// file format
+pub const XCURSOR_MAGIC: XcursorUInt = 0x7275_6358;
+pub const XCURSOR_FILE_MAJOR: XcursorUInt = 1;
+pub const XCURSOR_FILE_MINOR: XcursorUInt = 0;
+pub const XCURSOR_FILE_VERSION: XcursorUInt = (XCURSOR_FILE_MAJOR << 16) | XCURSOR_FILE_MINOR;
+pub const XCURSOR_FILE_HEADER_LEN: XcursorUInt = 4 * 4;
+pub const XCURSOR_FILE_TOC_LEN: XcursorUInt = 3 * 4;
+pub const XCURSOR_CHUNK_HEADER_LEN: XcursorUInt = 4 * 4;
+pub const XCURSOR_COMMENT_TYPE: XcursorUInt = 0xfffe_0001;
+pub const XCURSOR_COMMENT_VERSION: XcursorUInt = 1;
+pub const XCURSOR_COMMENT_HEADER_LEN: XcursorUInt = XCURSOR_CHUNK_HEADER_LEN + 4;
+pub const XCURSOR_COMMENT_MAX_LEN: XcursorUInt = 0x0010_0000;
+pub const XCURSOR_IMAGE_TYPE: XcursorUInt = 0xfffd_0002;
+pub const XCURSOR_IMAGE_VERSION: XcursorUInt = 1;
+pub const XCURSOR_IMAGE_HEADER_LEN: XcursorUInt = XCURSOR_CHUNK_HEADER_LEN + 5 * 4;
+pub const XCURSOR_IMAGE_MAX_SIZE: XcursorDim = 0x7fff;
+
+// misc
+pub const XCURSOR_BITMAP_HASH_SIZE: usize = 16;
+pub const XCURSOR_CORE_THEME: &str = "core";
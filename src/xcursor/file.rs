@@ -0,0 +1,488 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::{
+  ErrorKind,
+  Read,
+  Write,
+};
+
+use ::xcursor::{
+  XCURSOR_CHUNK_HEADER_LEN,
+  XCURSOR_COMMENT_HEADER_LEN,
+  XCURSOR_COMMENT_MAX_LEN,
+  XCURSOR_COMMENT_TYPE,
+  XCURSOR_COMMENT_VERSION,
+  XCURSOR_FILE_HEADER_LEN,
+  XCURSOR_FILE_TOC_LEN,
+  XCURSOR_FILE_VERSION,
+  XCURSOR_IMAGE_HEADER_LEN,
+  XCURSOR_IMAGE_MAX_SIZE,
+  XCURSOR_IMAGE_TYPE,
+  XCURSOR_IMAGE_VERSION,
+  XCURSOR_MAGIC,
+};
+
+
+//
+// types
+//
+
+
+// Something CursorFile::parse would reject; the index is into the file's
+// images or comments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EncodeError {
+  // A zero or oversized dimension, a hotspot outside the image, or a pixel
+  // count other than width * height.
+  BadImage(usize),
+  // Longer than XCURSOR_COMMENT_MAX_LEN bytes.
+  BadComment(usize),
+}
+
+impl fmt::Display for EncodeError {
+  fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      EncodeError::BadImage(index) => write!(f, "Xcursor image {} can't be encoded", index),
+      EncodeError::BadComment(index) => write!(f, "Xcursor comment {} is too long", index),
+    }
+  }
+}
+
+impl error::Error for EncodeError {
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+  Io(io::Error),
+  BadMagic,
+  BadHeader,
+  // The chunk at this file offset is truncated or disagrees with its TOC
+  // entry.
+  BadChunk(u32),
+  BadImage(u32),
+  BadComment(u32),
+}
+
+impl From<io::Error> for ParseError {
+  fn from (error: io::Error) -> ParseError {
+    ParseError::Io(error)
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt (&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      ParseError::Io(ref error) => write!(f, "can't read Xcursor file: {}", error),
+      ParseError::BadMagic => write!(f, "not an Xcursor file"),
+      ParseError::BadHeader => write!(f, "bad Xcursor file header"),
+      ParseError::BadChunk(offset) => write!(f, "bad Xcursor chunk at offset {}", offset),
+      ParseError::BadImage(offset) => write!(f, "bad Xcursor image at offset {}", offset),
+      ParseError::BadComment(offset) => write!(f, "bad Xcursor comment at offset {}", offset),
+    }
+  }
+}
+
+impl error::Error for ParseError {
+  fn source (&self) -> Option<&(dyn error::Error + 'static)> {
+    match *self {
+      ParseError::Io(ref error) => Some(error),
+      _ => None,
+    }
+  }
+}
+
+// One image chunk. Mirrors XcursorImage: `size` is the nominal size used to
+// pick images for a requested cursor size, `delay` is the time in
+// milliseconds before the next frame, and `pixels` holds width * height
+// premultiplied ARGB values, row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+  pub version: u32,
+  pub size: u32,
+  pub width: u32,
+  pub height: u32,
+  pub xhot: u32,
+  pub yhot: u32,
+  pub delay: u32,
+  pub pixels: Vec<u32>,
+}
+
+impl Image {
+  pub fn new (size: u32, width: u32, height: u32) -> Image {
+    Image {
+      version: XCURSOR_IMAGE_VERSION,
+      size,
+      width,
+      height,
+      xhot: 0,
+      yhot: 0,
+      delay: 0,
+      pixels: vec![0; width as usize * height as usize],
+    }
+  }
+}
+
+// One comment chunk. `comment_type` is one of XCURSOR_COMMENT_COPYRIGHT,
+// XCURSOR_COMMENT_LICENSE or XCURSOR_COMMENT_OTHER.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comment {
+  pub version: u32,
+  pub comment_type: u32,
+  pub comment: String,
+}
+
+impl Comment {
+  pub fn new (comment_type: u32, comment: &str) -> Comment {
+    Comment {
+      version: XCURSOR_COMMENT_VERSION,
+      comment_type,
+      comment: comment.to_string(),
+    }
+  }
+}
+
+// The contents of an Xcursor file. Frames of an animated cursor are the
+// images sharing a nominal size, in file order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CursorFile {
+  pub comments: Vec<Comment>,
+  pub images: Vec<Image>,
+}
+
+impl CursorFile {
+  pub fn read<R: Read> (reader: &mut R) -> Result<CursorFile, ParseError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    CursorFile::parse(&data)
+  }
+
+  // Parses a whole file. Chunks of unknown types are skipped, as libXcursor
+  // does.
+  pub fn parse (data: &[u8]) -> Result<CursorFile, ParseError> {
+    if read_u32(data, 0) != Some(XCURSOR_MAGIC) {
+      return Err(ParseError::BadMagic);
+    }
+
+    let (header, ntoc) = match (read_u32(data, 4), read_u32(data, 12)) {
+      (Some(header), Some(ntoc)) if header >= XCURSOR_FILE_HEADER_LEN => (header, ntoc),
+      _ => { return Err(ParseError::BadHeader); },
+    };
+
+    let toc_end = header as u64 + ntoc as u64 * XCURSOR_FILE_TOC_LEN as u64;
+    if toc_end > data.len() as u64 {
+      return Err(ParseError::BadHeader);
+    }
+
+    let mut file = CursorFile::default();
+    for i in 0..ntoc {
+      let entry = (header + i * XCURSOR_FILE_TOC_LEN) as usize;
+      let (toc_type, toc_subtype, position) = match (read_u32(data, entry), read_u32(data, entry + 4), read_u32(data, entry + 8)) {
+        (Some(t), Some(s), Some(p)) => (t, s, p),
+        _ => { return Err(ParseError::BadHeader); },
+      };
+
+      match toc_type {
+        XCURSOR_IMAGE_TYPE => file.images.push(parse_image(data, position, toc_subtype)?),
+        XCURSOR_COMMENT_TYPE => file.comments.push(parse_comment(data, position, toc_subtype)?),
+        _ => {},
+      }
+    }
+
+    Ok(file)
+  }
+
+  // Fails with ErrorKind::InvalidInput if the file can't be encoded.
+  pub fn write<W: Write> (&self, writer: &mut W) -> io::Result<()> {
+    match self.encode() {
+      Ok(data) => writer.write_all(&data),
+      Err(error) => Err(io::Error::new(ErrorKind::InvalidInput, error)),
+    }
+  }
+
+  // Encodes the file with comments first, then images, in order.
+  pub fn encode (&self) -> Result<Vec<u8>, EncodeError> {
+    self.validate()?;
+
+    let ntoc = (self.comments.len() + self.images.len()) as u32;
+    let mut out = Vec::new();
+    push_u32(&mut out, XCURSOR_MAGIC);
+    push_u32(&mut out, XCURSOR_FILE_HEADER_LEN);
+    push_u32(&mut out, XCURSOR_FILE_VERSION);
+    push_u32(&mut out, ntoc);
+
+    let mut position = XCURSOR_FILE_HEADER_LEN + ntoc * XCURSOR_FILE_TOC_LEN;
+    for comment in self.comments.iter() {
+      push_u32(&mut out, XCURSOR_COMMENT_TYPE);
+      push_u32(&mut out, comment.comment_type);
+      push_u32(&mut out, position);
+      position += XCURSOR_COMMENT_HEADER_LEN + comment.comment.len() as u32;
+    }
+    for image in self.images.iter() {
+      push_u32(&mut out, XCURSOR_IMAGE_TYPE);
+      push_u32(&mut out, image.size);
+      push_u32(&mut out, position);
+      position += XCURSOR_IMAGE_HEADER_LEN + image.pixels.len() as u32 * 4;
+    }
+
+    for comment in self.comments.iter() {
+      push_u32(&mut out, XCURSOR_COMMENT_HEADER_LEN);
+      push_u32(&mut out, XCURSOR_COMMENT_TYPE);
+      push_u32(&mut out, comment.comment_type);
+      push_u32(&mut out, comment.version);
+      push_u32(&mut out, comment.comment.len() as u32);
+      out.extend_from_slice(comment.comment.as_bytes());
+    }
+    for image in self.images.iter() {
+      push_u32(&mut out, XCURSOR_IMAGE_HEADER_LEN);
+      push_u32(&mut out, XCURSOR_IMAGE_TYPE);
+      push_u32(&mut out, image.size);
+      push_u32(&mut out, image.version);
+      push_u32(&mut out, image.width);
+      push_u32(&mut out, image.height);
+      push_u32(&mut out, image.xhot);
+      push_u32(&mut out, image.yhot);
+      push_u32(&mut out, image.delay);
+      for &pixel in image.pixels.iter() {
+        push_u32(&mut out, pixel);
+      }
+    }
+
+    Ok(out)
+  }
+
+  // Applies the same limits as parse.
+  fn validate (&self) -> Result<(), EncodeError> {
+    for (index, comment) in self.comments.iter().enumerate() {
+      if comment.comment.len() as u64 > XCURSOR_COMMENT_MAX_LEN as u64 {
+        return Err(EncodeError::BadComment(index));
+      }
+    }
+
+    for (index, image) in self.images.iter().enumerate() {
+      let (width, height) = (image.width, image.height);
+      if width == 0 || height == 0 || width > XCURSOR_IMAGE_MAX_SIZE || height > XCURSOR_IMAGE_MAX_SIZE
+        || image.xhot > width || image.yhot > height || image.pixels.len() != width as usize * height as usize
+      {
+        return Err(EncodeError::BadImage(index));
+      }
+    }
+
+    Ok(())
+  }
+
+  // The distinct nominal sizes in the file, smallest first.
+  pub fn sizes (&self) -> Vec<u32> {
+    let mut sizes: Vec<u32> = self.images.iter().map(|image| image.size).collect();
+    sizes.sort();
+    sizes.dedup();
+    sizes
+  }
+
+  // The nominal size closest to `size`, preferring the first one found on a
+  // tie, as libXcursor does.
+  pub fn best_size (&self, size: u32) -> Option<u32> {
+    let mut best: Option<u32> = None;
+    for image in self.images.iter() {
+      let distance = |s: u32| (s as i64 - size as i64).abs();
+      let closer = match best {
+        Some(b) => distance(image.size) < distance(b),
+        None => true,
+      };
+      if closer {
+        best = Some(image.size);
+      }
+    }
+    best
+  }
+
+  // The frames for the nominal size closest to `size`.
+  pub fn images_for_size (&self, size: u32) -> Vec<&Image> {
+    match self.best_size(size) {
+      Some(best) => self.images.iter().filter(|image| image.size == best).collect(),
+      None => Vec::new(),
+    }
+  }
+}
+
+
+//
+// private functions
+//
+
+
+fn parse_comment (data: &[u8], position: u32, toc_subtype: u32) -> Result<Comment, ParseError> {
+  let (header, _, subtype, version) = parse_chunk_header(data, position, XCURSOR_COMMENT_TYPE, toc_subtype)?;
+  if header < XCURSOR_COMMENT_HEADER_LEN {
+    return Err(ParseError::BadComment(position));
+  }
+
+  let length = match read_u32(data, position as usize + XCURSOR_CHUNK_HEADER_LEN as usize) {
+    Some(length) if length <= XCURSOR_COMMENT_MAX_LEN => length as usize,
+    _ => { return Err(ParseError::BadComment(position)); },
+  };
+
+  let body = position as usize + header as usize;
+  if body + length > data.len() {
+    return Err(ParseError::BadChunk(position));
+  }
+
+  match String::from_utf8(data[body..body + length].to_vec()) {
+    Ok(comment) => Ok(Comment {
+      version,
+      comment_type: subtype,
+      comment,
+    }),
+    Err(_) => Err(ParseError::BadComment(position)),
+  }
+}
+
+fn parse_image (data: &[u8], position: u32, toc_subtype: u32) -> Result<Image, ParseError> {
+  let (header, _, subtype, version) = parse_chunk_header(data, position, XCURSOR_IMAGE_TYPE, toc_subtype)?;
+  if header < XCURSOR_IMAGE_HEADER_LEN {
+    return Err(ParseError::BadImage(position));
+  }
+
+  let field = |i: usize| read_u32(data, position as usize + XCURSOR_CHUNK_HEADER_LEN as usize + i * 4);
+  let (width, height, xhot, yhot, delay) = match (field(0), field(1), field(2), field(3), field(4)) {
+    (Some(w), Some(h), Some(x), Some(y), Some(d)) => (w, h, x, y, d),
+    _ => { return Err(ParseError::BadChunk(position)); },
+  };
+
+  if width == 0 || height == 0 || width > XCURSOR_IMAGE_MAX_SIZE || height > XCURSOR_IMAGE_MAX_SIZE || xhot > width || yhot > height {
+    return Err(ParseError::BadImage(position));
+  }
+
+  let start = position as usize + header as usize;
+  let count = width as usize * height as usize;
+  if start + count * 4 > data.len() {
+    return Err(ParseError::BadChunk(position));
+  }
+
+  let pixels = data[start..start + count * 4].chunks(4)
+    .map(|p| p[0] as u32 | (p[1] as u32) << 8 | (p[2] as u32) << 16 | (p[3] as u32) << 24)
+    .collect();
+
+  Ok(Image {
+    version,
+    size: subtype,
+    width,
+    height,
+    xhot,
+    yhot,
+    delay,
+    pixels,
+  })
+}
+
+fn parse_chunk_header (data: &[u8], position: u32, toc_type: u32, toc_subtype: u32) -> Result<(u32, u32, u32, u32), ParseError> {
+  let at = position as usize;
+  match (read_u32(data, at), read_u32(data, at + 4), read_u32(data, at + 8), read_u32(data, at + 12)) {
+    (Some(header), Some(chunk_type), Some(subtype), Some(version))
+      if header >= XCURSOR_CHUNK_HEADER_LEN && chunk_type == toc_type && subtype == toc_subtype =>
+    {
+      Ok((header, chunk_type, subtype, version))
+    },
+    _ => Err(ParseError::BadChunk(position)),
+  }
+}
+
+fn push_u32 (out: &mut Vec<u8>, value: u32) {
+  out.extend_from_slice(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]);
+}
+
+fn read_u32 (data: &[u8], at: usize) -> Option<u32> {
+  data.get(at..at.checked_add(4)?)
+    .map(|b| b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24)
+}
+
+#[test]
+fn xcursor_file_round_trip_test () {
+  use ::xcursor::{
+    XCURSOR_COMMENT_COPYRIGHT,
+    XCURSOR_COMMENT_LICENSE,
+  };
+
+  let mut small = Image::new(24, 2, 3);
+  small.xhot = 1;
+  small.yhot = 2;
+  small.delay = 50;
+  small.pixels = vec![0xff00_0000, 0x8040_2010, 0, 0xffff_ffff, 0x7f7f_7f7f, 1];
+
+  let mut frame = small.clone();
+  frame.pixels.reverse();
+
+  let mut large = Image::new(48, 4, 4);
+  large.pixels[5] = 0xdead_beef;
+
+  let file = CursorFile {
+    comments: vec![
+      Comment::new(XCURSOR_COMMENT_COPYRIGHT, "public domain"),
+      Comment::new(XCURSOR_COMMENT_LICENSE, "CC0 \u{2014} no rights reserved"),
+    ],
+    images: vec![small.clone(), frame.clone(), large.clone()],
+  };
+
+  let data = file.encode().unwrap();
+  assert_eq!(&data[0..4], b"Xcur");
+  let parsed = CursorFile::parse(&data).unwrap();
+  assert_eq!(parsed, file);
+  assert_eq!(parsed.encode(), Ok(data));
+
+  let mut buffer = Vec::new();
+  file.write(&mut buffer).unwrap();
+  assert_eq!(CursorFile::read(&mut &buffer[..]).unwrap(), file);
+
+  assert_eq!(parsed.sizes(), vec![24, 48]);
+  assert_eq!(parsed.images_for_size(32), vec![&small, &frame]);
+  assert_eq!(parsed.images_for_size(40), vec![&large]);
+  assert!(CursorFile::default().images_for_size(24).is_empty());
+}
+
+#[test]
+fn xcursor_file_reject_test () {
+  let mut image = Image::new(16, 1, 1);
+  image.pixels[0] = 0x1234_5678;
+  let data = CursorFile { comments: Vec::new(), images: vec![image.clone()] }.encode().unwrap();
+
+  match CursorFile::parse(b"Xcux") { Err(ParseError::BadMagic) => {}, r => panic!("{:?}", r) }
+  match CursorFile::parse(&data[..20]) { Err(ParseError::BadHeader) => {}, r => panic!("{:?}", r) }
+  assert_eq!(ParseError::BadChunk(36).to_string(), "bad Xcursor chunk at offset 36");
+  match CursorFile::parse(&data[..data.len() - 1]) { Err(ParseError::BadChunk(28)) => {}, r => panic!("{:?}", r) }
+
+  // A hotspot outside the image.
+  let mut bad = data.clone();
+  bad[28 + 24] = 2;
+  match CursorFile::parse(&bad) { Err(ParseError::BadImage(28)) => {}, r => panic!("{:?}", r) }
+
+  // A chunk whose subtype disagrees with its TOC entry.
+  let mut bad = data.clone();
+  bad[28 + 8] = 17;
+  match CursorFile::parse(&bad) { Err(ParseError::BadChunk(28)) => {}, r => panic!("{:?}", r) }
+
+  // The encoder refuses what the parser would reject.
+  let mut short = image.clone();
+  short.pixels.push(0);
+  let file = CursorFile { comments: Vec::new(), images: vec![image.clone(), short] };
+  assert_eq!(file.encode(), Err(EncodeError::BadImage(1)));
+  let error = file.write(&mut Vec::new()).unwrap_err();
+  assert_eq!(error.kind(), ErrorKind::InvalidInput);
+  assert_eq!(error.to_string(), "Xcursor image 1 can't be encoded");
+
+  let mut hotspot = image.clone();
+  hotspot.xhot = 2;
+  assert_eq!(CursorFile { comments: Vec::new(), images: vec![hotspot] }.encode(), Err(EncodeError::BadImage(0)));
+
+  let long = "x".repeat(XCURSOR_COMMENT_MAX_LEN as usize + 1);
+  let comments = vec![Comment::new(0, "ok"), Comment::new(0, &long)];
+  assert_eq!(CursorFile { comments, images: Vec::new() }.encode(), Err(EncodeError::BadComment(1)));
+
+  // Unknown chunk types are skipped.
+  let mut unknown = data.clone();
+  unknown[16] = 0;
+  assert_eq!(CursorFile::parse(&unknown).unwrap(), CursorFile::default());
+}
@@ -0,0 +1,246 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::slice::{
+  from_raw_parts,
+  from_raw_parts_mut,
+};
+
+use libc::c_int;
+
+use ::xcursor::*;
+use ::xcursor::file::Image;
+use ::xlib::{
+  Cursor,
+  Display,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorImageError {
+  // Zero or larger than XCURSOR_IMAGE_MAX_SIZE, or the buffer length
+  // doesn't match width * height * 4.
+  BadSize,
+  // Past the right or bottom edge. A hotspot on the edge itself is allowed,
+  // as libXcursor does.
+  BadHotspot,
+  AllocFailed,
+  // The server lacks the RENDER support needed for ARGB cursors.
+  Unsupported,
+  LoadFailed,
+}
+
+// An owned XcursorImage, destroyed with XcursorImageDestroy on drop.
+pub struct CursorImage {
+  image: *mut XcursorImage,
+}
+
+impl CursorImage {
+  // Converts a straight-alpha RGBA buffer (4 bytes per pixel, row by row)
+  // into the premultiplied ARGB pixels Xcursor expects.
+  pub fn from_rgba (rgba: &[u8], width: u32, height: u32, xhot: u32, yhot: u32) -> Result<CursorImage, CursorImageError> {
+    if width == 0 || height == 0 || width > XCURSOR_IMAGE_MAX_SIZE || height > XCURSOR_IMAGE_MAX_SIZE {
+      return Err(CursorImageError::BadSize);
+    }
+    if rgba.len() != width as usize * height as usize * 4 {
+      return Err(CursorImageError::BadSize);
+    }
+    if xhot > width || yhot > height {
+      return Err(CursorImageError::BadHotspot);
+    }
+
+    let image = unsafe { XcursorImageCreate(width as c_int, height as c_int) };
+    if image.is_null() {
+      return Err(CursorImageError::AllocFailed);
+    }
+
+    let mut image = CursorImage { image };
+    unsafe {
+      (*image.image).xhot = xhot;
+      (*image.image).yhot = yhot;
+    }
+
+    for (pixel, rgba) in image.pixels_mut().iter_mut().zip(rgba.chunks(4)) {
+      *pixel = premultiply(rgba[0], rgba[1], rgba[2], rgba[3]);
+    }
+
+    Ok(image)
+  }
+
+  // Copies an image read from an Xcursor file, keeping its nominal size
+  // and delay.
+  pub fn from_image (source: &Image) -> Result<CursorImage, CursorImageError> {
+    let (width, height) = (source.width, source.height);
+    if width == 0 || height == 0 || width > XCURSOR_IMAGE_MAX_SIZE || height > XCURSOR_IMAGE_MAX_SIZE {
+      return Err(CursorImageError::BadSize);
+    }
+    if source.pixels.len() != width as usize * height as usize {
+      return Err(CursorImageError::BadSize);
+    }
+    if source.xhot > width || source.yhot > height {
+      return Err(CursorImageError::BadHotspot);
+    }
+
+    let image = unsafe { XcursorImageCreate(width as c_int, height as c_int) };
+    if image.is_null() {
+      return Err(CursorImageError::AllocFailed);
+    }
+
+    let mut image = CursorImage { image };
+    unsafe {
+      (*image.image).size = source.size;
+      (*image.image).xhot = source.xhot;
+      (*image.image).yhot = source.yhot;
+      (*image.image).delay = source.delay;
+    }
+    image.pixels_mut().copy_from_slice(&source.pixels);
+    Ok(image)
+  }
+
+  pub fn raw (&self) -> *mut XcursorImage {
+    self.image
+  }
+
+  pub fn width (&self) -> u32 {
+    unsafe { (*self.image).width }
+  }
+
+  pub fn height (&self) -> u32 {
+    unsafe { (*self.image).height }
+  }
+
+  pub fn hotspot (&self) -> (u32, u32) {
+    unsafe { ((*self.image).xhot, (*self.image).yhot) }
+  }
+
+  // The delay in milliseconds before the next frame of an animated cursor.
+  pub fn set_delay (&mut self, delay: u32) {
+    unsafe { (*self.image).delay = delay; }
+  }
+
+  pub fn pixels (&self) -> &[XcursorPixel] {
+    unsafe { from_raw_parts((*self.image).pixels, self.len()) }
+  }
+
+  pub fn pixels_mut (&mut self) -> &mut [XcursorPixel] {
+    unsafe { from_raw_parts_mut((*self.image).pixels, self.len()) }
+  }
+
+  /// Creates a cursor from this image. The image may be dropped afterwards.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection.
+  pub unsafe fn load_cursor (&self, display: *mut Display) -> Result<Cursor, CursorImageError> {
+    if XcursorSupportsARGB(display) == 0 {
+      return Err(CursorImageError::Unsupported);
+    }
+
+    match XcursorImageLoadCursor(display, self.image) {
+      0 => Err(CursorImageError::LoadFailed),
+      cursor => Ok(cursor),
+    }
+  }
+
+  fn len (&self) -> usize {
+    self.width() as usize * self.height() as usize
+  }
+}
+
+impl<'a> From<&'a CursorImage> for Image {
+  fn from (image: &'a CursorImage) -> Image {
+    let raw = unsafe { &*image.image };
+    Image {
+      version: raw.version,
+      size: raw.size,
+      width: raw.width,
+      height: raw.height,
+      xhot: raw.xhot,
+      yhot: raw.yhot,
+      delay: raw.delay,
+      pixels: image.pixels().to_vec(),
+    }
+  }
+}
+
+impl Drop for CursorImage {
+  fn drop (&mut self) {
+    unsafe {
+      XcursorImageDestroy(self.image);
+    }
+  }
+}
+
+
+//
+// functions
+//
+
+
+/// Creates an ARGB cursor from a straight-alpha RGBA buffer and hotspot.
+/// Free it with XFreeCursor.
+///
+/// # Safety
+///
+/// `display` must be an open connection.
+pub unsafe fn argb_cursor (display: *mut Display, rgba: &[u8], width: u32, height: u32, xhot: u32, yhot: u32) -> Result<Cursor, CursorImageError> {
+  CursorImage::from_rgba(rgba, width, height, xhot, yhot)?.load_cursor(display)
+}
+
+pub fn premultiply (r: u8, g: u8, b: u8, a: u8) -> XcursorPixel {
+  let scale = |c: u8| (c as u32 * a as u32 + 127) / 255;
+  (a as u32) << 24 | scale(r) << 16 | scale(g) << 8 | scale(b)
+}
+
+#[test]
+fn xcursor_image_from_rgba_test () {
+  let rgba = [
+    255, 0, 0, 255,   0, 255, 0, 128,
+    0, 0, 255, 0,     255, 255, 255, 255,
+  ];
+
+  let image = CursorImage::from_rgba(&rgba, 2, 2, 1, 0).unwrap();
+  assert_eq!((image.width(), image.height()), (2, 2));
+  assert_eq!(image.hotspot(), (1, 0));
+  assert_eq!(image.pixels(), &[0xffff_0000, 0x8000_8000, 0x0000_0000, 0xffff_ffff]);
+
+  assert_eq!(CursorImage::from_rgba(&rgba, 2, 1, 0, 0).err(), Some(CursorImageError::BadSize));
+  assert_eq!(CursorImage::from_rgba(&rgba, 2, 2, 2, 2).unwrap().hotspot(), (2, 2));
+  assert_eq!(CursorImage::from_rgba(&rgba, 2, 2, 3, 0).err(), Some(CursorImageError::BadHotspot));
+  assert_eq!(CursorImage::from_rgba(&[], 0, 0, 0, 0).err(), Some(CursorImageError::BadSize));
+}
+
+#[test]
+fn xcursor_image_file_conversion_test () {
+  let mut source = Image::new(32, 2, 2);
+  source.xhot = 1;
+  source.yhot = 1;
+  source.delay = 40;
+  source.pixels = vec![0xff00_0000, 0x8080_0000, 0, 0xffff_ffff];
+
+  let image = CursorImage::from_image(&source).unwrap();
+  assert_eq!(image.hotspot(), (1, 1));
+  assert_eq!(image.pixels(), &source.pixels[..]);
+  assert_eq!(Image::from(&image), source);
+
+  let mut bad = source.clone();
+  bad.pixels.pop();
+  assert_eq!(CursorImage::from_image(&bad).err(), Some(CursorImageError::BadSize));
+  bad = source.clone();
+  bad.yhot = 3;
+  assert_eq!(CursorImage::from_image(&bad).err(), Some(CursorImageError::BadHotspot));
+
+  // Every image the parser accepts converts, including edge hotspots.
+  let mut edge = source.clone();
+  edge.xhot = 2;
+  edge.yhot = 2;
+  let file = ::xcursor::file::CursorFile { comments: Vec::new(), images: vec![edge.clone()] };
+  let parsed = ::xcursor::file::CursorFile::parse(&file.encode().unwrap()).unwrap();
+  assert_eq!(Image::from(&CursorImage::from_image(&parsed.images[0]).unwrap()), edge);
+}
@@ -0,0 +1,691 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::cmp::min;
+use std::time::Duration;
+
+use libc::{
+  c_long,
+  c_uint,
+};
+
+use ::atom::AtomCache;
+use ::clipboard::{
+  Clipboard,
+  ClipboardError,
+};
+use ::error::ErrorTrap;
+use ::ewmh::client_message;
+use ::property::{
+  Property,
+  get_property,
+  set_atoms,
+};
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DndAction {
+  Copy,
+  Move,
+  Link,
+  Ask,
+  Private,
+}
+
+impl DndAction {
+  pub const ALL: [DndAction; 5] = [DndAction::Copy, DndAction::Move, DndAction::Link, DndAction::Ask, DndAction::Private];
+
+  pub fn name (self) -> &'static str {
+    match self {
+      DndAction::Copy => "XdndActionCopy",
+      DndAction::Move => "XdndActionMove",
+      DndAction::Link => "XdndActionLink",
+      DndAction::Ask => "XdndActionAsk",
+      DndAction::Private => "XdndActionPrivate",
+    }
+  }
+}
+
+// What the source learns from the current target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragEvent {
+  // The answer to the last position; None if the target won't accept a
+  // drop there.
+  Status { target: Window, action: Option<DndAction> },
+  // The drag is over. None if the target rejected or failed the drop.
+  Finished { target: Window, action: Option<DndAction> },
+}
+
+// The source side of a drag. The clipboard's window is the XDND source
+// window: it owns XdndSelection and receives the targets' replies, so its
+// events must go through DragSource::handle_event while the drag lasts.
+pub struct DragSource<'a> {
+  clipboard: &'a mut Clipboard,
+  display: *mut Display,
+  root: Window,
+  atoms: AtomCache,
+  types: Vec<Atom>,
+  action: DndAction,
+  site: Option<DropSite>,
+  accepted: Option<DndAction>,
+  awaiting_status: bool,
+  pending_position: Option<(i32, i32, Time)>,
+  pending_drop: Option<Time>,
+  quiet_rect: Option<(i32, i32, u32, u32)>,
+}
+
+impl<'a> DragSource<'a> {
+  /// Starts a drag offering `data` under each type name (usually MIME types),
+  /// requesting `action`. `time` is the timestamp of the button press.
+  ///
+  /// # Safety
+  ///
+  /// The clipboard's display must still be open.
+  pub unsafe fn start (clipboard: &'a mut Clipboard, data: &[(&str, &[u8])], action: DndAction, time: Time) -> Result<DragSource<'a>, ClipboardError> {
+    let display = clipboard.display();
+    let names: Vec<&str> = data.iter().map(|&(name, _)| name).collect();
+    let mut atoms = AtomCache::with_names(display, &ATOMS);
+    atoms.intern_all(&names);
+
+    let selection = atoms.atom("XdndSelection");
+    clipboard.set(selection, data, time)?;
+
+    let types: Vec<Atom> = names.iter().map(|name| atoms.atom(name)).collect();
+    if types.len() > 3 {
+      set_atoms(display, clipboard.window(), atoms.atom("XdndTypeList"), &types);
+    }
+
+    Ok(DragSource {
+      root: XDefaultRootWindow(display),
+      clipboard,
+      display,
+      atoms,
+      types,
+      action,
+      site: None,
+      accepted: None,
+      awaiting_status: false,
+      pending_position: None,
+      pending_drop: None,
+      quiet_rect: None,
+    })
+  }
+
+  // The XdndAware window under the pointer, if any.
+  pub fn target (&self) -> Option<Window> {
+    self.site.as_ref().map(|site| site.window)
+  }
+
+  // The action the current target last agreed to.
+  pub fn accepted (&self) -> Option<DndAction> {
+    self.accepted
+  }
+
+  /// Follows the pointer to wherever XQueryPointer finds it.
+  ///
+  /// # Safety
+  ///
+  /// The display of the source's clipboard must still be open.
+  pub unsafe fn track_pointer (&mut self, time: Time) {
+    let (mut root, mut child) = (0, 0);
+    let (mut x, mut y, mut window_x, mut window_y) = (0, 0, 0, 0);
+    let mut mask: c_uint = 0;
+    if XQueryPointer(self.display, self.root, &mut root, &mut child, &mut x, &mut y, &mut window_x, &mut window_y, &mut mask) != 0 {
+      self.motion(x, y, time);
+    }
+  }
+
+  /// Moves the drag to root coordinates (x, y), sending XdndLeave and
+  /// XdndEnter when the target changes and XdndPosition when the target
+  /// needs to hear about it. Only one position is in flight at a time.
+  ///
+  /// # Safety
+  ///
+  /// The display of the source's clipboard must still be open.
+  pub unsafe fn motion (&mut self, x: i32, y: i32, time: Time) {
+    let site = self.find_site(x, y);
+    if site.as_ref().map(|site| site.window) != self.target() {
+      self.leave();
+      if let Some(site) = site {
+        self.enter(site);
+      }
+    }
+    if self.site.is_none() {
+      return;
+    }
+
+    if self.awaiting_status {
+      self.pending_position = Some((x, y, time));
+      return;
+    }
+    if let Some((rect_x, rect_y, width, height)) = self.quiet_rect {
+      if x >= rect_x && y >= rect_y && x < rect_x + width as i32 && y < rect_y + height as i32 {
+        return;
+      }
+    }
+    self.send_position(x, y, time);
+  }
+
+  /// Drops on the current target. Returns false, after sending XdndLeave,
+  /// if there is no target or it doesn't accept the drop; otherwise wait for
+  /// DragEvent::Finished.
+  ///
+  /// # Safety
+  ///
+  /// The display of the source's clipboard must still be open.
+  pub unsafe fn release (&mut self, time: Time) -> bool {
+    if self.site.is_none() {
+      return false;
+    }
+    if self.awaiting_status {
+      self.pending_drop = Some(time);
+      return true;
+    }
+    if self.accepted.is_none() {
+      self.leave();
+      return false;
+    }
+
+    let data = [self.clipboard.window() as c_long, 0, time as c_long, 0, 0];
+    self.send("XdndDrop", data);
+    true
+  }
+
+  /// # Safety
+  ///
+  /// The display of the source's clipboard must still be open.
+  pub unsafe fn cancel (&mut self) {
+    self.leave();
+  }
+
+  /// Handles XdndStatus and XdndFinished, and passes everything else on to
+  /// the clipboard so the target can fetch the data.
+  ///
+  /// # Safety
+  ///
+  /// The display of the source's clipboard must still be open, and `event`
+  /// must have been read from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<DragEvent> {
+    if event.kind() != ClientMessage {
+      self.clipboard.handle_event(event);
+      return None;
+    }
+
+    let message = XClientMessageEvent::from(*event);
+    if message.window != self.clipboard.window() || message.format != 32 {
+      return None;
+    }
+    let from = message.data.get_long(0) as Window;
+    if Some(from) != self.target() {
+      return None;
+    }
+
+    if message.message_type == self.atoms.atom("XdndStatus") {
+      let flags = message.data.get_long(1);
+      self.awaiting_status = false;
+      self.accepted = match flags & 1 {
+        0 => None,
+        _ => Some(self.action_from(message.data.get_long(4)).unwrap_or(self.action)),
+      };
+      self.quiet_rect = match flags & 2 {
+        0 => {
+          let (x, y) = unpack_point(message.data.get_long(2));
+          let (width, height) = unpack_size(message.data.get_long(3));
+          Some((x, y, width, height))
+        },
+        _ => None,
+      };
+
+      if let Some(time) = self.pending_drop.take() {
+        self.release(time);
+      } else if let Some((x, y, time)) = self.pending_position.take() {
+        self.motion(x, y, time);
+      }
+      return Some(DragEvent::Status { target: from, action: self.accepted });
+    }
+
+    if message.message_type == self.atoms.atom("XdndFinished") {
+      // Version 5 targets say whether the drop succeeded and what they did.
+      let version = self.site.as_ref().map_or(0, |site| site.version);
+      let action = if version < 5 {
+        self.accepted
+      } else if message.data.get_long(1) & 1 != 0 {
+        self.action_from(message.data.get_long(2)).or(self.accepted)
+      } else {
+        None
+      };
+      self.reset();
+      self.release_selection();
+      return Some(DragEvent::Finished { target: from, action });
+    }
+
+    None
+  }
+
+  fn action_from (&self, atom: c_long) -> Option<DndAction> {
+    DndAction::ALL.iter().cloned().find(|action| atom != 0 && self.atoms.get(action.name()) == Some(atom as Atom))
+  }
+
+  fn reset (&mut self) {
+    self.site = None;
+    self.accepted = None;
+    self.awaiting_status = false;
+    self.pending_position = None;
+    self.pending_drop = None;
+    self.quiet_rect = None;
+  }
+
+  unsafe fn enter (&mut self, site: DropSite) {
+    let mut flags = site.version << 24;
+    if self.types.len() > 3 {
+      flags |= 1;
+    }
+    let first = |i: usize| self.types.get(i).map_or(0, |&atom| atom as c_long);
+    let data = [self.clipboard.window() as c_long, flags, first(0), first(1), first(2)];
+
+    self.site = Some(site);
+    self.send("XdndEnter", data);
+  }
+
+  // Stops the clipboard serving the dragged data.
+  unsafe fn release_selection (&mut self) {
+    let selection = self.atoms.atom("XdndSelection");
+    self.clipboard.release(selection);
+  }
+
+  unsafe fn leave (&mut self) {
+    if self.site.is_some() {
+      let data = [self.clipboard.window() as c_long, 0, 0, 0, 0];
+      self.send("XdndLeave", data);
+    }
+    self.reset();
+  }
+
+  unsafe fn send_position (&mut self, x: i32, y: i32, time: Time) {
+    let action = self.atoms.atom(self.action.name());
+    let data = [self.clipboard.window() as c_long, 0, pack_point(x, y), time as c_long, action as c_long];
+    self.send("XdndPosition", data);
+    self.awaiting_status = true;
+  }
+
+  // Sends to the target, through its proxy if it has one.
+  unsafe fn send (&mut self, message: &str, data: [c_long; 5]) {
+    if let Some((window, proxy)) = self.site.as_ref().map(|site| (site.window, site.proxy)) {
+      let event = client_message(window, self.atoms.atom(message), data);
+      XSendEvent(self.display, proxy, False, NoEventMask, &event);
+    }
+  }
+
+  // Descends from the root to the first XdndAware window containing the
+  // point. Windows may vanish while we look, so errors are trapped.
+  unsafe fn find_site (&mut self, x: i32, y: i32) -> Option<DropSite> {
+    let trap = ErrorTrap::new(self.display);
+    let mut window = self.root;
+
+    let site = loop {
+      let site = self.drop_site(window);
+      if site.is_some() {
+        break site;
+      }
+
+      let (mut window_x, mut window_y, mut child) = (0, 0, 0);
+      if XTranslateCoordinates(self.display, self.root, window, x, y, &mut window_x, &mut window_y, &mut child) == 0 || child == 0 {
+        break None;
+      }
+      window = child;
+    };
+
+    match trap.finish() {
+      Ok(()) => site,
+      Err(_) => None,
+    }
+  }
+
+  // The XdndAware version of `window`, honouring XdndProxy. A proxy is
+  // only valid if its own XdndProxy points back at itself.
+  unsafe fn drop_site (&mut self, window: Window) -> Option<DropSite> {
+    let proxy_atom = self.atoms.atom("XdndProxy");
+    let proxy = get_window(self.display, window, proxy_atom)
+      .filter(|&proxy| get_window(self.display, proxy, proxy_atom) == Some(proxy));
+    let aware = proxy.unwrap_or(window);
+
+    let version = get_property(self.display, aware, self.atoms.atom("XdndAware"), XA_ATOM).ok()??.u32s()?.first().cloned()? as c_long;
+    if version < MIN_VERSION {
+      return None;
+    }
+    Some(DropSite { window, proxy: aware, version: min(version, XDND_VERSION) })
+  }
+}
+
+impl<'a> Drop for DragSource<'a> {
+  fn drop (&mut self) {
+    unsafe {
+      self.leave();
+      self.release_selection();
+    }
+  }
+}
+
+// What a drop target hears from the source. Coordinates are relative to
+// the root window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DropEvent {
+  Enter { source: Window, types: Vec<Atom> },
+  // Must be answered with DropTarget::reply_status.
+  Position { source: Window, x: i32, y: i32, time: Time, action: Option<DndAction> },
+  Leave { source: Window },
+  // Fetch the data, then call DropTarget::finish.
+  Drop { source: Window, time: Time },
+}
+
+// The target side for one top-level window.
+pub struct DropTarget {
+  display: *mut Display,
+  window: Window,
+  atoms: AtomCache,
+  source: Option<Window>,
+  types: Vec<Atom>,
+  drop_time: Time,
+}
+
+impl DropTarget {
+  /// Marks `window` as XdndAware.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the target, and
+  /// `window` a window on it.
+  pub unsafe fn new (display: *mut Display, window: Window) -> DropTarget {
+    let mut atoms = AtomCache::with_names(display, &ATOMS);
+    set_atoms(display, window, atoms.atom("XdndAware"), &[XDND_VERSION as Atom]);
+    DropTarget {
+      display,
+      window,
+      atoms,
+      source: None,
+      types: Vec::new(),
+      drop_time: CurrentTime,
+    }
+  }
+
+  pub fn window (&self) -> Window {
+    self.window
+  }
+
+  pub fn source (&self) -> Option<Window> {
+    self.source
+  }
+
+  // The types offered by the current drag.
+  pub fn types (&self) -> &[Atom] {
+    &self.types
+  }
+
+  /// # Safety
+  ///
+  /// The target's display must still be open, and `event` must have been read
+  /// from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<DropEvent> {
+    if event.kind() != ClientMessage {
+      return None;
+    }
+    let message = XClientMessageEvent::from(*event);
+    if message.window != self.window || message.format != 32 {
+      return None;
+    }
+
+    let data = &message.data;
+    let source = data.get_long(0) as Window;
+    let kind = message.message_type;
+
+    if kind == self.atoms.atom("XdndEnter") {
+      // More than three types are listed on the source window instead.
+      self.types = if data.get_long(1) & 1 != 0 {
+        get_property(self.display, source, self.atoms.atom("XdndTypeList"), XA_ATOM).ok()
+          .and_then(|list| list?.atoms())
+          .unwrap_or_default()
+      } else {
+        (2..5).map(|i| data.get_long(i) as Atom).filter(|&atom| atom != 0).collect()
+      };
+      self.source = Some(source);
+      return Some(DropEvent::Enter { source, types: self.types.clone() });
+    }
+
+    if Some(source) != self.source {
+      return None;
+    }
+
+    if kind == self.atoms.atom("XdndPosition") {
+      let (x, y) = unpack_point(data.get_long(2));
+      let action = self.action_from(data.get_long(4));
+      Some(DropEvent::Position { source, x, y, time: data.get_long(3) as Time, action })
+    } else if kind == self.atoms.atom("XdndLeave") {
+      self.source = None;
+      self.types.clear();
+      Some(DropEvent::Leave { source })
+    } else if kind == self.atoms.atom("XdndDrop") {
+      self.drop_time = data.get_long(2) as Time;
+      Some(DropEvent::Drop { source, time: self.drop_time })
+    } else {
+      None
+    }
+  }
+
+  /// Answers the last position with the action the drop would perform, or
+  /// None to refuse it. `rect` is an area in root coordinates over which the
+  /// answer won't change, sparing the source further positions inside it.
+  ///
+  /// # Safety
+  ///
+  /// The target's display must still be open.
+  pub unsafe fn reply_status (&mut self, action: Option<DndAction>, rect: Option<(i32, i32, u32, u32)>) {
+    let source = match self.source {
+      Some(source) => source,
+      None => { return; },
+    };
+
+    let mut flags = action.is_some() as c_long;
+    let (point, size) = match rect {
+      Some((x, y, width, height)) => (pack_point(x, y), pack_size(width, height)),
+      None => { flags |= 2; (0, 0) },
+    };
+    let action = action.map_or(0, |action| self.atoms.atom(action.name()));
+    self.send(source, "XdndStatus", [self.window as c_long, flags, point, size, action as c_long]);
+  }
+
+  /// Requests the dropped data as `kind`. Call after DropEvent::Drop and
+  /// before finish.
+  ///
+  /// # Safety
+  ///
+  /// The target's display must still be open, and `clipboard` must use the
+  /// same display.
+  pub unsafe fn fetch (&mut self, clipboard: &mut Clipboard, kind: Atom, timeout: Duration) -> Result<Property, ClipboardError> {
+    let selection = self.atoms.atom("XdndSelection");
+    clipboard.get_at(selection, kind, self.drop_time, timeout)
+  }
+
+  /// Ends the drop, reporting the action performed or None on failure.
+  ///
+  /// # Safety
+  ///
+  /// The target's display must still be open.
+  pub unsafe fn finish (&mut self, action: Option<DndAction>) {
+    if let Some(source) = self.source.take() {
+      let performed = action.map_or(0, |action| self.atoms.atom(action.name()));
+      self.send(source, "XdndFinished", [self.window as c_long, action.is_some() as c_long, performed as c_long, 0, 0]);
+      self.types.clear();
+    }
+  }
+
+  fn action_from (&self, atom: c_long) -> Option<DndAction> {
+    DndAction::ALL.iter().cloned().find(|action| atom != 0 && self.atoms.get(action.name()) == Some(atom as Atom))
+  }
+
+  unsafe fn send (&mut self, source: Window, message: &str, data: [c_long; 5]) {
+    let event = client_message(source, self.atoms.atom(message), data);
+    XSendEvent(self.display, source, False, NoEventMask, &event);
+  }
+}
+
+struct DropSite {
+  window: Window,
+  // Where messages go: the proxy, or the window itself.
+  proxy: Window,
+  version: c_long,
+}
+
+
+//
+// functions
+//
+
+
+// Splits a text/uri-list payload into URIs, skipping comments.
+pub fn parse_uri_list (data: &[u8]) -> Vec<String> {
+  String::from_utf8_lossy(data).lines()
+    .map(|line| line.trim_end_matches('\r'))
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|line| line.to_string())
+    .collect()
+}
+
+
+//
+// constants
+//
+
+
+pub const XDND_VERSION: c_long = 5;
+
+const ATOMS: [&str; 16] = [
+  "XdndActionAsk",
+  "XdndActionCopy",
+  "XdndActionLink",
+  "XdndActionMove",
+  "XdndActionPrivate",
+  "XdndAware",
+  "XdndDrop",
+  "XdndEnter",
+  "XdndFinished",
+  "XdndLeave",
+  "XdndPosition",
+  "XdndProxy",
+  "XdndSelection",
+  "XdndStatus",
+  "XdndTypeList",
+  "text/uri-list",
+];
+
+// The oldest version whose messages carry everything used here.
+const MIN_VERSION: c_long = 3;
+
+
+//
+// private functions
+//
+
+
+unsafe fn get_window (display: *mut Display, window: Window, property: Atom) -> Option<Window> {
+  let property = get_property(display, window, property, XA_WINDOW).ok()??;
+  property.atoms()?.first().cloned().filter(|&window| window != 0)
+}
+
+fn pack_point (x: i32, y: i32) -> c_long {
+  ((x as c_long & 0xffff) << 16) | (y as c_long & 0xffff)
+}
+
+fn pack_size (width: u32, height: u32) -> c_long {
+  ((width as c_long & 0xffff) << 16) | (height as c_long & 0xffff)
+}
+
+fn unpack_point (packed: c_long) -> (i32, i32) {
+  ((packed >> 16) as i16 as i32, packed as i16 as i32)
+}
+
+fn unpack_size (packed: c_long) -> (u32, u32) {
+  (((packed >> 16) & 0xffff) as u32, (packed & 0xffff) as u32)
+}
+
+#[test]
+fn xdnd_packing_test () {
+  assert_eq!(unpack_point(pack_point(1920, 1080)), (1920, 1080));
+  assert_eq!(unpack_point(pack_point(-5, 7)), (-5, 7));
+  assert_eq!(unpack_size(pack_size(640, 480)), (640, 480));
+
+  let uris = b"# from the file manager\r\nfile:///tmp/a.txt\r\nfile:///tmp/b%20c.png\r\n";
+  assert_eq!(parse_uri_list(uris), vec!["file:///tmp/a.txt".to_string(), "file:///tmp/b%20c.png".to_string()]);
+}
+
+// Needs a bare X server, such as Xvfb, where nothing covers the drop
+// target; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn xdnd_drag_and_drop_test () {
+  use std::ptr::null;
+
+  unsafe fn pump (display: *mut Display, target: &mut DropTarget, source: &mut DragSource, drops: &mut Vec<DropEvent>, drags: &mut Vec<DragEvent>) {
+    for _ in 0..4 {
+      XSync(display, False);
+      while XPending(display) > 0 {
+        let mut event: XEvent = ::std::mem::zeroed();
+        XNextEvent(display, &mut event);
+        if let Some(drop) = target.handle_event(&event) {
+          match drop {
+            DropEvent::Position { .. } => target.reply_status(Some(DndAction::Copy), None),
+            DropEvent::Drop { .. } => target.finish(Some(DndAction::Copy)),
+            _ => {},
+          }
+          drops.push(drop);
+        }
+        if let Some(drag) = source.handle_event(&event) {
+          drags.push(drag);
+        }
+      }
+    }
+  }
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let root = XDefaultRootWindow(display);
+    let window = XCreateSimpleWindow(display, root, 500, 400, 50, 50, 0, 0, 0);
+    XMapRaised(display, window);
+    let mut target = DropTarget::new(display, window);
+
+    let mut clipboard = Clipboard::new(display);
+    let time = clipboard.server_time(Duration::from_secs(5)).unwrap();
+    let source_window = clipboard.window();
+    let mut source = DragSource::start(&mut clipboard, &[("text/uri-list", b"file:///tmp/a\r\n")], DndAction::Copy, time).unwrap();
+    let (mut drops, mut drags) = (Vec::new(), Vec::new());
+
+    source.motion(520, 420, time);
+    pump(display, &mut target, &mut source, &mut drops, &mut drags);
+    assert_eq!(source.target(), Some(window), "another client's window covers the drop spot");
+
+    let uri_list = XInternAtom(display, b"text/uri-list\0".as_ptr() as *const _, False);
+    assert_eq!(drops[0], DropEvent::Enter { source: source_window, types: vec![uri_list] });
+    assert_eq!(drops[1], DropEvent::Position { source: source_window, x: 520, y: 420, time, action: Some(DndAction::Copy) });
+    assert_eq!(drags, vec![DragEvent::Status { target: window, action: Some(DndAction::Copy) }]);
+
+    assert!(source.release(time));
+    pump(display, &mut target, &mut source, &mut drops, &mut drags);
+    assert_eq!(drags.last(), Some(&DragEvent::Finished { target: window, action: Some(DndAction::Copy) }));
+    assert_eq!(source.target(), None);
+
+    drop(source);
+    let selection = XInternAtom(display, b"XdndSelection\0".as_ptr() as *const _, False);
+    assert!(!clipboard.owns(selection));
+    drop(clipboard);
+    XDestroyWindow(display, window);
+    XCloseDisplay(display);
+  }
+}
@@ -0,0 +1,612 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::cmp::min;
+
+use libc::c_long;
+
+use ::atom::AtomCache;
+use ::error::ErrorTrap;
+use ::ewmh::client_message;
+use ::property::{
+  PropertyData,
+  get_property,
+  set_property,
+};
+use ::xlib::*;
+
+pub mod tray;
+
+
+//
+// types
+//
+
+
+// The client side of an embedding: a window that lives inside another
+// application's socket window, like a tray icon or a plug.
+pub struct EmbedClient {
+  display: *mut Display,
+  window: Window,
+  atoms: AtomCache,
+  embedder: Option<Window>,
+  version: c_long,
+  active: bool,
+  focused: bool,
+}
+
+impl EmbedClient {
+  /// Publishes _XEMBED_INFO on `window`. The embedder maps the window only
+  /// if `mapped` is set.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the client, and
+  /// `window` a window on it.
+  pub unsafe fn new (display: *mut Display, window: Window, mapped: bool) -> EmbedClient {
+    XSelectInput(display, window, event_mask(display, window) | StructureNotifyMask);
+    set_info(display, window, EmbedInfo { version: XEMBED_VERSION, mapped });
+    EmbedClient {
+      display,
+      window,
+      atoms: AtomCache::with_names(display, &ATOMS),
+      embedder: None,
+      version: XEMBED_VERSION,
+      active: false,
+      focused: false,
+    }
+  }
+
+  pub fn display (&self) -> *mut Display {
+    self.display
+  }
+
+  pub fn window (&self) -> Window {
+    self.window
+  }
+
+  pub fn embedder (&self) -> Option<Window> {
+    self.embedder
+  }
+
+  // The protocol version agreed with the embedder.
+  pub fn version (&self) -> c_long {
+    self.version
+  }
+
+  // Whether the embedder's top-level window is active.
+  pub fn is_active (&self) -> bool {
+    self.active
+  }
+
+  pub fn has_focus (&self) -> bool {
+    self.focused
+  }
+
+  /// Asks the embedder to show or hide the window.
+  ///
+  /// # Safety
+  ///
+  /// The client's display must still be open.
+  pub unsafe fn set_mapped (&mut self, mapped: bool) {
+    set_info(self.display, self.window, EmbedInfo { version: XEMBED_VERSION, mapped });
+  }
+
+  /// # Safety
+  ///
+  /// The client's display must still be open.
+  pub unsafe fn request_focus (&mut self, time: Time) {
+    self.send(time, XEmbedMessage::RequestFocus);
+  }
+
+  /// Hands focus on to the embedder's next widget after tabbing past the
+  /// client's last one.
+  ///
+  /// # Safety
+  ///
+  /// The client's display must still be open.
+  pub unsafe fn focus_next (&mut self, time: Time) {
+    self.send(time, XEmbedMessage::FocusNext);
+  }
+
+  /// # Safety
+  ///
+  /// The client's display must still be open.
+  pub unsafe fn focus_prev (&mut self, time: Time) {
+    self.send(time, XEmbedMessage::FocusPrev);
+  }
+
+  /// Sends any message to the embedder; ignored until embedded.
+  ///
+  /// # Safety
+  ///
+  /// The client's display must still be open.
+  pub unsafe fn send (&mut self, time: Time, message: XEmbedMessage) {
+    if let Some(embedder) = self.embedder {
+      let xembed = self.atoms.atom("_XEMBED");
+      send_message(self.display, embedder, xembed, time, message);
+    }
+  }
+
+  /// Decodes XEMBED messages sent to the window, tracking the embedder,
+  /// activation and focus, and notices being reparented out of the socket.
+  ///
+  /// # Safety
+  ///
+  /// The client's display must still be open, and `event` must have been read
+  /// from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<XEmbedMessage> {
+    match event.kind() {
+      ClientMessage => {
+        let message = XClientMessageEvent::from(*event);
+        if message.window != self.window || message.message_type != self.atoms.atom("_XEMBED") {
+          return None;
+        }
+        let (_, decoded) = decode_message(&message)?;
+        match decoded {
+          XEmbedMessage::EmbeddedNotify { embedder, version } => {
+            self.embedder = Some(embedder);
+            self.version = min(version, XEMBED_VERSION);
+          },
+          XEmbedMessage::WindowActivate => self.active = true,
+          XEmbedMessage::WindowDeactivate => self.active = false,
+          XEmbedMessage::FocusIn(_) => self.focused = true,
+          XEmbedMessage::FocusOut => self.focused = false,
+          _ => {},
+        }
+        Some(decoded)
+      },
+      ReparentNotify => {
+        let reparent = XReparentEvent::from(*event);
+        if reparent.window == self.window && Some(reparent.parent) != self.embedder {
+          self.embedder = None;
+          self.active = false;
+          self.focused = false;
+        }
+        None
+      },
+      _ => None,
+    }
+  }
+}
+
+// Contents of the _XEMBED_INFO property.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmbedInfo {
+  pub version: c_long,
+  pub mapped: bool,
+}
+
+// What an Embedder learns about its client.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmbedEvent {
+  Message(XEmbedMessage),
+  // The client changed its XEMBED_MAPPED flag; the window has already been
+  // mapped or unmapped accordingly.
+  Mapped(bool),
+  // The client was destroyed or left the socket.
+  Detached(Window),
+}
+
+// The socket side of an embedding: a window that hosts one client.
+pub struct Embedder {
+  display: *mut Display,
+  window: Window,
+  atoms: AtomCache,
+  client: Option<Window>,
+  version: c_long,
+}
+
+impl Embedder {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the embedder, and
+  /// `socket` a window on it.
+  pub unsafe fn new (display: *mut Display, socket: Window) -> Embedder {
+    Embedder {
+      display,
+      window: socket,
+      atoms: AtomCache::with_names(display, &ATOMS),
+      client: None,
+      version: XEMBED_VERSION,
+    }
+  }
+
+  pub fn window (&self) -> Window {
+    self.window
+  }
+
+  pub fn client (&self) -> Option<Window> {
+    self.client
+  }
+
+  /// Reparents `client` into the socket and tells it so. Clients without
+  /// _XEMBED_INFO are embedded and mapped anyway. Returns false if the
+  /// client vanished first.
+  ///
+  /// # Safety
+  ///
+  /// The embedder's display must still be open, and `client` must be a window
+  /// on it.
+  pub unsafe fn embed (&mut self, client: Window, time: Time) -> bool {
+    let trap = ErrorTrap::new(self.display);
+    let info = get_info(self.display, client);
+    XSelectInput(self.display, client, event_mask(self.display, client) | PropertyChangeMask | StructureNotifyMask);
+    XChangeSaveSet(self.display, client, SetModeInsert);
+    XReparentWindow(self.display, client, self.window, 0, 0);
+    if trap.finish().is_err() {
+      return false;
+    }
+
+    self.client = Some(client);
+    self.version = min(info.map_or(XEMBED_VERSION, |info| info.version), XEMBED_VERSION);
+    let embedder = self.window;
+    let version = self.version;
+    self.send(time, XEmbedMessage::EmbeddedNotify { embedder, version });
+    if info.map_or(true, |info| info.mapped) {
+      XMapWindow(self.display, client);
+    }
+    true
+  }
+
+  /// Hands the client back to the root window.
+  ///
+  /// # Safety
+  ///
+  /// The embedder's display must still be open.
+  pub unsafe fn release (&mut self) {
+    if let Some(client) = self.client.take() {
+      let trap = ErrorTrap::new(self.display);
+      XUnmapWindow(self.display, client);
+      XReparentWindow(self.display, client, XDefaultRootWindow(self.display), 0, 0);
+      XChangeSaveSet(self.display, client, SetModeDelete);
+      let _ = trap.finish();
+    }
+  }
+
+  /// Tells the client whether the embedder's top-level window is active.
+  ///
+  /// # Safety
+  ///
+  /// The embedder's display must still be open.
+  pub unsafe fn activate (&mut self, time: Time, active: bool) {
+    let message = if active { XEmbedMessage::WindowActivate } else { XEmbedMessage::WindowDeactivate };
+    self.send(time, message);
+  }
+
+  /// Gives the client the keyboard focus; the socket itself should hold
+  /// the X input focus and forward key events.
+  ///
+  /// # Safety
+  ///
+  /// The embedder's display must still be open.
+  pub unsafe fn focus_in (&mut self, time: Time, direction: FocusDirection) {
+    self.send(time, XEmbedMessage::FocusIn(direction));
+  }
+
+  /// # Safety
+  ///
+  /// The embedder's display must still be open.
+  pub unsafe fn focus_out (&mut self, time: Time) {
+    self.send(time, XEmbedMessage::FocusOut);
+  }
+
+  /// Sends any message to the client; ignored while nothing is embedded.
+  ///
+  /// # Safety
+  ///
+  /// The embedder's display must still be open.
+  pub unsafe fn send (&mut self, time: Time, message: XEmbedMessage) {
+    if let Some(client) = self.client {
+      let xembed = self.atoms.atom("_XEMBED");
+      send_message(self.display, client, xembed, time, message);
+    }
+  }
+
+  /// # Safety
+  ///
+  /// The embedder's display must still be open, and `event` must have been
+  /// read from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<EmbedEvent> {
+    let client = self.client?;
+    match event.kind() {
+      ClientMessage => {
+        let message = XClientMessageEvent::from(*event);
+        if message.window != self.window || message.message_type != self.atoms.atom("_XEMBED") {
+          return None;
+        }
+        decode_message(&message).map(|(_, decoded)| EmbedEvent::Message(decoded))
+      },
+      PropertyNotify => {
+        let notify = XPropertyEvent::from(*event);
+        if notify.window != client || notify.atom != self.atoms.atom("_XEMBED_INFO") {
+          return None;
+        }
+        let mapped = get_info(self.display, client).map_or(true, |info| info.mapped);
+        if mapped {
+          XMapWindow(self.display, client);
+        } else {
+          XUnmapWindow(self.display, client);
+        }
+        Some(EmbedEvent::Mapped(mapped))
+      },
+      DestroyNotify if XDestroyWindowEvent::from(*event).window == client => {
+        self.client = None;
+        Some(EmbedEvent::Detached(client))
+      },
+      ReparentNotify => {
+        let reparent = XReparentEvent::from(*event);
+        if reparent.window != client || reparent.parent == self.window {
+          return None;
+        }
+        self.client = None;
+        Some(EmbedEvent::Detached(client))
+      },
+      _ => None,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FocusDirection {
+  // Keep whatever widget had focus inside the client.
+  Current,
+  First,
+  Last,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XEmbedMessage {
+  EmbeddedNotify { embedder: Window, version: c_long },
+  WindowActivate,
+  WindowDeactivate,
+  RequestFocus,
+  FocusIn(FocusDirection),
+  FocusOut,
+  FocusNext,
+  FocusPrev,
+  ModalityOn,
+  ModalityOff,
+  RegisterAccelerator { id: c_long, keysym: KeySym, modifiers: c_long },
+  UnregisterAccelerator { id: c_long },
+  ActivateAccelerator { id: c_long, overloaded: bool },
+}
+
+impl XEmbedMessage {
+  // Decodes data.l[1..5] of an _XEMBED client message: the opcode, detail
+  // and two data words.
+  pub fn from_raw (raw: [c_long; 4]) -> Option<XEmbedMessage> {
+    let [opcode, detail, data1, data2] = raw;
+    Some(match opcode {
+      XEMBED_EMBEDDED_NOTIFY => XEmbedMessage::EmbeddedNotify { embedder: data1 as Window, version: data2 },
+      XEMBED_WINDOW_ACTIVATE => XEmbedMessage::WindowActivate,
+      XEMBED_WINDOW_DEACTIVATE => XEmbedMessage::WindowDeactivate,
+      XEMBED_REQUEST_FOCUS => XEmbedMessage::RequestFocus,
+      XEMBED_FOCUS_IN => XEmbedMessage::FocusIn(match detail {
+        XEMBED_FOCUS_FIRST => FocusDirection::First,
+        XEMBED_FOCUS_LAST => FocusDirection::Last,
+        _ => FocusDirection::Current,
+      }),
+      XEMBED_FOCUS_OUT => XEmbedMessage::FocusOut,
+      XEMBED_FOCUS_NEXT => XEmbedMessage::FocusNext,
+      XEMBED_FOCUS_PREV => XEmbedMessage::FocusPrev,
+      XEMBED_MODALITY_ON => XEmbedMessage::ModalityOn,
+      XEMBED_MODALITY_OFF => XEmbedMessage::ModalityOff,
+      XEMBED_REGISTER_ACCELERATOR => XEmbedMessage::RegisterAccelerator { id: detail, keysym: data1 as KeySym, modifiers: data2 },
+      XEMBED_UNREGISTER_ACCELERATOR => XEmbedMessage::UnregisterAccelerator { id: detail },
+      XEMBED_ACTIVATE_ACCELERATOR => XEmbedMessage::ActivateAccelerator { id: detail, overloaded: data1 & XEMBED_ACCELERATOR_OVERLOADED != 0 },
+      _ => { return None; },
+    })
+  }
+
+  pub fn to_raw (self) -> [c_long; 4] {
+    match self {
+      XEmbedMessage::EmbeddedNotify { embedder, version } => [XEMBED_EMBEDDED_NOTIFY, 0, embedder as c_long, version],
+      XEmbedMessage::WindowActivate => [XEMBED_WINDOW_ACTIVATE, 0, 0, 0],
+      XEmbedMessage::WindowDeactivate => [XEMBED_WINDOW_DEACTIVATE, 0, 0, 0],
+      XEmbedMessage::RequestFocus => [XEMBED_REQUEST_FOCUS, 0, 0, 0],
+      XEmbedMessage::FocusIn(direction) => {
+        let detail = match direction {
+          FocusDirection::Current => XEMBED_FOCUS_CURRENT,
+          FocusDirection::First => XEMBED_FOCUS_FIRST,
+          FocusDirection::Last => XEMBED_FOCUS_LAST,
+        };
+        [XEMBED_FOCUS_IN, detail, 0, 0]
+      },
+      XEmbedMessage::FocusOut => [XEMBED_FOCUS_OUT, 0, 0, 0],
+      XEmbedMessage::FocusNext => [XEMBED_FOCUS_NEXT, 0, 0, 0],
+      XEmbedMessage::FocusPrev => [XEMBED_FOCUS_PREV, 0, 0, 0],
+      XEmbedMessage::ModalityOn => [XEMBED_MODALITY_ON, 0, 0, 0],
+      XEmbedMessage::ModalityOff => [XEMBED_MODALITY_OFF, 0, 0, 0],
+      XEmbedMessage::RegisterAccelerator { id, keysym, modifiers } => [XEMBED_REGISTER_ACCELERATOR, id, keysym as c_long, modifiers],
+      XEmbedMessage::UnregisterAccelerator { id } => [XEMBED_UNREGISTER_ACCELERATOR, id, 0, 0],
+      XEmbedMessage::ActivateAccelerator { id, overloaded } => [XEMBED_ACTIVATE_ACCELERATOR, id, overloaded as c_long, 0],
+    }
+  }
+}
+
+
+//
+// functions
+//
+
+
+// Decodes an _XEMBED client message into its timestamp and message.
+pub fn decode_message (event: &XClientMessageEvent) -> Option<(Time, XEmbedMessage)> {
+  if event.format != 32 {
+    return None;
+  }
+  let data = &event.data;
+  let message = XEmbedMessage::from_raw([data.get_long(1), data.get_long(2), data.get_long(3), data.get_long(4)])?;
+  Some((data.get_long(0) as Time, message))
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn get_info (display: *mut Display, window: Window) -> Option<EmbedInfo> {
+  let property = get_property(display, window, info_atom(display), AnyPropertyType).ok()??;
+  match *property.u32s()? {
+    [version, flags, ..] => Some(EmbedInfo { version: version as c_long, mapped: flags as c_long & XEMBED_MAPPED != 0 }),
+    _ => None,
+  }
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn send_message (display: *mut Display, window: Window, xembed: Atom, time: Time, message: XEmbedMessage) {
+  let [opcode, detail, data1, data2] = message.to_raw();
+  let event = client_message(window, xembed, [time as c_long, opcode, detail, data1, data2]);
+  XSendEvent(display, window, False, NoEventMask, &event);
+}
+
+/// # Safety
+///
+/// `display` must be an open connection and `window` a window on it.
+pub unsafe fn set_info (display: *mut Display, window: Window, info: EmbedInfo) {
+  let flags = if info.mapped { XEMBED_MAPPED } else { 0 };
+  let atom = info_atom(display);
+  let data = PropertyData::Format32(vec![info.version as u32, flags as u32]);
+  set_property(display, window, atom, atom, &data, PropModeReplace);
+}
+
+
+//
+// constants
+//
+
+
+pub const XEMBED_VERSION: c_long = 0;
+
+// accelerator flags
+pub const XEMBED_ACCELERATOR_OVERLOADED: c_long = 1 << 0;
+
+// focus details
+pub const XEMBED_FOCUS_CURRENT: c_long = 0;
+pub const XEMBED_FOCUS_FIRST: c_long = 1;
+pub const XEMBED_FOCUS_LAST: c_long = 2;
+
+// info flags
+pub const XEMBED_MAPPED: c_long = 1 << 0;
+
+// messages
+pub const XEMBED_EMBEDDED_NOTIFY: c_long = 0;
+pub const XEMBED_WINDOW_ACTIVATE: c_long = 1;
+pub const XEMBED_WINDOW_DEACTIVATE: c_long = 2;
+pub const XEMBED_REQUEST_FOCUS: c_long = 3;
+pub const XEMBED_FOCUS_IN: c_long = 4;
+pub const XEMBED_FOCUS_OUT: c_long = 5;
+pub const XEMBED_FOCUS_NEXT: c_long = 6;
+pub const XEMBED_FOCUS_PREV: c_long = 7;
+pub const XEMBED_MODALITY_ON: c_long = 10;
+pub const XEMBED_MODALITY_OFF: c_long = 11;
+pub const XEMBED_REGISTER_ACCELERATOR: c_long = 12;
+pub const XEMBED_UNREGISTER_ACCELERATOR: c_long = 13;
+pub const XEMBED_ACTIVATE_ACCELERATOR: c_long = 14;
+
+const ATOMS: [&str; 2] = [
+  "_XEMBED",
+  "_XEMBED_INFO",
+];
+
+
+//
+// private functions
+//
+
+
+// Our client's current event mask on `window`, so selecting more events
+// doesn't drop the ones the application already asked for.
+unsafe fn event_mask (display: *mut Display, window: Window) -> c_long {
+  let mut attributes: XWindowAttributes = ::std::mem::zeroed();
+  if XGetWindowAttributes(display, window, &mut attributes) == 0 {
+    return NoEventMask;
+  }
+  attributes.your_event_mask
+}
+
+unsafe fn info_atom (display: *mut Display) -> Atom {
+  XInternAtom(display, b"_XEMBED_INFO\0".as_ptr() as *const _, False)
+}
+
+#[test]
+fn xembed_message_test () {
+  let messages = [
+    XEmbedMessage::EmbeddedNotify { embedder: 0x40_0001, version: 0 },
+    XEmbedMessage::FocusIn(FocusDirection::Last),
+    XEmbedMessage::FocusPrev,
+    XEmbedMessage::RegisterAccelerator { id: 3, keysym: 0x61, modifiers: 1 << 1 },
+    XEmbedMessage::ActivateAccelerator { id: 3, overloaded: true },
+  ];
+  for &message in messages.iter() {
+    assert_eq!(XEmbedMessage::from_raw(message.to_raw()), Some(message));
+  }
+  assert_eq!(XEmbedMessage::from_raw([8, 0, 0, 0]), None);
+  assert_eq!(XEmbedMessage::from_raw([XEMBED_FOCUS_IN, 9, 0, 0]), Some(XEmbedMessage::FocusIn(FocusDirection::Current)));
+}
+
+#[test]
+#[ignore]
+fn xembed_embed_test () {
+  use std::ptr::null;
+
+  unsafe fn next_event (display: *mut Display) -> XEvent {
+    let mut event: XEvent = ::std::mem::zeroed();
+    XNextEvent(display, &mut event);
+    event
+  }
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let root = XDefaultRootWindow(display);
+    let socket = XCreateSimpleWindow(display, root, 0, 0, 32, 32, 0, 0, 0);
+    let plug = XCreateSimpleWindow(display, root, 0, 0, 32, 32, 0, 0, 0);
+    let mut client = EmbedClient::new(display, plug, false);
+    assert_eq!(get_info(display, plug), Some(EmbedInfo { version: XEMBED_VERSION, mapped: false }));
+
+    let mut embedder = Embedder::new(display, socket);
+    assert!(embedder.embed(plug, CurrentTime));
+    embedder.activate(CurrentTime, true);
+    XSync(display, False);
+
+    let mut messages = Vec::new();
+    while XPending(display) > 0 {
+      if let Some(message) = client.handle_event(&next_event(display)) {
+        messages.push(message);
+      }
+    }
+    assert_eq!(messages, vec![
+      XEmbedMessage::EmbeddedNotify { embedder: socket, version: XEMBED_VERSION },
+      XEmbedMessage::WindowActivate,
+    ]);
+    assert_eq!(client.embedder(), Some(socket));
+    assert!(client.is_active());
+
+    client.set_mapped(true);
+    client.request_focus(CurrentTime);
+    XSync(display, False);
+    let mut events = Vec::new();
+    while XPending(display) > 0 {
+      if let Some(event) = embedder.handle_event(&next_event(display)) {
+        events.push(event);
+      }
+    }
+    assert_eq!(events, vec![EmbedEvent::Mapped(true), EmbedEvent::Message(XEmbedMessage::RequestFocus)]);
+
+    XDestroyWindow(display, plug);
+    XSync(display, False);
+    let mut detached = false;
+    while XPending(display) > 0 {
+      detached |= embedder.handle_event(&next_event(display)) == Some(EmbedEvent::Detached(plug));
+    }
+    assert!(detached);
+    assert_eq!(embedder.client(), None);
+
+    XDestroyWindow(display, socket);
+    XCloseDisplay(display);
+  }
+}
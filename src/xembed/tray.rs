@@ -0,0 +1,341 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::mem::zeroed;
+
+use libc::{
+  c_int,
+  c_long,
+};
+
+use ::atom::AtomCache;
+use ::error::ErrorTrap;
+use ::ewmh::client_message;
+use ::property::get_property;
+use ::xembed::{
+  EmbedClient,
+  XEmbedMessage,
+  event_mask,
+};
+use ::xlib::*;
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+  Horizontal,
+  Vertical,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrayEvent {
+  // A dock request went to this tray, after startup or a tray restart.
+  Docked(Window),
+  // The tray went away. The icon is back on the root window until the next
+  // tray announces itself.
+  Undocked,
+  Message(XEmbedMessage),
+}
+
+// A window docked in the system tray of one screen, as described by the
+// freedesktop.org System Tray Protocol. The tray is whoever owns the
+// _NET_SYSTEM_TRAY_Sn selection; the icon follows it across restarts.
+pub struct TrayIcon {
+  client: EmbedClient,
+  display: *mut Display,
+  root: Window,
+  atoms: AtomCache,
+  selection: Atom,
+  owner: Option<Window>,
+}
+
+impl TrayIcon {
+  /// Publishes _XEMBED_INFO on `window` and docks it if a tray is running.
+  /// Create the window with the tray's visual where one is advertised.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the icon, `screen`
+  /// one of its screens and `window` a window on it.
+  pub unsafe fn new (display: *mut Display, screen: c_int, window: Window) -> TrayIcon {
+    let root = XRootWindow(display, screen);
+    XSelectInput(display, root, event_mask(display, root) | StructureNotifyMask);
+
+    let mut atoms = AtomCache::with_names(display, &ATOMS);
+    let selection = atoms.atom(&format!("_NET_SYSTEM_TRAY_S{}", screen));
+    let mut icon = TrayIcon {
+      client: EmbedClient::new(display, window, true),
+      display,
+      root,
+      atoms,
+      selection,
+      owner: None,
+    };
+    icon.dock(CurrentTime);
+    icon
+  }
+
+  pub fn window (&self) -> Window {
+    self.client.window()
+  }
+
+  pub fn client (&mut self) -> &mut EmbedClient {
+    &mut self.client
+  }
+
+  // The tray's selection owner window, once a dock request was sent.
+  pub fn owner (&self) -> Option<Window> {
+    self.owner
+  }
+
+  // Whether the tray has embedded the icon.
+  pub fn is_docked (&self) -> bool {
+    self.client.embedder().is_some()
+  }
+
+  /// How the tray lays out its icons.
+  ///
+  /// # Safety
+  ///
+  /// The icon's display must still be open.
+  pub unsafe fn orientation (&mut self) -> Option<Orientation> {
+    let property = self.atoms.atom("_NET_SYSTEM_TRAY_ORIENTATION");
+    match self.owner_cardinal(property, XA_CARDINAL)? {
+      SYSTEM_TRAY_ORIENTATION_VERT => Some(Orientation::Vertical),
+      _ => Some(Orientation::Horizontal),
+    }
+  }
+
+  /// The visual icons should use, typically an ARGB one on compositing
+  /// desktops.
+  ///
+  /// # Safety
+  ///
+  /// The icon's display must still be open.
+  pub unsafe fn visual (&mut self) -> Option<VisualID> {
+    let property = self.atoms.atom("_NET_SYSTEM_TRAY_VISUAL");
+    self.owner_cardinal(property, XA_VISUALID).map(|id| id as VisualID)
+  }
+
+  /// # Safety
+  ///
+  /// The icon's display must still be open.
+  pub unsafe fn visual_info (&mut self) -> Option<XVisualInfo> {
+    let mut template: XVisualInfo = zeroed();
+    template.visualid = self.visual()?;
+    let mut count = 0;
+    let infos = XGetVisualInfo(self.display, VisualIDMask, &template, &mut count);
+    if infos.is_null() {
+      return None;
+    }
+    let info = *infos;
+    XFree(infos as *mut _);
+    Some(info)
+  }
+
+  /// Sends a dock request to the current tray. Returns false if there is no
+  /// tray or it vanished meanwhile.
+  ///
+  /// # Safety
+  ///
+  /// The icon's display must still be open.
+  pub unsafe fn dock (&mut self, time: Time) -> bool {
+    self.owner = None;
+    let owner = XGetSelectionOwner(self.display, self.selection);
+    if owner == 0 {
+      return false;
+    }
+
+    // Watching the owner tells us when the tray exits.
+    let trap = ErrorTrap::new(self.display);
+    XSelectInput(self.display, owner, event_mask(self.display, owner) | StructureNotifyMask);
+    let data = [time as c_long, SYSTEM_TRAY_REQUEST_DOCK, self.window() as c_long, 0, 0];
+    let event = client_message(owner, self.atoms.atom("_NET_SYSTEM_TRAY_OPCODE"), data);
+    XSendEvent(self.display, owner, False, NoEventMask, &event);
+    if trap.finish().is_err() {
+      return false;
+    }
+
+    self.owner = Some(owner);
+    true
+  }
+
+  /// Re-docks when a new tray announces itself with a MANAGER message on the
+  /// root window, notices the tray exiting and passes XEMBED messages on.
+  ///
+  /// # Safety
+  ///
+  /// The icon's display must still be open, and `event` must have been read
+  /// from it.
+  pub unsafe fn handle_event (&mut self, event: &XEvent) -> Option<TrayEvent> {
+    match event.kind() {
+      ClientMessage => {
+        let message = XClientMessageEvent::from(*event);
+        let manager = self.atoms.atom("MANAGER");
+        if message.window == self.root && message.message_type == manager {
+          if message.data.get_long(1) as Atom != self.selection {
+            return None;
+          }
+          let time = message.data.get_long(0) as Time;
+          return if self.dock(time) { self.owner.map(TrayEvent::Docked) } else { None };
+        }
+        self.client.handle_event(event).map(TrayEvent::Message)
+      },
+      DestroyNotify if Some(XDestroyWindowEvent::from(*event).window) == self.owner => {
+        // A replacement tray may already hold the selection.
+        if self.dock(CurrentTime) {
+          self.owner.map(TrayEvent::Docked)
+        } else {
+          Some(TrayEvent::Undocked)
+        }
+      },
+      _ => self.client.handle_event(event).map(TrayEvent::Message),
+    }
+  }
+
+  unsafe fn owner_cardinal (&self, property: Atom, kind: Atom) -> Option<u32> {
+    let property = get_property(self.display, self.owner?, property, kind).ok()??;
+    property.u32s()?.first().cloned()
+  }
+}
+
+
+//
+// constants
+//
+
+
+// opcodes
+pub const SYSTEM_TRAY_REQUEST_DOCK: c_long = 0;
+pub const SYSTEM_TRAY_BEGIN_MESSAGE: c_long = 1;
+pub const SYSTEM_TRAY_CANCEL_MESSAGE: c_long = 2;
+
+// orientations
+pub const SYSTEM_TRAY_ORIENTATION_HORZ: u32 = 0;
+pub const SYSTEM_TRAY_ORIENTATION_VERT: u32 = 1;
+
+const ATOMS: [&str; 4] = [
+  "MANAGER",
+  "_NET_SYSTEM_TRAY_OPCODE",
+  "_NET_SYSTEM_TRAY_ORIENTATION",
+  "_NET_SYSTEM_TRAY_VISUAL",
+];
+
+// Needs an X server without a system tray, such as Xvfb; run with
+// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn tray_redock_test () {
+  use std::ptr::null;
+  use std::thread::sleep;
+  use std::time::{
+    Duration,
+    Instant,
+  };
+  use ::property::set_cardinals;
+  use ::xembed::Embedder;
+
+  // Plays the tray: takes the selection, announces itself and embeds the
+  // first icon that asks.
+  unsafe fn start_tray (display: *mut Display, selection: Atom, orientation: u32) -> Window {
+    let root = XDefaultRootWindow(display);
+    let owner = XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+    let property = XInternAtom(display, b"_NET_SYSTEM_TRAY_ORIENTATION\0".as_ptr() as *const _, False);
+    set_cardinals(display, owner, property, &[orientation]);
+    XSetSelectionOwner(display, selection, owner, CurrentTime);
+
+    let manager = XInternAtom(display, b"MANAGER\0".as_ptr() as *const _, False);
+    let event = client_message(root, manager, [CurrentTime as c_long, selection as c_long, owner as c_long, 0, 0]);
+    XSendEvent(display, root, False, StructureNotifyMask, &event);
+    XSync(display, False);
+    owner
+  }
+
+  unsafe fn accept_dock (display: *mut Display, owner: Window) -> Embedder {
+    let mut event: XEvent = zeroed();
+    XNextEvent(display, &mut event);
+    let message = XClientMessageEvent::from(event);
+    assert_eq!(message.window, owner);
+    assert_eq!(message.data.get_long(1), SYSTEM_TRAY_REQUEST_DOCK);
+
+    let mut embedder = Embedder::new(display, owner);
+    assert!(embedder.embed(message.data.get_long(2) as Window, CurrentTime));
+    XSync(display, False);
+    embedder
+  }
+
+  unsafe fn drain (display: *mut Display, icon: &mut TrayIcon) -> Vec<TrayEvent> {
+    XSync(display, False);
+    let mut events = Vec::new();
+    while XPending(display) > 0 {
+      let mut event: XEvent = zeroed();
+      XNextEvent(display, &mut event);
+      events.extend(icon.handle_event(&event));
+    }
+    events
+  }
+
+  // Collects tray events until `last` arrives, which needs the server to
+  // have processed another connection's requests first.
+  unsafe fn wait_for (display: *mut Display, icon: &mut TrayIcon, last: TrayEvent) -> Vec<TrayEvent> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut events = Vec::new();
+    while events.last() != Some(&last) {
+      assert!(Instant::now() < deadline, "timed out waiting for {:?}, got {:?}", last, events);
+      if XPending(display) == 0 {
+        sleep(Duration::from_millis(10));
+        continue;
+      }
+      let mut event: XEvent = zeroed();
+      XNextEvent(display, &mut event);
+      events.extend(icon.handle_event(&event));
+    }
+    events
+  }
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+    let tray_display = XOpenDisplay(null());
+    assert!(!tray_display.is_null(), "cannot open a second connection");
+
+    let screen = XDefaultScreen(display);
+    let selection = XInternAtom(display, format!("_NET_SYSTEM_TRAY_S{}\0", screen).as_ptr() as *const _, False);
+    assert_eq!(XGetSelectionOwner(display, selection), 0, "a system tray is already running");
+
+    let owner = start_tray(tray_display, selection, SYSTEM_TRAY_ORIENTATION_VERT);
+    let window = XCreateSimpleWindow(display, XRootWindow(display, screen), 0, 0, 24, 24, 0, 0, 0);
+    let mut icon = TrayIcon::new(display, screen, window);
+    assert_eq!(icon.owner(), Some(owner));
+    assert_eq!(icon.orientation(), Some(Orientation::Vertical));
+    assert_eq!(icon.visual(), None);
+
+    accept_dock(tray_display, owner);
+    drain(display, &mut icon);
+    assert!(icon.is_docked());
+
+    // The tray restarts: its save-set hands the icon back to the root as
+    // it exits, then a new tray announces itself and the icon docks again.
+    XCloseDisplay(tray_display);
+    assert_eq!(wait_for(display, &mut icon, TrayEvent::Undocked), vec![TrayEvent::Undocked]);
+    let tray_display = XOpenDisplay(null());
+    assert!(!tray_display.is_null(), "cannot reopen the tray connection");
+    assert!(!icon.is_docked());
+
+    let owner = start_tray(tray_display, selection, SYSTEM_TRAY_ORIENTATION_HORZ);
+    assert_eq!(drain(display, &mut icon), vec![TrayEvent::Docked(owner)]);
+    assert_eq!(icon.orientation(), Some(Orientation::Horizontal));
+    accept_dock(tray_display, owner);
+    drain(display, &mut icon);
+    assert!(icon.is_docked());
+
+    XCloseDisplay(tray_display);
+    XDestroyWindow(display, window);
+    XCloseDisplay(display);
+  }
+}
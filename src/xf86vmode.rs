@@ -3,7 +3,10 @@
 // These bindings are public domain.
 
 use libc::{
+  c_char,
+  c_float,
   c_int,
+  c_uchar,
   c_uint,
   c_ushort,
 };
@@ -11,18 +14,60 @@ use libc::{
 use ::xlib::{
   Bool,
   Display,
+  Status,
 };
 
+pub mod guard;
+pub mod modes;
+
+
+//
+// functions
+//
+
 
 #[link(name="Xxf86vm")]
 extern "C" {
+  pub fn XF86VidModeAddModeLine (display: *mut Display, screen: c_int, new_modeline: *mut XF86VidModeModeInfo, after_modeline: *mut XF86VidModeModeInfo) -> Bool;
+  pub fn XF86VidModeDeleteModeLine (display: *mut Display, screen: c_int, modeline: *mut XF86VidModeModeInfo) -> Bool;
   pub fn XF86VidModeGetAllModeLines (display: *mut Display, screen: c_int, mode_count_return: *mut c_int, modes_return: *mut *mut *mut XF86VidModeModeInfo) -> Bool;
+  pub fn XF86VidModeGetDotClocks (display: *mut Display, screen: c_int, flags_return: *mut c_int, number_of_clocks_return: *mut c_int, max_dot_clock_return: *mut c_int, clocks_return: *mut *mut c_int) -> Bool;
+  pub fn XF86VidModeGetGamma (display: *mut Display, screen: c_int, gamma: *mut XF86VidModeGamma) -> Bool;
+  pub fn XF86VidModeGetGammaRamp (display: *mut Display, screen: c_int, size: c_int, red: *mut c_ushort, green: *mut c_ushort, blue: *mut c_ushort) -> Bool;
+  pub fn XF86VidModeGetGammaRampSize (display: *mut Display, screen: c_int, size: *mut c_int) -> Bool;
+  pub fn XF86VidModeGetModeLine (display: *mut Display, screen: c_int, dotclock_return: *mut c_int, modeline: *mut XF86VidModeModeLine) -> Bool;
+  pub fn XF86VidModeGetMonitor (display: *mut Display, screen: c_int, monitor: *mut XF86VidModeMonitor) -> Bool;
+  pub fn XF86VidModeGetPermissions (display: *mut Display, screen: c_int, permissions: *mut c_int) -> Bool;
+  pub fn XF86VidModeGetViewPort (display: *mut Display, screen: c_int, x_return: *mut c_int, y_return: *mut c_int) -> Bool;
+  pub fn XF86VidModeLockModeSwitch (display: *mut Display, screen: c_int, lock: c_int) -> Bool;
+  pub fn XF86VidModeModModeLine (display: *mut Display, screen: c_int, modeline: *mut XF86VidModeModeLine) -> Bool;
+  pub fn XF86VidModeQueryExtension (display: *mut Display, event_base: *mut c_int, error_base: *mut c_int) -> Bool;
+  pub fn XF86VidModeQueryVersion (display: *mut Display, major_version: *mut c_int, minor_version: *mut c_int) -> Bool;
+  pub fn XF86VidModeSetClientVersion (display: *mut Display) -> Bool;
+  pub fn XF86VidModeSetGamma (display: *mut Display, screen: c_int, gamma: *mut XF86VidModeGamma) -> Bool;
+  pub fn XF86VidModeSetGammaRamp (display: *mut Display, screen: c_int, size: c_int, red: *mut c_ushort, green: *mut c_ushort, blue: *mut c_ushort) -> Bool;
   pub fn XF86VidModeSetViewPort (display: *mut Display, screen: c_int, x: c_int, y: c_int) -> Bool;
+  pub fn XF86VidModeSwitchMode (display: *mut Display, screen: c_int, zoom: c_int) -> Bool;
   pub fn XF86VidModeSwitchToMode (display: *mut Display, screen: c_int, modeline: *const XF86VidModeModeInfo) -> Bool;
+  pub fn XF86VidModeValidateModeLine (display: *mut Display, screen: c_int, modeline: *mut XF86VidModeModeInfo) -> Status;
 }
 
 
-#[allow(raw_pointer_derive)]
+//
+// types
+//
+
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XF86VidModeGamma {
+  pub red: c_float,
+  pub green: c_float,
+  pub blue: c_float,
+}
+
+// The mode returned by XF86VidModeGetAllModeLines; the current mode as
+// returned by XF86VidModeGetModeLine lacks the dot clock (XF86VidModeModeLine).
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XF86VidModeModeInfo {
@@ -31,6 +76,26 @@ pub struct XF86VidModeModeInfo {
   pub hsyncstart: c_ushort,
   pub hsyncend: c_ushort,
   pub htotal: c_ushort,
+  pub hskew: c_ushort,
+  pub vdisplay: c_ushort,
+  pub vsyncstart: c_ushort,
+  pub vsyncend: c_ushort,
+  pub vtotal: c_ushort,
+  pub flags: c_uint,
+  pub privsize: c_int,
+  pub private: *mut i32,
+}
+
+// `private` is allocated by Xlib when `privsize` is non-zero and must be
+// freed with XFree.
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XF86VidModeModeLine {
+  pub hdisplay: c_ushort,
+  pub hsyncstart: c_ushort,
+  pub hsyncend: c_ushort,
+  pub htotal: c_ushort,
+  pub hskew: c_ushort,
   pub vdisplay: c_ushort,
   pub vsyncstart: c_ushort,
   pub vsyncend: c_ushort,
@@ -39,3 +104,68 @@ pub struct XF86VidModeModeInfo {
   pub privsize: c_int,
   pub private: *mut i32,
 }
+
+// `vendor`, `model`, `hsync` and `vsync` are allocated by Xlib and must be
+// freed with XFree.
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XF86VidModeMonitor {
+  pub vendor: *mut c_char,
+  pub model: *mut c_char,
+  pub EMPTY: c_float,
+  pub nhsync: c_uchar,
+  pub hsync: *mut XF86VidModeSyncRange,
+  pub nvsync: c_uchar,
+  pub vsync: *mut XF86VidModeSyncRange,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XF86VidModeSyncRange {
+  pub hi: c_float,
+  pub lo: c_float,
+}
+
+
+//
+// constants
+//
+
+
+// dot clock flags
+pub const CLKFLAG_PROGRAMABLE: c_int = 1;
+
+// error codes
+pub const XF86VidModeBadClock: c_int = 0;
+pub const XF86VidModeBadHTimings: c_int = 1;
+pub const XF86VidModeBadVTimings: c_int = 2;
+pub const XF86VidModeModeUnsuitable: c_int = 3;
+pub const XF86VidModeExtensionDisabled: c_int = 4;
+pub const XF86VidModeClientNotLocal: c_int = 5;
+pub const XF86VidModeZoomLocked: c_int = 6;
+pub const XF86VidModeNumberErrors: c_int = 7;
+
+// event masks
+pub const XF86VidModeNotifyMask: c_int = 0x0000_0001;
+
+// event types
+pub const XF86VidModeNotify: c_int = 0;
+pub const XF86VidModeNumberEvents: c_int = 1;
+
+// mode flags
+pub const V_PHSYNC: c_uint = 0x0001;
+pub const V_NHSYNC: c_uint = 0x0002;
+pub const V_PVSYNC: c_uint = 0x0004;
+pub const V_NVSYNC: c_uint = 0x0008;
+pub const V_INTERLACE: c_uint = 0x0010;
+pub const V_DBLSCAN: c_uint = 0x0020;
+pub const V_CSYNC: c_uint = 0x0040;
+pub const V_PCSYNC: c_uint = 0x0080;
+pub const V_NCSYNC: c_uint = 0x0100;
+
+// mode validation
+pub const MODE_OK: Status = 0;
+
+// permissions
+pub const XF86VM_READ_PERMISSION: c_int = 1;
+pub const XF86VM_WRITE_PERMISSION: c_int = 2;
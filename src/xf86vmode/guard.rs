@@ -0,0 +1,404 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::mem::zeroed;
+use std::ptr::null_mut;
+use std::sync::{
+  Mutex,
+  Once,
+};
+use std::sync::atomic::{
+  AtomicPtr,
+  AtomicUsize,
+  Ordering,
+};
+
+use libc::{
+  c_int,
+  c_ushort,
+};
+
+use ::error::{
+  ErrorTrap,
+  TrappedError,
+};
+use ::xf86vmode::*;
+use ::xf86vmode::modes::ModeList;
+use ::xlib::{
+  Display,
+  False,
+  XCloseDisplay,
+  XDisplayString,
+  XOpenDisplay,
+  XSync,
+};
+
+
+//
+// types
+//
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModeSwitchError {
+  NoExtension,
+  NoModes,
+  NoMatch,
+  SwitchFailed(Vec<TrappedError>),
+}
+
+// Switches a screen to another video mode and switches it back when dropped,
+// including while unwinding from a panic. For crashes and exits that skip
+// destructors, see `install_exit_hook`.
+pub struct ModeSwitchGuard {
+  id: usize,
+  display: *mut Display,
+  screen: c_int,
+  saved: SavedState,
+  restored: bool,
+}
+
+impl ModeSwitchGuard {
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the guard, and
+  /// `screen` one of its screens.
+  pub unsafe fn switch_to (display: *mut Display, screen: c_int, mode: &XF86VidModeModeInfo) -> Result<ModeSwitchGuard, ModeSwitchError> {
+    let (mut event_base, mut error_base) = (0, 0);
+    if XF86VidModeQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+      return Err(ModeSwitchError::NoExtension);
+    }
+
+    let saved = match SavedState::record(display, screen) {
+      Some(saved) => saved,
+      None => { return Err(ModeSwitchError::NoModes); },
+    };
+
+    let trap = ErrorTrap::new(display);
+    let switched = XF86VidModeSwitchToMode(display, screen, mode) != 0;
+    XF86VidModeSetViewPort(display, screen, 0, 0);
+    let errors = trap.errors();
+
+    if !switched || !errors.is_empty() {
+      saved.restore(display, screen);
+      drop(trap);
+      return Err(ModeSwitchError::SwitchFailed(errors));
+    }
+
+    Ok(ModeSwitchGuard {
+      id: NEXT_GUARD_ID.fetch_add(1, Ordering::SeqCst),
+      display,
+      screen,
+      saved,
+      restored: false,
+    })
+  }
+
+  /// Switches to the mode that best matches the requested resolution and
+  /// refresh rate (see modes::best_mode).
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection that outlives the guard, and
+  /// `screen` one of its screens.
+  pub unsafe fn switch_to_best (display: *mut Display, screen: c_int, width: c_ushort, height: c_ushort, refresh: Option<f64>) -> Result<ModeSwitchGuard, ModeSwitchError> {
+    let modes = match ModeList::query(display, screen) {
+      Some(modes) => modes,
+      None => { return Err(ModeSwitchError::NoExtension); },
+    };
+
+    let mode = match modes.best_match(width, height, refresh) {
+      Some(mode) => *mode,
+      None => { return Err(ModeSwitchError::NoMatch); },
+    };
+
+    ModeSwitchGuard::switch_to(display, screen, &mode)
+  }
+
+  // The mode that will be restored.
+  pub fn original_mode (&self) -> &XF86VidModeModeInfo {
+    &self.saved.mode
+  }
+
+  /// Restores the original mode now rather than on drop, and disarms the exit
+  /// hook if this guard installed it.
+  ///
+  /// # Safety
+  ///
+  /// The guard's display must still be open.
+  pub unsafe fn restore (&mut self) {
+    if self.restored {
+      return;
+    }
+
+    let trap = ErrorTrap::new(self.display);
+    self.saved.restore(self.display, self.screen);
+    drop(trap);
+    self.restored = true;
+
+    let _lock = lock_exit_hook();
+    let hook = EXIT_HOOK.load(Ordering::SeqCst);
+    if !hook.is_null() && (*hook).owner == self.id && EXIT_HOOK.compare_exchange(hook, null_mut(), Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+      let hook = Box::from_raw(hook);
+      XCloseDisplay(hook.display);
+    }
+  }
+
+  /// Arranges for the original mode to be restored if the process exits
+  /// without dropping the guard: from an atexit handler and from handlers
+  /// for SIGINT, SIGTERM, SIGHUP, SIGQUIT and SIGABRT. The signal handlers
+  /// then put back whatever action the application had installed and re-raise
+  /// the signal, so its own handler still runs. The restore runs on a
+  /// dedicated connection so it doesn't interfere with a request the main
+  /// connection may be in the middle of; from a signal handler it is still
+  /// best effort, since Xlib isn't async-signal-safe. Crashes (SIGSEGV,
+  /// SIGBUS and the like) are not covered. Only the most recently hooked
+  /// guard is covered. Returns false if the second connection couldn't be
+  /// opened.
+  ///
+  /// # Safety
+  ///
+  /// The guard's display must still be open.
+  pub unsafe fn install_exit_hook (&self) -> bool {
+    let connection = XOpenDisplay(XDisplayString(self.display));
+    if connection.is_null() {
+      return false;
+    }
+
+    let hook = Box::into_raw(Box::new(ExitHook {
+      owner: self.id,
+      display: connection,
+      screen: self.screen,
+      saved: self.saved,
+    }));
+    let _lock = lock_exit_hook();
+    let previous = EXIT_HOOK.swap(hook, Ordering::SeqCst);
+    if !previous.is_null() {
+      XCloseDisplay(Box::from_raw(previous).display);
+    }
+
+    INSTALL_HANDLERS.call_once(|| install_handlers());
+    true
+  }
+}
+
+impl Drop for ModeSwitchGuard {
+  fn drop (&mut self) {
+    unsafe {
+      self.restore();
+    }
+  }
+}
+
+#[cfg(feature="xrandr")]
+#[derive(Clone, Copy, PartialEq)]
+struct RandrState {
+  size_id: ::xrandr::SizeID,
+  rotation: ::xrandr::Rotation,
+  rate: ::libc::c_short,
+}
+
+// Without RandR support only the XF86VidMode state is restored.
+#[cfg(not(feature="xrandr"))]
+#[derive(Clone, Copy, PartialEq)]
+struct RandrState;
+
+#[derive(Clone, Copy, PartialEq)]
+struct SavedState {
+  mode: XF86VidModeModeInfo,
+  viewport: (c_int, c_int),
+  randr: Option<RandrState>,
+}
+
+impl SavedState {
+  unsafe fn record (display: *mut Display, screen: c_int) -> Option<SavedState> {
+    // The current mode is always the first one listed.
+    let mut mode = match ModeList::query(display, screen) {
+      Some(ref modes) => match modes.get(0) {
+        Some(mode) => *mode,
+        None => { return None; },
+      },
+      None => { return None; },
+    };
+
+    // The private data is freed along with the list and isn't needed to
+    // switch back.
+    mode.privsize = 0;
+    mode.private = null_mut();
+
+    let mut viewport = (0, 0);
+    XF86VidModeGetViewPort(display, screen, &mut viewport.0, &mut viewport.1);
+
+    Some(SavedState {
+      mode,
+      viewport,
+      randr: query_randr(display, screen),
+    })
+  }
+
+  unsafe fn restore (&self, display: *mut Display, screen: c_int) {
+    XF86VidModeSwitchToMode(display, screen, &self.mode);
+    XF86VidModeSetViewPort(display, screen, self.viewport.0, self.viewport.1);
+
+    if let Some(saved) = self.randr {
+      if query_randr(display, screen) != Some(saved) {
+        restore_randr(display, screen, saved);
+      }
+    }
+
+    XSync(display, False);
+  }
+}
+
+struct ExitHook {
+  // The id of the guard that installed the hook.
+  owner: usize,
+  display: *mut Display,
+  screen: c_int,
+  saved: SavedState,
+}
+
+
+
+//
+// private functions
+//
+
+
+// Signal handlers only ever swap the hook out, which is async-signal-safe;
+// everything that installs or frees one also holds HOOK_LOCK.
+static EXIT_HOOK: AtomicPtr<ExitHook> = AtomicPtr::new(null_mut());
+static HOOK_LOCK: Mutex<()> = Mutex::new(());
+static INSTALL_HANDLERS: Once = Once::new();
+static NEXT_GUARD_ID: AtomicUsize = AtomicUsize::new(1);
+
+// The application's actions for HOOKED_SIGNALS, in the same order. Written
+// once before the handlers are installed and never freed.
+static PREVIOUS_ACTIONS: AtomicPtr<::libc::sigaction> = AtomicPtr::new(null_mut());
+
+static HOOKED_SIGNALS: [c_int; 5] = [
+  ::libc::SIGINT,
+  ::libc::SIGTERM,
+  ::libc::SIGHUP,
+  ::libc::SIGQUIT,
+  ::libc::SIGABRT,
+];
+
+// Restores the mode, puts the application's action back and re-raises. The
+// signal stays blocked until this returns, so the re-raised one is then
+// delivered to that action. Nothing is closed or freed here.
+extern "C" fn handle_signal (signal: c_int) {
+  unsafe {
+    let hook = EXIT_HOOK.swap(null_mut(), Ordering::SeqCst);
+    if !hook.is_null() {
+      (*hook).saved.restore((*hook).display, (*hook).screen);
+    }
+
+    let previous = PREVIOUS_ACTIONS.load(Ordering::SeqCst);
+    if let Some(index) = HOOKED_SIGNALS.iter().position(|&hooked| hooked == signal) {
+      if !previous.is_null() {
+        ::libc::sigaction(signal, previous.add(index), null_mut());
+      }
+    }
+    ::libc::raise(signal);
+  }
+}
+
+unsafe fn install_handlers () {
+  ::libc::atexit(run_exit_hook);
+
+  let mut previous: Vec<::libc::sigaction> = HOOKED_SIGNALS.iter().map(|_| zeroed()).collect();
+  let mut action: ::libc::sigaction = zeroed();
+  action.sa_sigaction = handle_signal as extern "C" fn (c_int) as ::libc::sighandler_t;
+  ::libc::sigemptyset(&mut action.sa_mask);
+
+  // Record every previous action before any handler can run.
+  for (&signal, previous) in HOOKED_SIGNALS.iter().zip(previous.iter_mut()) {
+    ::libc::sigaction(signal, null_mut(), previous);
+  }
+  PREVIOUS_ACTIONS.store(Box::into_raw(previous.into_boxed_slice()) as *mut ::libc::sigaction, Ordering::SeqCst);
+
+  for (index, &signal) in HOOKED_SIGNALS.iter().enumerate() {
+    // Leave signals the application ignores alone.
+    if (*PREVIOUS_ACTIONS.load(Ordering::SeqCst).add(index)).sa_sigaction != ::libc::SIG_IGN {
+      ::libc::sigaction(signal, &action, null_mut());
+    }
+  }
+}
+
+fn lock_exit_hook () -> ::std::sync::MutexGuard<'static, ()> {
+  match HOOK_LOCK.lock() {
+    Ok(guard) => guard,
+    Err(poisoned) => poisoned.into_inner(),
+  }
+}
+
+#[cfg(feature="xrandr")]
+unsafe fn query_randr (display: *mut Display, screen: c_int) -> Option<RandrState> {
+  use ::xlib::XRootWindow;
+  use ::xrandr::*;
+
+  let (mut event_base, mut error_base) = (0, 0);
+  if XRRQueryExtension(display, &mut event_base, &mut error_base) == 0 {
+    return None;
+  }
+
+  let config = XRRGetScreenInfo(display, XRootWindow(display, screen));
+  if config.is_null() {
+    return None;
+  }
+
+  let mut rotation = 0;
+  let size_id = XRRConfigCurrentConfiguration(config, &mut rotation);
+  let rate = XRRConfigCurrentRate(config);
+  XRRFreeScreenConfigInfo(config);
+
+  Some(RandrState {
+    size_id,
+    rotation,
+    rate,
+  })
+}
+
+#[cfg(not(feature="xrandr"))]
+unsafe fn query_randr (_display: *mut Display, _screen: c_int) -> Option<RandrState> {
+  None
+}
+
+#[cfg(feature="xrandr")]
+unsafe fn restore_randr (display: *mut Display, screen: c_int, saved: RandrState) {
+  use ::xlib::{
+    CurrentTime,
+    XRootWindow,
+  };
+  use ::xrandr::*;
+
+  let root = XRootWindow(display, screen);
+  let config = XRRGetScreenInfo(display, root);
+  if !config.is_null() {
+    XRRSetScreenConfigAndRate(display, config, root, saved.size_id as c_int, saved.rotation, saved.rate, CurrentTime);
+    XRRFreeScreenConfigInfo(config);
+  }
+}
+
+#[cfg(not(feature="xrandr"))]
+unsafe fn restore_randr (_display: *mut Display, _screen: c_int, _saved: RandrState) {
+}
+
+// Never blocks: if the lock is held the restore is left to its holder.
+extern "C" fn run_exit_hook () {
+  let _lock = match HOOK_LOCK.try_lock() {
+    Ok(lock) => lock,
+    Err(::std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+    Err(::std::sync::TryLockError::WouldBlock) => { return; },
+  };
+  let hook = EXIT_HOOK.swap(null_mut(), Ordering::SeqCst);
+  if !hook.is_null() {
+    unsafe {
+      let hook = Box::from_raw(hook);
+      hook.saved.restore(hook.display, hook.screen);
+      XCloseDisplay(hook.display);
+    }
+  }
+}
@@ -0,0 +1,245 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use std::cmp::Ordering;
+use std::ptr::null_mut;
+use std::slice::from_raw_parts;
+
+use libc::{
+  c_int,
+  c_ushort,
+};
+
+use ::xf86vmode::*;
+use ::xlib::{
+  Display,
+  XFree,
+};
+
+
+//
+// types
+//
+
+
+// The modes of a screen as returned by XF86VidModeGetAllModeLines. The
+// current mode comes first. The array is freed with XFree on drop.
+pub struct ModeList {
+  modes: *mut *mut XF86VidModeModeInfo,
+  count: usize,
+}
+
+impl ModeList {
+  /// None if the extension is missing or the request fails.
+  ///
+  /// # Safety
+  ///
+  /// `display` must be an open connection and `screen` one of its screens.
+  pub unsafe fn query (display: *mut Display, screen: c_int) -> Option<ModeList> {
+    let mut count = 0;
+    let mut modes = null_mut();
+    if XF86VidModeGetAllModeLines(display, screen, &mut count, &mut modes) == 0 || modes.is_null() {
+      return None;
+    }
+
+    Some(ModeList {
+      modes,
+      count: count.max(0) as usize,
+    })
+  }
+
+  pub fn len (&self) -> usize {
+    self.count
+  }
+
+  pub fn is_empty (&self) -> bool {
+    self.count == 0
+  }
+
+  pub fn get (&self, index: usize) -> Option<&XF86VidModeModeInfo> {
+    self.pointers().get(index).map(|&mode| unsafe { &*mode })
+  }
+
+  // See `best_mode`.
+  pub fn best_match (&self, width: c_ushort, height: c_ushort, refresh: Option<f64>) -> Option<&XF86VidModeModeInfo> {
+    best_mode(self.iter(), width, height, refresh)
+  }
+
+  pub fn iter (&self) -> ModeIter<'_> {
+    ModeIter {
+      inner: self.pointers().iter(),
+    }
+  }
+
+  fn pointers (&self) -> &[*mut XF86VidModeModeInfo] {
+    unsafe { from_raw_parts(self.modes, self.count) }
+  }
+}
+
+impl Drop for ModeList {
+  fn drop (&mut self) {
+    unsafe {
+      XFree(self.modes as *mut _);
+    }
+  }
+}
+
+impl<'a> IntoIterator for &'a ModeList {
+  type Item = &'a XF86VidModeModeInfo;
+  type IntoIter = ModeIter<'a>;
+
+  fn into_iter (self) -> ModeIter<'a> {
+    self.iter()
+  }
+}
+
+pub struct ModeIter<'a> {
+  inner: ::std::slice::Iter<'a, *mut XF86VidModeModeInfo>,
+}
+
+impl<'a> Iterator for ModeIter<'a> {
+  type Item = &'a XF86VidModeModeInfo;
+
+  fn next (&mut self) -> Option<&'a XF86VidModeModeInfo> {
+    self.inner.next().map(|&mode| unsafe { &*mode })
+  }
+
+  fn size_hint (&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl<'a> ExactSizeIterator for ModeIter<'a> {}
+
+
+//
+// functions
+//
+
+
+// The vertical refresh rate of `mode` in Hz, or 0 if its timings are empty.
+pub fn refresh_rate (mode: &XF86VidModeModeInfo) -> f64 {
+  if mode.htotal == 0 || mode.vtotal == 0 {
+    return 0.0;
+  }
+
+  // dotclock is in kHz.
+  let mut rate = mode.dotclock as f64 * 1000.0 / (mode.htotal as f64 * mode.vtotal as f64);
+  if mode.flags & V_INTERLACE != 0 {
+    rate *= 2.0;
+  }
+  if mode.flags & V_DBLSCAN != 0 {
+    rate /= 2.0;
+  }
+  rate
+}
+
+// Picks the mode that best fits a requested resolution and refresh rate:
+// an exact resolution if there is one, otherwise the smallest mode at least
+// as large in both dimensions, otherwise the largest mode. Among modes of
+// the chosen resolution the refresh rate closest to `refresh` wins, or the
+// highest one if `refresh` is None.
+pub fn best_mode<'a, I> (modes: I, width: c_ushort, height: c_ushort, refresh: Option<f64>) -> Option<&'a XF86VidModeModeInfo>
+  where I: IntoIterator<Item=&'a XF86VidModeModeInfo>
+{
+  let modes: Vec<&XF86VidModeModeInfo> = modes.into_iter().collect();
+  let area = |mode: &XF86VidModeModeInfo| mode.hdisplay as u32 * mode.vdisplay as u32;
+
+  let size = if modes.iter().any(|m| m.hdisplay == width && m.vdisplay == height) {
+    (width, height)
+  } else if let Some(m) = modes.iter().filter(|m| m.hdisplay >= width && m.vdisplay >= height).min_by_key(|m| area(m)) {
+    (m.hdisplay, m.vdisplay)
+  } else if let Some(m) = modes.iter().max_by_key(|m| area(m)) {
+    (m.hdisplay, m.vdisplay)
+  } else {
+    return None;
+  };
+
+  let candidates = modes.into_iter().filter(|m| (m.hdisplay, m.vdisplay) == size);
+  match refresh {
+    Some(refresh) => candidates.min_by(|a, b| {
+      let da = (refresh_rate(a) - refresh).abs();
+      let db = (refresh_rate(b) - refresh).abs();
+      da.partial_cmp(&db).unwrap_or(Ordering::Equal)
+    }),
+    None => candidates.max_by(|a, b| {
+      refresh_rate(a).partial_cmp(&refresh_rate(b)).unwrap_or(Ordering::Equal)
+    }),
+  }
+}
+
+#[test]
+fn xf86vmode_best_mode_test () {
+  fn mode (hdisplay: c_ushort, vdisplay: c_ushort, rate: u32) -> XF86VidModeModeInfo {
+    let (htotal, vtotal) = (hdisplay + 100, vdisplay + 50);
+    XF86VidModeModeInfo {
+      dotclock: htotal as u32 * vtotal as u32 * rate / 1000,
+      hdisplay,
+      hsyncstart: hdisplay + 10,
+      hsyncend: hdisplay + 20,
+      htotal,
+      hskew: 0,
+      vdisplay,
+      vsyncstart: vdisplay + 5,
+      vsyncend: vdisplay + 10,
+      vtotal,
+      flags: 0,
+      privsize: 0,
+      private: null_mut(),
+    }
+  }
+
+  let modes = [
+    mode(1920, 1080, 60),
+    mode(1920, 1080, 144),
+    mode(1280, 1024, 75),
+    mode(1024, 768, 60),
+    mode(640, 480, 60),
+  ];
+
+  let best = best_mode(modes.iter(), 1920, 1080, Some(60.0)).unwrap();
+  assert_eq!((best.hdisplay, best.vdisplay), (1920, 1080));
+  assert!((refresh_rate(best) - 60.0).abs() < 0.5);
+
+  let best = best_mode(modes.iter(), 1920, 1080, None).unwrap();
+  assert!((refresh_rate(best) - 144.0).abs() < 0.5);
+
+  let best = best_mode(modes.iter(), 800, 600, None).unwrap();
+  assert_eq!((best.hdisplay, best.vdisplay), (1024, 768));
+
+  let best = best_mode(modes.iter(), 2560, 1440, Some(75.0)).unwrap();
+  assert_eq!((best.hdisplay, best.vdisplay), (1920, 1080));
+
+  assert!(best_mode([].iter(), 640, 480, None).is_none());
+}
+
+// Needs a server with XFree86-VidModeExtension at $DISPLAY; run with
+// `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn xf86vmode_mode_list_test () {
+  use std::ptr::null;
+  use ::xlib::{
+    XCloseDisplay,
+    XDefaultScreen,
+    XOpenDisplay,
+  };
+
+  unsafe {
+    let display = XOpenDisplay(null());
+    assert!(!display.is_null(), "cannot open display");
+
+    let (mut event_base, mut error_base) = (0, 0);
+    assert!(XF86VidModeQueryExtension(display, &mut event_base, &mut error_base) != 0, "no XFree86-VidModeExtension");
+    let (mut major, mut minor) = (0, 0);
+    assert!(XF86VidModeQueryVersion(display, &mut major, &mut minor) != 0);
+
+    let modes = ModeList::query(display, XDefaultScreen(display)).unwrap();
+    assert!(!modes.is_empty());
+    assert_eq!(modes.iter().count(), modes.len());
+    assert!(modes.iter().all(|mode| mode.hdisplay > 0 && mode.vdisplay > 0));
+
+    XCloseDisplay(display);
+  }
+}
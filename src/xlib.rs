@@ -42,6 +42,9 @@ extern "C" {
   pub fn XAddToSaveSet (display: *mut Display, window: Window);
   pub fn XAllocClassHint () -> *mut XClassHint;
   pub fn XAllocColor (display: *mut Display, colormap: Colormap, color: *mut XColor) -> Status;
+  pub fn XAllocSizeHints () -> *mut XSizeHints;
+  pub fn XAllocWMHints () -> *mut XWMHints;
+  pub fn XAllowEvents (display: *mut Display, event_mode: c_int, time: Time) -> c_int;
   pub fn XAllPlanes () -> c_ulong;
   pub fn XBitmapBitOrder (display: *mut Display) -> c_int;
   pub fn XBitmapPad (display: *mut Display) -> c_int;
@@ -49,13 +52,29 @@ extern "C" {
   pub fn XBlackPixel (display: *mut Display, screen_num: c_int) -> c_ulong;
   pub fn XBlackPixelOfScreen (screen: *mut Screen) -> c_ulong;
   pub fn XCellsOfScreen (screen: *mut Screen) -> c_int;
+  pub fn XChangeProperty (display: *mut Display, window: Window, property: Atom, kind: Atom, format: c_int, mode: c_int, data: *const c_uchar, nelements: c_int) -> c_int;
+  pub fn XChangeSaveSet (display: *mut Display, window: Window, change_mode: c_int) -> c_int;
+  pub fn XChangeWindowAttributes (display: *mut Display, window: Window, valuemask: c_ulong, attributes: *const XSetWindowAttributes) -> c_int;
+  pub fn XCheckIfEvent (display: *mut Display, event_return: *mut XEvent, predicate: EventPredicate, arg: XPointer) -> Bool;
   pub fn XCheckMaskEvent (display: *mut Display, event_mask: c_long, event_return: *mut XEvent) -> Bool;
   pub fn XCheckTypedEvent (display: *mut Display, event_type: c_int, event_return: *mut XEvent) -> Bool;
+  pub fn XCheckTypedWindowEvent (display: *mut Display, window: Window, event_type: c_int, event_return: *mut XEvent) -> Bool;
+  pub fn XCirculateSubwindows (display: *mut Display, window: Window, direction: c_int) -> c_int;
+  pub fn XCirculateSubwindowsDown (display: *mut Display, window: Window) -> c_int;
+  pub fn XCirculateSubwindowsUp (display: *mut Display, window: Window) -> c_int;
   pub fn XCloseDisplay (display: *mut Display);
   pub fn XCloseIM (im: XIM) -> Status;
+  pub fn XConfigureWindow (display: *mut Display, window: Window, value_mask: c_uint, values: *const XWindowChanges) -> c_int;
+  pub fn XConnectionNumber (display: *mut Display) -> c_int;
+  pub fn XConvertSelection (display: *mut Display, selection: Atom, target: Atom, property: Atom, requestor: Window, time: Time) -> c_int;
+  pub fn XCreateBitmapFromData (display: *mut Display, drawable: Drawable, data: *const c_char, width: c_uint, height: c_uint) -> Pixmap;
   pub fn XCreateColormap (display: *mut Display, window: Window, visual: *mut Visual, alloc: c_int) -> Colormap;
+  pub fn XCreateFontCursor (display: *mut Display, shape: c_uint) -> Cursor;
   pub fn XCreateGC (display: *mut Display, drawable: Drawable, valuemask: c_ulong, values: *const XGCValues) -> GC;
+  pub fn XCreateGlyphCursor (display: *mut Display, source_font: Font, mask_font: Font, source_char: c_uint, mask_char: c_uint, foreground_color: *const XColor, background_color: *const XColor) -> Cursor;
   pub fn XCreateIC (im: XIM, ...) -> XIC;
+  pub fn XCreatePixmap (display: *mut Display, drawable: Drawable, width: c_uint, height: c_uint, depth: c_uint) -> Pixmap;
+  pub fn XCreatePixmapCursor (display: *mut Display, source: Pixmap, mask: Pixmap, foreground_color: *mut XColor, background_color: *mut XColor, x: c_uint, y: c_uint) -> Cursor;
   pub fn XCreateSimpleWindow (display: *mut Display, parent: Window, x: c_int, y: c_int, width: c_uint, height: c_uint, border_width: c_uint, border: c_ulong, background: c_ulong) -> Window;
   pub fn XCreateWindow (display: *mut Display, parent: Window, x: c_int, y: c_int, width: c_uint, height: c_uint, border_width: c_uint, depth: c_int, class: c_int, visual: *mut Visual, attr_mask: c_ulong, attr: *const XSetWindowAttributes) -> Window;
   pub fn XDefaultColormap (display: *mut Display, screen_num: c_int) -> Colormap;
@@ -63,67 +82,123 @@ extern "C" {
   pub fn XDefaultScreen (display: *mut Display) -> c_int;
   pub fn XDefaultVisual (display: *mut Display, screen_num: c_int) -> *mut Visual;
   pub fn XDefineCursor (display: *mut Display, window: Window, cursor: Cursor);
+  pub fn XDeleteProperty (display: *mut Display, window: Window, property: Atom) -> c_int;
   pub fn XDestroyIC (ic: XIC);
   pub fn XDestroyWindow (display: *mut Display, window: Window);
   pub fn XDisableAccessControl (display: *mut Display) -> c_int;
   pub fn XDisplayOfIM (im: XIM) -> *mut Display;
+  pub fn XDisplayString (display: *mut Display) -> *mut c_char;
   pub fn XDrawRectangle (display: *mut Display, drawable: Drawable, gc: GC, x: c_int, y: c_int, width: c_uint, height: c_uint);
   pub fn XEnableAccessControl (display: *mut Display) -> c_int;
+  pub fn XExtendedMaxRequestSize (display: *mut Display) -> c_long;
   pub fn XFetchName (display: *mut Display, window: Window, name: *mut *mut c_char) -> Status;
   pub fn XFillRectangle (display: *mut Display, drawable: Drawable, gc: GC, x: c_int, y: c_int, width: c_uint, height: c_uint);
   pub fn XFilterEvent (event: *mut XEvent, window: Window) -> Bool;
   pub fn XFlush (display: *mut Display);
   pub fn XFree (mem: *mut c_void);
   pub fn XFreeColormap (display: *mut Display, colormap: Colormap);
+  pub fn XFreeCursor (display: *mut Display, cursor: Cursor) -> c_int;
   pub fn XFreeGC (display: *mut Display, gc: GC);
+  pub fn XFreeModifiermap (modmap: *mut XModifierKeymap) -> c_int;
+  pub fn XFreePixmap (display: *mut Display, pixmap: Pixmap) -> c_int;
+  pub fn XGetAtomName (display: *mut Display, atom: Atom) -> *mut c_char;
+  pub fn XGetAtomNames (display: *mut Display, atoms: *mut Atom, count: c_int, names_return: *mut *mut c_char) -> Status;
   pub fn XGetClassHint (display: *mut Display, window: Window, class_hints_return: *mut XClassHint) -> Status;
   pub fn XGetGeometry (display: *mut Display, drawable: Drawable, root: *mut Window, x: *mut c_int, y: *mut c_int, width: *mut c_uint, height: *mut c_uint, border_width: *mut c_uint, depth: *mut c_uint) -> Status;
   pub fn XGetIMValues (im: XIM, ...) -> *const c_char;
+  pub fn XGetInputFocus (display: *mut Display, focus_return: *mut Window, revert_to_return: *mut c_int) -> c_int;
   pub fn XGetKeyboardMapping (display: *mut Display, keycode: *const KeyCode, keycode_count: c_int, keysyms_returned_per_keycode: *mut c_int)-> *mut KeySym;
+  pub fn XGetModifierMapping (display: *mut Display) -> *mut XModifierKeymap;
+  pub fn XGetSelectionOwner (display: *mut Display, selection: Atom) -> Window;
+  pub fn XGetTransientForHint (display: *mut Display, window: Window, prop_window_return: *mut Window) -> Status;
   pub fn XGetVisualInfo (display: *mut Display, mask: c_long, template: *const XVisualInfo, nitems: *mut c_int) -> *mut XVisualInfo;
   pub fn XGetWindowAttributes (display: *mut Display, window: Window, attr: *mut XWindowAttributes) -> Status;
-  pub fn XGetWindowProperty (display: *mut Display, window: Window, property: Atom, long_offset: c_long, long_length: c_long, delete: Bool, requestedkind: Atom, outkind: *mut Atom, out_format: *mut c_int, out_length: *mut c_ulong, out_remaining: *mut c_ulong, out_data: *mut *mut c_char) -> c_int;
+  pub fn XGetWindowProperty (display: *mut Display, window: Window, property: Atom, long_offset: c_long, long_length: c_long, delete: Bool, requestedkind: Atom, outkind: *mut Atom, out_format: *mut c_int, out_length: *mut c_ulong, out_remaining: *mut c_ulong, out_data: *mut *mut c_uchar) -> c_int;
+  pub fn XGetWMHints (display: *mut Display, window: Window) -> *mut XWMHints;
+  pub fn XGetWMIconName (display: *mut Display, window: Window, text_prop_return: *mut XTextProperty) -> Status;
   pub fn XGetWMName (display: *mut Display, window: Window, name: *mut XTextProperty) -> Status;
-  pub fn XGrabKey (display: *mut Display, keycode: KeyCode, modifiers: c_uint, window: Window, owner_events: c_int, pointer_mode: c_int, keyboard_mode: c_int);
+  pub fn XGetWMNormalHints (display: *mut Display, window: Window, hints_return: *mut XSizeHints, supplied_return: *mut c_long) -> Status;
+  pub fn XGetWMProtocols (display: *mut Display, window: Window, protocols_return: *mut *mut Atom, count_return: *mut c_int) -> Status;
+  pub fn XGrabButton (display: *mut Display, button: c_uint, modifiers: c_uint, grab_window: Window, owner_events: Bool, event_mask: c_uint, pointer_mode: c_int, keyboard_mode: c_int, confine_to: Window, cursor: Cursor) -> c_int;
+  pub fn XGrabKey (display: *mut Display, keycode: c_int, modifiers: c_uint, grab_window: Window, owner_events: Bool, pointer_mode: c_int, keyboard_mode: c_int) -> c_int;
+  pub fn XGrabKeyboard (display: *mut Display, grab_window: Window, owner_events: Bool, pointer_mode: c_int, keyboard_mode: c_int, time: Time) -> c_int;
   pub fn XGrabPointer (display: *mut Display, grab_window: Window, owner_events: Bool, event_mask: c_uint, pointer_mode: c_int, keyboard_mode: c_int, confine_to: Window, cursor: Cursor, time: Time) -> c_int;
+  pub fn XGrabServer (display: *mut Display) -> c_int;
   pub fn XHeightOfScreen (screen: *mut Screen) -> c_int;
+  pub fn XIfEvent (display: *mut Display, event_return: *mut XEvent, predicate: EventPredicate, arg: XPointer) -> c_int;
   pub fn XIMOfIC (ic: XIC) -> XIM;
   pub fn XInitThreads () -> Status;
   pub fn XInternAtom (display: *mut Display, name: *const c_char, only_if_exists: Bool) -> Atom;
+  pub fn XInternAtoms (display: *mut Display, names: *mut *mut c_char, count: c_int, only_if_exists: Bool, atoms_return: *mut Atom) -> Status;
   pub fn XkbSetDetectableAutoRepeat (display: *mut Display, detectable: Bool, supported_return: *mut Bool) -> Bool;
   pub fn XKeycodeToKeysym (display: *mut Display, keycode: KeyCode, index: c_int) -> KeySym;
+  pub fn XKeysymToKeycode (display: *mut Display, keysym: KeySym) -> KeyCode;
+  pub fn XKillClient (display: *mut Display, resource: XID) -> c_int;
   pub fn XListHosts (display: *mut Display, nhosts_return: *mut c_int, state_return: Bool) -> *mut XHostAddress;
+  pub fn XListProperties (display: *mut Display, window: Window, num_prop_return: *mut c_int) -> *mut Atom;
   pub fn XLocaleOfIM (im: XIM) -> *const c_char;
   pub fn XLookupKeysym (key_event: *const XKeyEvent, index: c_int) -> KeySym;
+  pub fn XLowerWindow (display: *mut Display, window: Window) -> c_int;
   pub fn XMapRaised (display: *mut Display, window: Window);
   pub fn XMapWindow (display: *mut Display, window: Window);
+  pub fn XMaxRequestSize (display: *mut Display) -> c_long;
+  pub fn XMoveResizeWindow (display: *mut Display, window: Window, x: c_int, y: c_int, width: c_uint, height: c_uint) -> c_int;
   pub fn XMoveWindow (display: *mut Display, window: Window, x: c_int, y: c_int);
   pub fn XNextEvent (display: *mut Display, event: *mut XEvent);
   pub fn XOpenDisplay (name: *const c_char) -> *mut Display;
   pub fn XOpenIM (display: *mut Display, db: XrmDatabase, res_name: *const c_char, res_class: *const c_char) -> XIM;
   pub fn XPeekEvent (display: *mut Display, event_return: *mut XEvent);
+  pub fn XPeekIfEvent (display: *mut Display, event_return: *mut XEvent, predicate: EventPredicate, arg: XPointer) -> c_int;
   pub fn XPending (display: *mut Display) -> c_int;
+  pub fn XQueryBestCursor (display: *mut Display, drawable: Drawable, width: c_uint, height: c_uint, width_return: *mut c_uint, height_return: *mut c_uint) -> Status;
+  pub fn XQueryPointer (display: *mut Display, window: Window, root_return: *mut Window, child_return: *mut Window, root_x_return: *mut c_int, root_y_return: *mut c_int, win_x_return: *mut c_int, win_y_return: *mut c_int, mask_return: *mut c_uint) -> Bool;
   pub fn XQueryTree (display: *mut Display, window: Window, root: *mut Window, parent: *mut Window, children: *mut *mut Window, nchildren: *mut c_uint) -> Status;
+  pub fn XRaiseWindow (display: *mut Display, window: Window) -> c_int;
+  pub fn XRecolorCursor (display: *mut Display, cursor: Cursor, foreground_color: *mut XColor, background_color: *mut XColor) -> c_int;
   pub fn XRefreshKeyboardMapping (event_map: *mut XMappingEvent);
+  pub fn XRemoveFromSaveSet (display: *mut Display, window: Window) -> c_int;
   pub fn XRemoveHost (display: *mut Display, host: *const XHostAddress) -> c_int;
   pub fn XRemoveHosts (display: *mut Display, hosts: *const XHostAddress, num_hosts: c_int) -> c_int;
+  pub fn XReparentWindow (display: *mut Display, window: Window, parent: Window, x: c_int, y: c_int) -> c_int;
   pub fn XResizeWindow (display: *mut Display, window: Window, width: c_uint, height: c_uint);
+  pub fn XRestackWindows (display: *mut Display, windows: *mut Window, nwindows: c_int) -> c_int;
   pub fn XRootWindow (display: *mut Display, screen_num: c_int) -> Window;
+  pub fn XRotateWindowProperties (display: *mut Display, window: Window, properties: *mut Atom, num_prop: c_int, npositions: c_int) -> c_int;
   pub fn XScreenCount (display: *mut Display) -> c_int;
   pub fn XScreenOfDisplay (display: *mut Display, screen_num: c_int) -> *mut Screen;
+  pub fn XSelectInput (display: *mut Display, window: Window, event_mask: c_long) -> c_int;
   pub fn XSendEvent (display: *mut Display, window: Window, propagate: Bool, event_mask: c_long, event: *const XEvent) -> Status;
   pub fn XSetAccessControl (display: *mut Display, mode: c_int) -> c_int;
   pub fn XSetClassHint (display: *mut Display, window: Window, class_hints: *const XClassHint);
   pub fn XSetClipRectangles (display: *mut Display, gc: GC, clip_x_origin: c_int, clip_y_origin: c_int, rectangles: *const XRectangle, n: c_int, ordering: c_int);
+  pub fn XSetCloseDownMode (display: *mut Display, close_mode: c_int) -> c_int;
+  pub fn XSetCommand (display: *mut Display, window: Window, argv: *mut *mut c_char, argc: c_int) -> c_int;
   pub fn XSetErrorHandler (handler: ErrorHandler) -> ErrorHandler;
   pub fn XSetForeground (display: *mut Display, gc: GC, fg: c_ulong);
   pub fn XSetICFocus (ic: XIC);
   pub fn XSetIMValues (im: XIM, ...) -> *const c_char;
+  pub fn XSetInputFocus (display: *mut Display, focus: Window, revert_to: c_int, time: Time) -> c_int;
+  pub fn XSetSelectionOwner (display: *mut Display, selection: Atom, owner: Window, time: Time) -> c_int;
+  pub fn XSetTransientForHint (display: *mut Display, window: Window, prop_window: Window) -> c_int;
+  pub fn XSetWindowBorder (display: *mut Display, window: Window, border_pixel: c_ulong) -> c_int;
+  pub fn XSetWindowBorderPixmap (display: *mut Display, window: Window, border_pixmap: Pixmap) -> c_int;
+  pub fn XSetWindowBorderWidth (display: *mut Display, window: Window, width: c_uint) -> c_int;
+  pub fn XSetWMHints (display: *mut Display, window: Window, wm_hints: *const XWMHints) -> c_int;
+  pub fn XSetWMIconName (display: *mut Display, window: Window, text_prop: *const XTextProperty);
+  pub fn XSetWMName (display: *mut Display, window: Window, text_prop: *const XTextProperty);
   pub fn XSetWMNormalHints (display: *mut Display, window: Window, hints: *const XSizeHints);
+  pub fn XSetWMProperties (display: *mut Display, window: Window, window_name: *const XTextProperty, icon_name: *const XTextProperty, argv: *mut *mut c_char, argc: c_int, normal_hints: *const XSizeHints, wm_hints: *const XWMHints, class_hints: *const XClassHint);
   pub fn XSetWMProtocols (display: *mut Display, window: Window, protocols: *const Atom, count: c_int) -> Status;
   pub fn XStoreName (display: *mut Display, window: Window, name: *const c_char);
   pub fn XStringToKeysym (display: *mut Display, window: Window, string: *const c_char) -> *mut KeySym;
+  pub fn XSync (display: *mut Display, discard: Bool) -> c_int;
+  pub fn XTranslateCoordinates (display: *mut Display, src_window: Window, dest_window: Window, src_x: c_int, src_y: c_int, dest_x_return: *mut c_int, dest_y_return: *mut c_int, child_return: *mut Window) -> Bool;
+  pub fn XUndefineCursor (display: *mut Display, window: Window) -> c_int;
+  pub fn XUngrabButton (display: *mut Display, button: c_uint, modifiers: c_uint, grab_window: Window) -> c_int;
+  pub fn XUngrabKey (display: *mut Display, keycode: c_int, modifiers: c_uint, grab_window: Window) -> c_int;
+  pub fn XUngrabKeyboard (display: *mut Display, time: Time) -> c_int;
   pub fn XUngrabPointer (display: *mut Display, time: Time);
+  pub fn XUngrabServer (display: *mut Display) -> c_int;
   pub fn XUnmapWindow (display: *mut Display, window: Window);
   pub fn XUnsetICFocus (ic: XIC);
   pub fn Xutf8LookupString (ic: XIC, event: *const XKeyPressedEvent, buffer_return: *mut c_char, buffer_size: c_int, keysym_return: *mut KeySym, status_return: *mut Status) -> c_int;
@@ -162,32 +237,33 @@ pub type XPointer = *mut c_void;
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct Display;
+pub struct Display { _private: [u8; 0] }
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct Screen;
+pub struct Screen { _private: [u8; 0] }
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct Visual;
+pub struct Visual { _private: [u8; 0] }
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct XIC_Rec;
+pub struct XIC_Rec { _private: [u8; 0] }
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct XIM_Rec;
+pub struct XIM_Rec { _private: [u8; 0] }
 
 #[allow(missing_copy_implementations)]
 #[repr(C)]
-pub struct _XrmHashBucketRec;
+pub struct _XrmHashBucketRec { _private: [u8; 0] }
 
 pub type AddPixelFn = Option<unsafe extern "C" fn () -> c_int>;
 pub type CreateImageFn = Option<unsafe extern "C" fn () -> *mut XImage>;
 pub type DestroyImageFn = Option<unsafe extern "C" fn () -> c_int>;
 pub type ErrorHandler = Option<unsafe extern "C" fn (*mut Display, *const XErrorEvent) -> c_int>;
+pub type EventPredicate = Option<unsafe extern "C" fn (*mut Display, *mut XEvent, XPointer) -> Bool>;
 pub type GetPixelFn = Option<unsafe extern "C" fn () -> c_ulong>;
 pub type PutPixelFn = Option<unsafe extern "C" fn () -> c_int>;
 pub type SubImageFn = Option<unsafe extern "C" fn () -> *mut XImage>;
@@ -429,7 +505,6 @@ fn xevent_size_test () {
   assert!(size_of::<XEvent>() >= size_of::<XVisibilityEvent>());
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XAnyEvent {
@@ -446,7 +521,6 @@ impl From<XEvent> for XAnyEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XButtonEvent {
@@ -475,7 +549,6 @@ impl From<XEvent> for XButtonEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XCirculateEvent {
@@ -494,7 +567,6 @@ impl From<XEvent> for XCirculateEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XCirculateRequestEvent {
@@ -513,7 +585,6 @@ impl From<XEvent> for XCirculateRequestEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XClientMessageEvent {
@@ -533,7 +604,6 @@ impl From<XEvent> for XClientMessageEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XColormapEvent {
@@ -553,7 +623,6 @@ impl From<XEvent> for XColormapEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XConfigureEvent {
@@ -578,7 +647,6 @@ impl From<XEvent> for XConfigureEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XConfigureRequestEvent {
@@ -604,7 +672,6 @@ impl From<XEvent> for XConfigureRequestEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XCreateWindowEvent {
@@ -628,7 +695,6 @@ impl From<XEvent> for XCreateWindowEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XCrossingEvent {
@@ -659,7 +725,6 @@ impl From<XEvent> for XCrossingEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XDestroyWindowEvent {
@@ -677,7 +742,6 @@ impl From<XEvent> for XDestroyWindowEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XErrorEvent {
@@ -696,7 +760,6 @@ impl From<XEvent> for XErrorEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XExposeEvent {
@@ -718,7 +781,6 @@ impl From<XEvent> for XExposeEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XFocusChangeEvent {
@@ -739,7 +801,6 @@ impl From<XEvent> for XFocusChangeEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XGraphicsExposeEvent {
@@ -763,7 +824,6 @@ impl From<XEvent> for XGraphicsExposeEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XGravityEvent {
@@ -783,7 +843,6 @@ impl From<XEvent> for XGravityEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XKeyEvent {
@@ -812,7 +871,6 @@ impl From<XEvent> for XKeyEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XKeymapEvent {
@@ -830,7 +888,6 @@ impl From<XEvent> for XKeymapEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XMapEvent {
@@ -849,7 +906,6 @@ impl From<XEvent> for XMapEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XMappingEvent {
@@ -869,7 +925,6 @@ impl From<XEvent> for XMappingEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XMapRequestEvent {
@@ -887,7 +942,6 @@ impl From<XEvent> for XMapRequestEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XMotionEvent {
@@ -915,7 +969,6 @@ impl From<XEvent> for XMotionEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XNoExposeEvent {
@@ -934,7 +987,6 @@ impl From<XEvent> for XNoExposeEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XPropertyEvent {
@@ -954,7 +1006,6 @@ impl From<XEvent> for XPropertyEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XReparentEvent {
@@ -976,7 +1027,6 @@ impl From<XEvent> for XReparentEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XResizeRequestEvent {
@@ -995,7 +1045,6 @@ impl From<XEvent> for XResizeRequestEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XSelectionClearEvent {
@@ -1014,7 +1063,6 @@ impl From<XEvent> for XSelectionClearEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XSelectionEvent {
@@ -1035,7 +1083,6 @@ impl From<XEvent> for XSelectionEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XSelectionRequestEvent {
@@ -1057,7 +1104,6 @@ impl From<XEvent> for XSelectionRequestEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XUnmapEvent {
@@ -1076,7 +1122,6 @@ impl From<XEvent> for XUnmapEvent {
   }
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XVisibilityEvent {
@@ -1100,7 +1145,6 @@ impl From<XEvent> for XVisibilityEvent {
 //
 
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XClassHint {
@@ -1156,7 +1200,6 @@ pub struct XGCValues {
   pub dashes: c_char,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XHostAddress {
@@ -1165,7 +1208,6 @@ pub struct XHostAddress {
   pub address: *const c_char,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XImage {
@@ -1188,7 +1230,6 @@ pub struct XImage {
   pub funcs: ImageFns,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XModifierKeymap {
@@ -1246,7 +1287,6 @@ pub struct XSizeHints {
   pub win_gravity: c_int,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XTextProperty {
@@ -1256,7 +1296,6 @@ pub struct XTextProperty {
   pub nitems: c_ulong,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XVisualInfo {
@@ -1272,7 +1311,6 @@ pub struct XVisualInfo {
   pub bits_per_rgb: c_int,
 }
 
-#[allow(raw_pointer_derive)]
 #[derive(Clone, Copy, PartialEq)]
 #[repr(C)]
 pub struct XWindowAttributes {
@@ -1301,6 +1339,32 @@ pub struct XWindowAttributes {
   pub screen: *mut Screen,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XWindowChanges {
+  pub x: c_int,
+  pub y: c_int,
+  pub width: c_int,
+  pub height: c_int,
+  pub border_width: c_int,
+  pub sibling: Window,
+  pub stack_mode: c_int,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XWMHints {
+  pub flags: c_long,
+  pub input: Bool,
+  pub initial_state: c_int,
+  pub icon_pixmap: Pixmap,
+  pub icon_window: Window,
+  pub icon_x: c_int,
+  pub icon_y: c_int,
+  pub icon_mask: Pixmap,
+  pub window_group: XID,
+}
+
 
 //
 // anonymous structures
@@ -1393,7 +1457,18 @@ impl PartialEq for ImageFns {
 pub const AllocNone: c_int = 0;
 pub const AllocAll: c_int = 1;
 
+// allow events modes
+pub const AsyncPointer: c_int = 0;
+pub const SyncPointer: c_int = 1;
+pub const ReplayPointer: c_int = 2;
+pub const AsyncKeyboard: c_int = 3;
+pub const SyncKeyboard: c_int = 4;
+pub const ReplayKeyboard: c_int = 5;
+pub const AsyncBoth: c_int = 6;
+pub const SyncBoth: c_int = 7;
+
 // atoms
+pub const AnyPropertyType: Atom = 0;
 pub const XA_PRIMARY: Atom = 1;
 pub const XA_SECONDARY: Atom = 2;
 pub const XA_ARC: Atom = 3;
@@ -1467,17 +1542,116 @@ pub const XA_WM_TRANSIENT_FOR: Atom = 68;
 pub const False: Bool = 0;
 pub const True: Bool = 1;
 
+// circulation directions
+pub const RaiseLowest: c_int = 0;
+pub const LowerHighest: c_int = 1;
+
 // clip rect ordering
 pub const Unsorted: c_int = 0;
 pub const YSorted: c_int = 1;
 pub const YXSorted: c_int = 2;
 pub const YXBanded: c_int = 3;
 
+// close down modes
+pub const DestroyAll: c_int = 0;
+pub const RetainPermanent: c_int = 1;
+pub const RetainTemporary: c_int = 2;
+pub const AllTemporary: XID = 0;
+
 // color component mask
 pub const DoRed: c_char = 1;
 pub const DoGreen: c_char = 2;
 pub const DoBlue: c_char = 4;
 
+// configure window mask
+pub const CWX: c_uint = 0x0001;
+pub const CWY: c_uint = 0x0002;
+pub const CWWidth: c_uint = 0x0004;
+pub const CWHeight: c_uint = 0x0008;
+pub const CWBorderWidth: c_uint = 0x0010;
+pub const CWSibling: c_uint = 0x0020;
+pub const CWStackMode: c_uint = 0x0040;
+
+// cursor font
+pub const XC_num_glyphs: c_uint = 154;
+pub const XC_X_cursor: c_uint = 0;
+pub const XC_arrow: c_uint = 2;
+pub const XC_based_arrow_down: c_uint = 4;
+pub const XC_based_arrow_up: c_uint = 6;
+pub const XC_boat: c_uint = 8;
+pub const XC_bogosity: c_uint = 10;
+pub const XC_bottom_left_corner: c_uint = 12;
+pub const XC_bottom_right_corner: c_uint = 14;
+pub const XC_bottom_side: c_uint = 16;
+pub const XC_bottom_tee: c_uint = 18;
+pub const XC_box_spiral: c_uint = 20;
+pub const XC_center_ptr: c_uint = 22;
+pub const XC_circle: c_uint = 24;
+pub const XC_clock: c_uint = 26;
+pub const XC_coffee_mug: c_uint = 28;
+pub const XC_cross: c_uint = 30;
+pub const XC_cross_reverse: c_uint = 32;
+pub const XC_crosshair: c_uint = 34;
+pub const XC_diamond_cross: c_uint = 36;
+pub const XC_dot: c_uint = 38;
+pub const XC_dotbox: c_uint = 40;
+pub const XC_double_arrow: c_uint = 42;
+pub const XC_draft_large: c_uint = 44;
+pub const XC_draft_small: c_uint = 46;
+pub const XC_draped_box: c_uint = 48;
+pub const XC_exchange: c_uint = 50;
+pub const XC_fleur: c_uint = 52;
+pub const XC_gobbler: c_uint = 54;
+pub const XC_gumby: c_uint = 56;
+pub const XC_hand1: c_uint = 58;
+pub const XC_hand2: c_uint = 60;
+pub const XC_heart: c_uint = 62;
+pub const XC_icon: c_uint = 64;
+pub const XC_iron_cross: c_uint = 66;
+pub const XC_left_ptr: c_uint = 68;
+pub const XC_left_side: c_uint = 70;
+pub const XC_left_tee: c_uint = 72;
+pub const XC_leftbutton: c_uint = 74;
+pub const XC_ll_angle: c_uint = 76;
+pub const XC_lr_angle: c_uint = 78;
+pub const XC_man: c_uint = 80;
+pub const XC_middlebutton: c_uint = 82;
+pub const XC_mouse: c_uint = 84;
+pub const XC_pencil: c_uint = 86;
+pub const XC_pirate: c_uint = 88;
+pub const XC_plus: c_uint = 90;
+pub const XC_question_arrow: c_uint = 92;
+pub const XC_right_ptr: c_uint = 94;
+pub const XC_right_side: c_uint = 96;
+pub const XC_right_tee: c_uint = 98;
+pub const XC_rightbutton: c_uint = 100;
+pub const XC_rtl_logo: c_uint = 102;
+pub const XC_sailboat: c_uint = 104;
+pub const XC_sb_down_arrow: c_uint = 106;
+pub const XC_sb_h_double_arrow: c_uint = 108;
+pub const XC_sb_left_arrow: c_uint = 110;
+pub const XC_sb_right_arrow: c_uint = 112;
+pub const XC_sb_up_arrow: c_uint = 114;
+pub const XC_sb_v_double_arrow: c_uint = 116;
+pub const XC_shuttle: c_uint = 118;
+pub const XC_sizing: c_uint = 120;
+pub const XC_spider: c_uint = 122;
+pub const XC_spraycan: c_uint = 124;
+pub const XC_star: c_uint = 126;
+pub const XC_target: c_uint = 128;
+pub const XC_tcross: c_uint = 130;
+pub const XC_top_left_arrow: c_uint = 132;
+pub const XC_top_left_corner: c_uint = 134;
+pub const XC_top_right_corner: c_uint = 136;
+pub const XC_top_side: c_uint = 138;
+pub const XC_top_tee: c_uint = 140;
+pub const XC_trek: c_uint = 142;
+pub const XC_ul_angle: c_uint = 144;
+pub const XC_umbrella: c_uint = 146;
+pub const XC_ur_angle: c_uint = 148;
+pub const XC_watch: c_uint = 150;
+pub const XC_xterm: c_uint = 152;
+
 // error codes
 pub const Success: c_int = 0;
 pub const BadRequest: c_int = 1;
@@ -1574,11 +1748,47 @@ pub const GrabInvalidTime: c_int = 2;
 pub const GrabNotViewable: c_int = 3;
 pub const GrabFrozen: c_int = 4;
 
+// grab wildcards
+pub const AnyKey: c_int = 0;
+pub const AnyButton: c_uint = 0;
+
+// input focus
+pub const PointerRoot: Window = 1;
+pub const RevertToNone: c_int = 0;
+pub const RevertToPointerRoot: c_int = 1;
+pub const RevertToParent: c_int = 2;
+
 // map state
 pub const IsUnmapped: c_int = 0;
 pub const IsUnviewable: c_int = 1;
 pub const IsViewable: c_int = 2;
 
+// modifier map indices
+pub const ShiftMapIndex: c_int = 0;
+pub const LockMapIndex: c_int = 1;
+pub const ControlMapIndex: c_int = 2;
+pub const Mod1MapIndex: c_int = 3;
+pub const Mod2MapIndex: c_int = 4;
+pub const Mod3MapIndex: c_int = 5;
+pub const Mod4MapIndex: c_int = 6;
+pub const Mod5MapIndex: c_int = 7;
+
+// modifier masks
+pub const ShiftMask: c_uint = 0x0001;
+pub const LockMask: c_uint = 0x0002;
+pub const ControlMask: c_uint = 0x0004;
+pub const Mod1Mask: c_uint = 0x0008;
+pub const Mod2Mask: c_uint = 0x0010;
+pub const Mod3Mask: c_uint = 0x0020;
+pub const Mod4Mask: c_uint = 0x0040;
+pub const Mod5Mask: c_uint = 0x0080;
+pub const Button1Mask: c_uint = 0x0100;
+pub const Button2Mask: c_uint = 0x0200;
+pub const Button3Mask: c_uint = 0x0400;
+pub const Button4Mask: c_uint = 0x0800;
+pub const Button5Mask: c_uint = 0x1000;
+pub const AnyModifier: c_uint = 0x8000;
+
 // mouse buttons
 pub const Button1: c_int = 1;
 pub const Button2: c_int = 2;
@@ -1586,6 +1796,19 @@ pub const Button3: c_int = 3;
 pub const Button4: c_int = 4;
 pub const Button5: c_int = 5;
 
+// property modes
+pub const PropModeReplace: c_int = 0;
+pub const PropModePrepend: c_int = 1;
+pub const PropModeAppend: c_int = 2;
+
+// property notification
+pub const PropertyNewValue: c_int = 0;
+pub const PropertyDelete: c_int = 1;
+
+// save set modes
+pub const SetModeInsert: c_int = 0;
+pub const SetModeDelete: c_int = 1;
+
 // size hints mask
 pub const USPosition: c_long = 0x0001;
 pub const USSize: c_long = 0x0002;
@@ -1599,6 +1822,13 @@ pub const PBaseSize: c_long = 0x0100;
 pub const PWinGravity: c_long = 0x0200;
 pub const PAllHints: c_long = PPosition | PSize | PMinSize | PMaxSize | PResizeInc | PAspect;
 
+// stacking modes
+pub const Above: c_int = 0;
+pub const Below: c_int = 1;
+pub const TopIf: c_int = 2;
+pub const BottomIf: c_int = 3;
+pub const Opposite: c_int = 4;
+
 // time constants
 pub const CurrentTime: Time = 0;
 
@@ -1644,6 +1874,22 @@ pub const CWCursor: c_ulong = 0x4000;
 pub const InputOutput: c_int = 1;
 pub const InputOnly: c_int = 2;
 
+// wm hints mask
+pub const InputHint: c_long = 0x0001;
+pub const StateHint: c_long = 0x0002;
+pub const IconPixmapHint: c_long = 0x0004;
+pub const IconWindowHint: c_long = 0x0008;
+pub const IconPositionHint: c_long = 0x0010;
+pub const IconMaskHint: c_long = 0x0020;
+pub const WindowGroupHint: c_long = 0x0040;
+pub const AllHints: c_long = InputHint | StateHint | IconPixmapHint | IconWindowHint | IconPositionHint | IconMaskHint | WindowGroupHint;
+pub const XUrgencyHint: c_long = 0x0100;
+
+// wm states
+pub const WithdrawnState: c_int = 0;
+pub const NormalState: c_int = 1;
+pub const IconicState: c_int = 3;
+
 // XCreateIC values
 pub const XIMPreeditArea: c_int = 0x0001;
 pub const XIMPreeditCallbacks: c_int = 0x0002;
@@ -1665,13 +1911,13 @@ unsafe fn mem_eq<T: Sized> (a: &T, b: &T) -> bool {
   let a_addr = a as *const T as usize;
   let b_addr = b as *const T as usize;
 
-  for i in (0..size_of::<T>()) {
+  for i in 0..size_of::<T>() {
     if *((a_addr + i) as *const u8) != *((b_addr + i) as *const u8) {
       return false;
     }
   }
 
-  return true;
+  true
 }
 
 unsafe fn transmute_union<I, O> (input: &I) -> O
@@ -1684,5 +1930,116 @@ unsafe fn transmute_union<I, O> (input: &I) -> O
     *((&mut output as *mut O as usize + i) as *mut u8) = *((input as *const I as usize + i) as *const u8);
   }
 
-  return output;
+  output
+}
+
+// Plays the window manager itself, so it needs an X server without one, such
+// as Xvfb; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn reparenting_wm_test () {
+  use std::ptr::{
+    null,
+    null_mut,
+  };
+  use std::thread::sleep;
+  use std::time::{
+    Duration,
+    Instant,
+  };
+  use ::error::ErrorTrap;
+
+  unsafe {
+    let wm = XOpenDisplay(null());
+    assert!(!wm.is_null(), "cannot open display");
+    let client = XOpenDisplay(null());
+    assert!(!client.is_null(), "cannot open a second connection");
+    let root = XDefaultRootWindow(wm);
+
+    // Only one client may redirect the root's children.
+    let trap = ErrorTrap::new(wm);
+    XSelectInput(wm, root, SubstructureRedirectMask | SubstructureNotifyMask);
+    assert!(trap.finish().is_ok(), "another window manager is running");
+
+    let window = XCreateSimpleWindow(client, XDefaultRootWindow(client), 10, 10, 100, 80, 0, 0, 0);
+    XSelectInput(client, window, StructureNotifyMask);
+    XMapWindow(client, window);
+    XSync(client, False);
+    XSync(wm, False);
+
+    let mut event: XEvent = zeroed();
+    assert!(XCheckTypedWindowEvent(wm, root, MapRequest, &mut event) != 0);
+    assert_eq!(XMapRequestEvent::from(event).window, window);
+
+    let frame = XCreateSimpleWindow(wm, root, 10, 10, 102, 101, 1, 0, 0);
+    XSelectInput(wm, frame, SubstructureRedirectMask | SubstructureNotifyMask);
+    XChangeSaveSet(wm, window, SetModeInsert);
+    XReparentWindow(wm, window, frame, 1, 20);
+    XSetWindowBorder(wm, frame, 0x00ff_ffff);
+    XSetWindowBorderWidth(wm, frame, 2);
+    XMapWindow(wm, frame);
+    XMapWindow(wm, window);
+    XSync(wm, False);
+
+    let (mut root_return, mut parent, mut children, mut nchildren) = (0, 0, null_mut(), 0);
+    XQueryTree(client, window, &mut root_return, &mut parent, &mut children, &mut nchildren);
+    if !children.is_null() {
+      XFree(children as *mut c_void);
+    }
+    assert_eq!(parent, frame);
+
+    // The client's resize is redirected to the frame and granted by the WM.
+    XResizeWindow(client, window, 200, 150);
+    XSync(client, False);
+    XSync(wm, False);
+    assert!(XCheckTypedWindowEvent(wm, frame, ConfigureRequest, &mut event) != 0);
+    let request = XConfigureRequestEvent::from(event);
+    assert_eq!((request.window, request.width, request.height), (window, 200, 150));
+    assert_eq!(request.value_mask, (CWWidth | CWHeight) as c_ulong);
+
+    let changes = XWindowChanges {
+      x: 1,
+      y: 20,
+      width: request.width,
+      height: request.height,
+      border_width: 0,
+      sibling: 0,
+      stack_mode: Above,
+    };
+    XConfigureWindow(wm, window, CWX | CWY | CWWidth | CWHeight | CWBorderWidth, &changes);
+    XMoveResizeWindow(wm, frame, 10, 10, 202, 171);
+    XRaiseWindow(wm, frame);
+    XSetInputFocus(wm, window, RevertToPointerRoot, CurrentTime);
+    XSync(wm, False);
+
+    let (mut x, mut y, mut width, mut height, mut border_width, mut depth) = (0, 0, 0, 0, 0, 0);
+    XGetGeometry(client, window, &mut root_return, &mut x, &mut y, &mut width, &mut height, &mut border_width, &mut depth);
+    assert_eq!((x, y, width, height), (1, 20, 200, 150));
+
+    let (mut focus, mut revert_to) = (0, 0);
+    XGetInputFocus(client, &mut focus, &mut revert_to);
+    assert_eq!((focus, revert_to), (window, RevertToPointerRoot));
+
+    // The save-set hands the client back to the root when the WM goes away.
+    // The server tears the connection down on its own time, so wait for the
+    // ReparentNotify rather than a round trip.
+    XCloseDisplay(wm);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+      XSync(client, False);
+      if XCheckTypedWindowEvent(client, window, ReparentNotify, &mut event) != 0 && XReparentEvent::from(event).parent == root {
+        break;
+      }
+      assert!(Instant::now() < deadline, "timed out waiting for the save-set");
+      sleep(Duration::from_millis(10));
+    }
+    XQueryTree(client, window, &mut root_return, &mut parent, &mut children, &mut nchildren);
+    if !children.is_null() {
+      XFree(children as *mut c_void);
+    }
+    assert_eq!(parent, root_return);
+
+    XDestroyWindow(client, window);
+    XCloseDisplay(client);
+  }
 }
@@ -0,0 +1,100 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_int,
+  c_short,
+  c_ushort,
+};
+
+use ::xlib::{
+  Bool,
+  Display,
+  Drawable,
+  Status,
+  Time,
+  Window,
+  XEvent,
+};
+
+
+//
+// functions
+//
+
+
+#[link(name="Xrandr")]
+extern "C" {
+  pub fn XRRConfigCurrentConfiguration (config: *mut XRRScreenConfiguration, rotation: *mut Rotation) -> SizeID;
+  pub fn XRRConfigCurrentRate (config: *mut XRRScreenConfiguration) -> c_short;
+  pub fn XRRConfigRates (config: *mut XRRScreenConfiguration, size_id: c_int, nrates: *mut c_int) -> *mut c_short;
+  pub fn XRRConfigRotations (config: *mut XRRScreenConfiguration, current_rotation: *mut Rotation) -> Rotation;
+  pub fn XRRConfigSizes (config: *mut XRRScreenConfiguration, nsizes: *mut c_int) -> *mut XRRScreenSize;
+  pub fn XRRConfigTimes (config: *mut XRRScreenConfiguration, config_timestamp: *mut Time) -> Time;
+  pub fn XRRFreeScreenConfigInfo (config: *mut XRRScreenConfiguration);
+  pub fn XRRGetScreenInfo (display: *mut Display, window: Window) -> *mut XRRScreenConfiguration;
+  pub fn XRRQueryExtension (display: *mut Display, event_base_return: *mut c_int, error_base_return: *mut c_int) -> Bool;
+  pub fn XRRQueryVersion (display: *mut Display, major_version_return: *mut c_int, minor_version_return: *mut c_int) -> Status;
+  pub fn XRRRates (display: *mut Display, screen: c_int, size_id: c_int, nrates: *mut c_int) -> *mut c_short;
+  pub fn XRRRootToScreen (display: *mut Display, root: Window) -> c_int;
+  pub fn XRRRotations (display: *mut Display, screen: c_int, current_rotation: *mut Rotation) -> Rotation;
+  pub fn XRRSelectInput (display: *mut Display, window: Window, mask: c_int);
+  pub fn XRRSetScreenConfig (display: *mut Display, config: *mut XRRScreenConfiguration, draw: Drawable, size_index: c_int, rotation: Rotation, timestamp: Time) -> Status;
+  pub fn XRRSetScreenConfigAndRate (display: *mut Display, config: *mut XRRScreenConfiguration, draw: Drawable, size_index: c_int, rotation: Rotation, rate: c_short, timestamp: Time) -> Status;
+  pub fn XRRSizes (display: *mut Display, screen: c_int, nsizes: *mut c_int) -> *mut XRRScreenSize;
+  pub fn XRRTimes (display: *mut Display, screen: c_int, config_timestamp: *mut Time) -> Time;
+  pub fn XRRUpdateConfiguration (event: *mut XEvent) -> c_int;
+}
+
+
+//
+// types
+//
+
+
+pub type Rotation = c_ushort;
+pub type SizeID = c_ushort;
+pub type SubpixelOrder = c_ushort;
+
+// opaque pointers
+#[allow(missing_copy_implementations)]
+#[repr(C)]
+pub struct XRRScreenConfiguration { _private: [u8; 0] }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct XRRScreenSize {
+  pub width: c_int,
+  pub height: c_int,
+  pub mwidth: c_int,
+  pub mheight: c_int,
+}
+
+
+//
+// constants
+//
+
+
+// event masks
+pub const RRScreenChangeNotifyMask: c_int = 0x0000_0001;
+
+// event types
+pub const RRScreenChangeNotify: c_int = 0;
+
+// rotations
+pub const RR_Rotate_0: Rotation = 0x0001;
+pub const RR_Rotate_90: Rotation = 0x0002;
+pub const RR_Rotate_180: Rotation = 0x0004;
+pub const RR_Rotate_270: Rotation = 0x0008;
+
+// reflections
+pub const RR_Reflect_X: Rotation = 0x0010;
+pub const RR_Reflect_Y: Rotation = 0x0020;
+
+// set config statuses
+pub const RRSetConfigSuccess: Status = 0;
+pub const RRSetConfigInvalidConfigTime: Status = 1;
+pub const RRSetConfigInvalidTime: Status = 2;
+pub const RRSetConfigFailed: Status = 3;
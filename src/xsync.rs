@@ -0,0 +1,111 @@
+// x11-rs: Rust bindings for X11 libraries
+// The X11 libraries are available under the MIT license.
+// These bindings are public domain.
+
+use libc::{
+  c_char,
+  c_int,
+  c_uint,
+};
+
+use ::xlib::{
+  Bool,
+  Display,
+  Status,
+  XID,
+};
+
+
+//
+// functions
+//
+
+
+#[link(name="Xext")]
+extern "C" {
+  pub fn XSyncChangeCounter (display: *mut Display, counter: XSyncCounter, value: XSyncValue) -> Status;
+  pub fn XSyncCreateCounter (display: *mut Display, initial_value: XSyncValue) -> XSyncCounter;
+  pub fn XSyncDestroyCounter (display: *mut Display, counter: XSyncCounter) -> Status;
+  pub fn XSyncFreeSystemCounterList (list: *mut XSyncSystemCounter);
+  pub fn XSyncGetPriority (display: *mut Display, client_resource_id: XID, return_priority: *mut c_int) -> Status;
+  pub fn XSyncInitialize (display: *mut Display, major_version_return: *mut c_int, minor_version_return: *mut c_int) -> Status;
+  pub fn XSyncIntToValue (value: *mut XSyncValue, i: c_int);
+  pub fn XSyncIntsToValue (value: *mut XSyncValue, low: c_uint, high: c_int);
+  pub fn XSyncListSystemCounters (display: *mut Display, n_counters_return: *mut c_int) -> *mut XSyncSystemCounter;
+  pub fn XSyncMaxValue (value: *mut XSyncValue);
+  pub fn XSyncMinValue (value: *mut XSyncValue);
+  pub fn XSyncQueryCounter (display: *mut Display, counter: XSyncCounter, value_return: *mut XSyncValue) -> Status;
+  pub fn XSyncQueryExtension (display: *mut Display, event_base_return: *mut c_int, error_base_return: *mut c_int) -> Bool;
+  pub fn XSyncSetCounter (display: *mut Display, counter: XSyncCounter, value: XSyncValue) -> Status;
+  pub fn XSyncSetPriority (display: *mut Display, client_resource_id: XID, priority: c_int) -> Status;
+  pub fn XSyncValueAdd (result: *mut XSyncValue, a: XSyncValue, b: XSyncValue, overflow: *mut Bool);
+  pub fn XSyncValueEqual (a: XSyncValue, b: XSyncValue) -> Bool;
+  pub fn XSyncValueGreaterOrEqual (a: XSyncValue, b: XSyncValue) -> Bool;
+  pub fn XSyncValueGreaterThan (a: XSyncValue, b: XSyncValue) -> Bool;
+  pub fn XSyncValueHigh32 (value: XSyncValue) -> c_int;
+  pub fn XSyncValueIsNegative (value: XSyncValue) -> Bool;
+  pub fn XSyncValueIsPositive (value: XSyncValue) -> Bool;
+  pub fn XSyncValueIsZero (value: XSyncValue) -> Bool;
+  pub fn XSyncValueLessOrEqual (a: XSyncValue, b: XSyncValue) -> Bool;
+  pub fn XSyncValueLessThan (a: XSyncValue, b: XSyncValue) -> Bool;
+  pub fn XSyncValueLow32 (value: XSyncValue) -> c_uint;
+  pub fn XSyncValueSubtract (result: *mut XSyncValue, a: XSyncValue, b: XSyncValue, overflow: *mut Bool);
+}
+
+
+//
+// types
+//
+
+
+pub type XSyncCounter = XID;
+
+#[derive(Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct XSyncSystemCounter {
+  pub name: *mut c_char,
+  pub counter: XSyncCounter,
+  pub resolution: XSyncValue,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct XSyncValue {
+  pub hi: c_int,
+  pub lo: c_uint,
+}
+
+impl From<i64> for XSyncValue {
+  fn from (value: i64) -> XSyncValue {
+    XSyncValue { hi: (value >> 32) as c_int, lo: value as c_uint }
+  }
+}
+
+impl From<XSyncValue> for i64 {
+  fn from (value: XSyncValue) -> i64 {
+    ((value.hi as i64) << 32) | value.lo as i64
+  }
+}
+
+
+//
+// constants
+//
+
+
+// event types
+pub const XSyncCounterNotify: c_int = 0;
+pub const XSyncAlarmNotify: c_int = 1;
+
+// versions
+pub const SYNC_MAJOR_VERSION: c_int = 3;
+pub const SYNC_MINOR_VERSION: c_int = 1;
+
+#[test]
+fn sync_value_test () {
+  for &value in [0, 1, -1, 0x1_0000_0000, -0x1_2345_6789, i64::MAX, i64::MIN].iter() {
+    assert_eq!(i64::from(XSyncValue::from(value)), value);
+  }
+  assert_eq!(XSyncValue::from(0x2_0000_0005), XSyncValue { hi: 2, lo: 5 });
+  assert_eq!(XSyncValue::from(-1), XSyncValue { hi: -1, lo: 0xffff_ffff });
+}